@@ -0,0 +1,389 @@
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use crate::config::WalterConfig;
+
+const CACHE_DIR: &str = "~/.walter/cache";
+const STATS_FILE: &str = "stats.json";
+const DIGEST_EXTENSION: &str = "sha256";
+// Lookups counted in memory before they are added to the stats file
+const FLUSH_EVERY: u64 = 64;
+
+/// Hits and misses since the cache was last cleared.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+struct Counters {
+    hits: u64,
+    misses: u64,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct CacheStats {
+    pub dir: String,
+    pub blobs: usize,
+    pub size: u64,
+    pub max_size: u64,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl CacheStats {
+    pub fn describe(&self) -> String {
+        let lookups = self.hits + self.misses;
+        let hit_rate = if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64 * 100.0
+        };
+
+        format!(
+            "{}: {} blobs, {:.1} of {:.1} MiB, {} hits and {} misses ({:.0}% hit rate)",
+            self.dir,
+            self.blobs,
+            self.size as f64 / (1024.0 * 1024.0),
+            self.max_size as f64 / (1024.0 * 1024.0),
+            self.hits,
+            self.misses,
+            hit_rate
+        )
+    }
+}
+
+/// Blobs read from Walrus kept on disk by blob ID, so reading a blob again
+/// doesn't download it again. Blob contents never change under an ID, each
+/// one is stored with its SHA-256 and checked against it on every hit. The
+/// least recently read blobs are dropped once the cache grows past `max_size`.
+///
+/// Clones share their hit and miss counters, build the cache once and hand
+/// clones to whatever reads blobs.
+#[derive(Clone)]
+pub struct BlobCache {
+    pub dir: PathBuf,
+    pub max_size: u64,
+    pending: Arc<Mutex<Counters>>,
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    openssl::sha::sha256(data)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+// Blob IDs are URL safe base64, anything else could escape the cache directory
+fn is_blob_id(blob_id: &str) -> bool {
+    !blob_id.is_empty()
+        && blob_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+impl BlobCache {
+    pub fn new(max_size: u64) -> Self {
+        BlobCache::with_dir(
+            PathBuf::from(shellexpand::tilde(CACHE_DIR).to_string()),
+            max_size,
+        )
+    }
+
+    pub fn with_dir(dir: PathBuf, max_size: u64) -> Self {
+        BlobCache {
+            dir,
+            max_size,
+            pending: Arc::new(Mutex::new(Counters::default())),
+        }
+    }
+
+    /// The cache with the size limit from `config`.
+    pub fn from_config(config: &WalterConfig) -> Self {
+        BlobCache::new(config.get_cache_max_size())
+    }
+
+    /// The cache with the size limit from the config file.
+    pub fn open() -> Self {
+        BlobCache::from_config(&WalterConfig::load_config_file())
+    }
+
+    fn blob_path(&self, blob_id: &str) -> PathBuf {
+        self.dir.join(blob_id)
+    }
+
+    fn digest_path(&self, blob_id: &str) -> PathBuf {
+        self.dir.join(format!("{}.{}", blob_id, DIGEST_EXTENSION))
+    }
+
+    fn counters(&self) -> Counters {
+        fs::read_to_string(self.dir.join(STATS_FILE))
+            .ok()
+            .and_then(|stats| serde_json::from_str(&stats).ok())
+            .unwrap_or_default()
+    }
+
+    fn count(&self, hit: bool) {
+        let mut pending = self.pending.lock().unwrap();
+        if hit {
+            pending.hits += 1;
+        } else {
+            pending.misses += 1;
+        }
+
+        if pending.hits + pending.misses >= FLUSH_EVERY {
+            self.write_counters(&mut pending);
+        }
+    }
+
+    // Best effort, processes sharing the cache may lose each other's counts
+    fn write_counters(&self, pending: &mut Counters) {
+        if pending.hits + pending.misses == 0 {
+            return;
+        }
+
+        let mut counters = self.counters();
+        counters.hits += pending.hits;
+        counters.misses += pending.misses;
+        *pending = Counters::default();
+
+        if let Ok(stats) = serde_json::to_string(&counters) {
+            let _ = fs::create_dir_all(&self.dir);
+            let _ = fs::write(self.dir.join(STATS_FILE), stats);
+        }
+    }
+
+    /// Adds the lookups counted in memory to the stats file.
+    pub fn flush(&self) {
+        self.write_counters(&mut self.pending.lock().unwrap());
+    }
+
+    /// The cached contents of a blob. A blob that no longer matches its
+    /// digest is dropped and counts as a miss.
+    pub fn get(&self, blob_id: &str) -> Option<Vec<u8>> {
+        if !is_blob_id(blob_id) {
+            return None;
+        }
+
+        let path = self.blob_path(blob_id);
+        let cached = fs::read(&path)
+            .ok()
+            .zip(fs::read_to_string(self.digest_path(blob_id)).ok());
+        let data = match cached {
+            Some((data, digest)) if sha256_hex(&data) == digest.trim() => data,
+            Some(_) => {
+                self.remove(blob_id);
+                self.count(false);
+                return None;
+            }
+            None => {
+                self.count(false);
+                return None;
+            }
+        };
+
+        // The modification time doubles as the last time the blob was read
+        if let Ok(file) = fs::File::options().write(true).open(&path) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+        self.count(true);
+        Some(data)
+    }
+
+    /// Same as `get`, hashing and reading the blob on the blocking thread pool.
+    pub async fn load(&self, blob_id: &str) -> Option<Vec<u8>> {
+        let (cache, blob_id) = (self.clone(), blob_id.to_string());
+        tokio::task::spawn_blocking(move || cache.get(&blob_id))
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// Writes the cached contents of a blob to `out`, false when it isn't cached.
+    pub fn copy_to(&self, blob_id: &str, out: &Path) -> bool {
+        match self.get(blob_id) {
+            Some(data) => fs::write(out, data).is_ok(),
+            None => false,
+        }
+    }
+
+    pub fn insert(&self, blob_id: &str, data: &[u8]) -> Result<(), Box<dyn Error>> {
+        // A blob that can't fit would only push everything else out
+        if !is_blob_id(blob_id) || data.len() as u64 > self.max_size {
+            return Ok(());
+        }
+        fs::create_dir_all(&self.dir)?;
+
+        // The digest goes first, a blob without one is never read
+        fs::write(self.digest_path(blob_id), sha256_hex(data))?;
+        let temp_path = self.dir.join(format!(".{}.part", blob_id));
+        fs::write(&temp_path, data)?;
+        fs::rename(&temp_path, self.blob_path(blob_id))?;
+
+        self.evict()
+    }
+
+    pub fn insert_file(&self, blob_id: &str, path: &Path) -> Result<(), Box<dyn Error>> {
+        self.insert(blob_id, &fs::read(path)?)
+    }
+
+    /// Reads the blob at `path` and caches it on the blocking thread pool,
+    /// returning its contents. Failing to cache it isn't an error.
+    pub async fn load_file(&self, blob_id: &str, path: &Path) -> std::io::Result<Vec<u8>> {
+        let (cache, blob_id, path) = (self.clone(), blob_id.to_string(), path.to_path_buf());
+        tokio::task::spawn_blocking(move || {
+            let data = fs::read(&path)?;
+            let _ = cache.insert(&blob_id, &data);
+            Ok(data)
+        })
+        .await?
+    }
+
+    pub fn remove(&self, blob_id: &str) {
+        if is_blob_id(blob_id) {
+            let _ = fs::remove_file(self.blob_path(blob_id));
+            let _ = fs::remove_file(self.digest_path(blob_id));
+        }
+    }
+
+    // Every cached blob with when it was last read and its size, oldest first
+    fn blobs(&self) -> Vec<(SystemTime, u64, String)> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+
+        let mut blobs: Vec<(SystemTime, u64, String)> = entries
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let blob_id = entry.file_name().to_string_lossy().to_string();
+                let metadata = entry.metadata().ok()?;
+                (metadata.is_file() && is_blob_id(&blob_id)).then_some((
+                    metadata.modified().ok()?,
+                    metadata.len(),
+                    blob_id,
+                ))
+            })
+            .collect();
+        blobs.sort();
+        blobs
+    }
+
+    /// Removes the least recently read blobs until the cache fits `max_size`.
+    pub fn evict(&self) -> Result<(), Box<dyn Error>> {
+        let blobs = self.blobs();
+        let mut size: u64 = blobs.iter().map(|(_, len, _)| len).sum();
+
+        for (_, len, blob_id) in blobs {
+            if size <= self.max_size {
+                break;
+            }
+            self.remove(&blob_id);
+            size -= len;
+        }
+
+        Ok(())
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let blobs = self.blobs();
+        let pending = self.pending.lock().unwrap().clone();
+        let counters = self.counters();
+
+        CacheStats {
+            dir: self.dir.to_string_lossy().to_string(),
+            blobs: blobs.len(),
+            size: blobs.iter().map(|(_, len, _)| len).sum(),
+            max_size: self.max_size,
+            hits: counters.hits + pending.hits,
+            misses: counters.misses + pending.misses,
+        }
+    }
+
+    /// Empties the cache and resets its counters, returning what was in it.
+    pub fn clear(&self) -> Result<CacheStats, Box<dyn Error>> {
+        let stats = self.stats();
+        *self.pending.lock().unwrap() = Counters::default();
+        if self.dir.exists() {
+            fs::remove_dir_all(&self.dir)?;
+        }
+        Ok(stats)
+    }
+}
+
+impl Drop for BlobCache {
+    fn drop(&mut self) {
+        // Only the last clone writes what the clones counted together
+        if Arc::strong_count(&self.pending) == 1 {
+            self.flush();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_blob_cache() {
+        let cache = BlobCache::with_dir(
+            std::env::temp_dir().join(format!("walter_blob_cache_{}", std::process::id())),
+            10,
+        );
+        let _ = fs::remove_dir_all(&cache.dir);
+        let read_at = |blob_id: &str, secs_ago: u64| {
+            fs::File::options()
+                .write(true)
+                .open(cache.blob_path(blob_id))
+                .unwrap()
+                .set_modified(SystemTime::now() - Duration::from_secs(secs_ago))
+                .unwrap();
+        };
+
+        assert_eq!(cache.get("a"), None);
+        cache.insert("a", b"1234").unwrap();
+        cache.insert("b", b"5678").unwrap();
+        read_at("a", 60);
+        read_at("b", 120);
+        assert_eq!(cache.get("b"), Some(b"5678".to_vec()));
+
+        // Exactly full, nothing is dropped yet
+        cache.insert("c", b"90").unwrap();
+        assert!(cache.blob_path("a").exists());
+
+        // Reading "b" made "a" the least recently read blob
+        cache.insert("d", b"xyz").unwrap();
+        assert!(!cache.blob_path("a").exists());
+        assert!(!cache.digest_path("a").exists());
+
+        // Too big to cache at all, and not a blob ID
+        cache.insert("e", b"01234567890").unwrap();
+        cache.insert("../e", b"0").unwrap();
+        assert!(!cache.blob_path("e").exists());
+        assert_eq!(cache.get("../e"), None);
+
+        // A corrupted blob is dropped instead of returned
+        fs::write(cache.blob_path("c"), b"09").unwrap();
+        assert_eq!(cache.get("c"), None);
+        assert!(!cache.blob_path("c").exists());
+
+        let out = std::env::temp_dir().join(format!("walter_blob_out_{}", std::process::id()));
+        assert!(cache.copy_to("d", &out));
+        assert_eq!(fs::read(&out).unwrap(), b"xyz");
+        assert!(!cache.copy_to("a", &out));
+        fs::remove_file(&out).unwrap();
+
+        let stats = cache.stats();
+        assert_eq!((stats.blobs, stats.size), (2, 7));
+        assert_eq!((stats.hits, stats.misses), (2, 3));
+
+        // Counts stay in memory until flushed, and add up across flushes
+        assert!(!cache.dir.join(STATS_FILE).exists());
+        cache.flush();
+        assert_eq!(cache.counters(), Counters { hits: 2, misses: 3 });
+        assert_eq!(cache.stats(), stats);
+
+        assert_eq!(cache.clear().unwrap(), stats);
+        assert_eq!(cache.stats().blobs, 0);
+        assert_eq!(cache.stats().hits, 0);
+    }
+}
@@ -1,55 +1,107 @@
 use std::io::Write;
+use std::path::Path;
 
+use crate::blob_cache::BlobCache;
 use crate::config::WalterConfig;
-use crate::encryptor::{decrypt_file, encrypt_file};
+use crate::encryptor::{decrypt_file, encrypt_file_chunked};
+use crate::reader::read_range;
 use crate::sharder::Sharder;
 use crate::types::*;
 
 pub struct WalrusClient {
     pub config: WalterConfig,
+    pub cache: BlobCache,
 }
 
 impl WalrusClient {
     pub fn new(config: WalterConfig) -> Self {
-        WalrusClient { config }
+        let cache = BlobCache::from_config(&config);
+        WalrusClient { config, cache }
     }
 
     pub async fn upload_file(
         &mut self,
         file_path: &str,
         password: Option<String>,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        let epochs = self.config.get_default_epochs();
+        self.upload_file_with_epochs(file_path, password, epochs)
+            .await
+    }
+
+    pub async fn upload_file_with_epochs(
+        &mut self,
+        file_path: &str,
+        password: Option<String>,
+        epochs: u16,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        let shard_size = self.config.get_default_shard_size();
+        self.upload_file_with_options(file_path, password, epochs, shard_size)
+            .await
+    }
+
+    /// Same as `upload_file_with_epochs`, splitting the file into `shard_size` byte shards.
+    pub async fn upload_file_with_options(
+        &mut self,
+        file_path: &str,
+        password: Option<String>,
+        epochs: u16,
+        shard_size: usize,
     ) -> Result<bool, Box<dyn std::error::Error>> {
         let to_encrypt: bool = password.is_some();
+
+        // Encrypt into a temp file so the original file is left untouched
+        let mut encryption = None;
+        let source_path = if to_encrypt {
+            let encrypted_path = temp_file_path("enc")?;
+            encryption = Some(encrypt_file_chunked(
+                file_path,
+                encrypted_path.to_str().unwrap(),
+                &password.unwrap(),
+            )?);
+            encrypted_path
+        } else {
+            std::path::PathBuf::from(file_path)
+        };
+
+        let size = std::fs::metadata(&source_path)?.len();
+        let result = self
+            .upload_shards(source_path.to_str().unwrap(), epochs, shard_size)
+            .await;
         if to_encrypt {
-            encrypt_file(file_path, file_path, &password.unwrap())?;
+            std::fs::remove_file(&source_path)?;
         }
 
-        let shards = Sharder::new(file_path, self.config.get_default_shard_size())?;
+        self.config.add_file(file_path, to_encrypt, result?);
+        self.config.set_file_layout(file_path, size, shard_size as u64);
+        if let Some(header) = encryption {
+            self.config.set_file_encryption(file_path, header);
+        }
+        self.config.save_config_file();
+        Ok(true)
+    }
+
+    async fn upload_shards(
+        &self,
+        file_path: &str,
+        epochs: u16,
+        shard_size: usize,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let shards = Sharder::new(file_path, shard_size)?;
         let mut blobs: Vec<String> = Vec::new();
 
         for shard in shards {
-            let temp_file_path = std::env::temp_dir().join(format!(
-                "shard_{}.tmp",
-                std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)?
-                    .as_nanos()
-            ));
+            let temp_file_path = temp_file_path("shard")?;
 
             std::fs::write(&temp_file_path, shard)?;
 
-            let blob_id = upload_blob(
-                temp_file_path.to_str().unwrap(),
-                self.config.get_default_epochs(),
-            )
-            .await?;
-
-            blobs.push(blob_id);
+            let blob_id = upload_blob(temp_file_path.to_str().unwrap(), epochs).await;
             std::fs::remove_file(&temp_file_path)?;
+
+            blobs.push(blob_id?);
         }
 
-        self.config.add_file(file_path, to_encrypt, blobs);
-        self.config.save_config_file();
-        Ok(true)
+        Ok(blobs)
     }
 
     pub async fn download_file(
@@ -57,41 +109,107 @@ impl WalrusClient {
         file_path: &str,
         password: Option<String>,
     ) -> Result<bool, Box<dyn std::error::Error>> {
-        let to_decrypt: bool = password.is_some();
-        let blobs = self.config.get_file_blobs(file_path).unwrap();
+        self.download_file_to(file_path, file_path, password).await
+    }
+
+    /// Reads `len` bytes of a registered file starting at `offset`, fetching
+    /// only the shards that hold them.
+    pub async fn read_range(
+        &self,
+        file_path: &str,
+        offset: u64,
+        len: u64,
+        password: Option<String>,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let file_info = self
+            .config
+            .get_files()
+            .get(file_path)
+            .ok_or(format!("{} is not a registered file", file_path))?;
+
+        read_range(
+            &self.cache,
+            file_info,
+            offset..offset.saturating_add(len),
+            password.as_deref(),
+        )
+        .await
+    }
+
+    /// Reassembles a registered file from its blobs and writes it to `out_path`.
+    pub async fn download_file_to(
+        &self,
+        file_path: &str,
+        out_path: &str,
+        password: Option<String>,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        self.download_file_with_progress(file_path, out_path, password, |_, _| {})
+            .await?;
+        Ok(true)
+    }
+
+    /// Same as `download_file_to`, calling `on_shard(done, total)` after each
+    /// shard is read and returning what was written.
+    pub async fn download_file_with_progress<F>(
+        &self,
+        file_path: &str,
+        out_path: &str,
+        password: Option<String>,
+        mut on_shard: F,
+    ) -> Result<DownloadReport, Box<dyn std::error::Error>>
+    where
+        F: FnMut(usize, usize),
+    {
+        let blobs = self
+            .config
+            .get_file_blobs(file_path)
+            .ok_or(format!("{} is not a registered file", file_path))?;
         let mut file_data = Vec::new();
+        let mut shards = Vec::new();
 
         for blob in blobs {
-            let temp_file_path = std::env::current_dir()?.join(format!(
-                "{}.tmp",
-                std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)?
-                    .as_nanos()
-            ));
-
-            let success = download_blob(blob, &temp_file_path.to_str().unwrap()).await;
-            let success = success.unwrap();
-
-            if !success {
-                return Err("Failed to download blob".into());
-            }
+            let shard = read_blob(&self.cache, blob).await?;
 
-            let shard = std::fs::read(&temp_file_path)?;
-            std::fs::remove_file(&temp_file_path)?;
+            shards.push(ShardDownload {
+                blob_id: blob.clone(),
+                size: shard.len() as u64,
+            });
             file_data.extend(shard);
+            on_shard(shards.len(), blobs.len());
         }
 
-        let mut file = std::fs::File::create(file_path)?;
-        file.write_all(&file_data.as_slice())?;
+        let mut file = std::fs::File::create(out_path)?;
+        file.write_all(file_data.as_slice())?;
 
-        if to_decrypt {
-            decrypt_file(file_path, file_path, &password.unwrap())?;
+        if let Some(password) = &password {
+            // Don't leave the ciphertext behind when the password is wrong
+            if let Err(e) = decrypt_file(out_path, out_path, password) {
+                std::fs::remove_file(out_path)?;
+                return Err(format!("Decryption failed: {}", e).into());
+            }
         }
 
-        Ok(true)
+        Ok(DownloadReport {
+            out_path: out_path.to_string(),
+            shards,
+            decrypted: password.is_some(),
+            size: std::fs::metadata(out_path)?.len(),
+        })
     }
 }
 
+// Unique path in the system temp dir, so concurrent operations never share files
+pub(crate) fn temp_file_path(extension: &str) -> Result<std::path::PathBuf, Box<dyn std::error::Error>> {
+    Ok(std::env::temp_dir().join(format!(
+        "walter_{}_{}.{}",
+        std::process::id(),
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_nanos(),
+        extension
+    )))
+}
+
 pub async fn upload_blob(
     file_path: &str,
     epochs: u16,
@@ -105,11 +223,12 @@ pub async fn upload_blob(
         }
     });
 
-    let output = std::process::Command::new("walrus")
+    let output = tokio::process::Command::new("walrus")
         .arg("json")
         .arg(command_json.to_string())
+        .kill_on_drop(true)
         .output()
-        .expect("failed to execute process");
+        .await?;
 
     // che ck if output execution is successful gracefully
     if !output.status.success() {
@@ -141,7 +260,25 @@ pub async fn upload_blob(
     }
 }
 
+/// Reads a blob into `file_location`, from the blob cache when it's there.
 pub async fn download_blob(
+    cache: &BlobCache,
+    blob_id: &str,
+    file_location: &str,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    if let Some(data) = cache.load(blob_id).await {
+        tokio::fs::write(file_location, data).await?;
+        return Ok(true);
+    }
+
+    let success = fetch_blob(blob_id, file_location).await?;
+    // Failing to cache only means the next read downloads the blob again
+    let _ = cache.load_file(blob_id, Path::new(file_location)).await;
+    Ok(success)
+}
+
+// Reads a blob from Walrus, skipping the cache
+async fn fetch_blob(
     blob_id: &str,
     file_location: &str,
 ) -> Result<bool, Box<dyn std::error::Error>> {
@@ -154,11 +291,12 @@ pub async fn download_blob(
         }
     });
 
-    let output = std::process::Command::new("walrus")
+    let output = tokio::process::Command::new("walrus")
         .arg("json")
         .arg(command_json.to_string())
+        .kill_on_drop(true)
         .output()
-        .expect("failed to execute process");
+        .await?;
 
     if !output.status.success() {
         return Err("Failed to download file from walrus".into());
@@ -176,6 +314,206 @@ pub async fn download_blob(
     Ok(true)
 }
 
+/// Reads a blob into memory instead of a file, from the blob cache when it's there.
+pub async fn read_blob(
+    cache: &BlobCache,
+    blob_id: &str,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    if let Some(data) = cache.load(blob_id).await {
+        return Ok(data);
+    }
+
+    let temp_file_path = temp_file_path("tmp")?;
+    // Errors aren't Send, they can't be held across the awaits below
+    let fetched = fetch_blob(blob_id, temp_file_path.to_str().unwrap())
+        .await
+        .map_err(|e| e.to_string());
+    let data = match fetched {
+        Ok(true) => cache
+            .load_file(blob_id, &temp_file_path)
+            .await
+            .map_err(|e| e.to_string()),
+        Ok(false) => Err("Failed to download blob".to_string()),
+        Err(e) => Err(e),
+    };
+    let _ = tokio::fs::remove_file(&temp_file_path).await;
+    Ok(data?)
+}
+
+pub async fn list_blobs(include_expired: bool) -> Result<Vec<BlobInfo>, Box<dyn std::error::Error>> {
+    let command_json = serde_json::json!({
+        "command": {
+            "listBlobs": {
+                "includeExpired": include_expired,
+            },
+        }
+    });
+
+    let output = tokio::process::Command::new("walrus")
+        .arg("json")
+        .arg(command_json.to_string())
+        .kill_on_drop(true)
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to list blobs: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+
+    let output_json = String::from_utf8_lossy(&output.stdout);
+    let blobs: Vec<ListedBlob> = serde_json::from_str(&output_json)
+        .map_err(|e| format!("Failed to parse blob list: {}", e))?;
+
+    Ok(blobs.into_iter().map(BlobInfo::from).collect())
+}
+
+/// Deletes a blob owned by the active address and drops it from `cache`.
+pub async fn delete_blob(
+    cache: &BlobCache,
+    blob_id: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let command_json = serde_json::json!({
+        "command": {
+            "delete": {
+                "blobId": blob_id,
+            },
+        }
+    });
+
+    let output = tokio::process::Command::new("walrus")
+        .arg("json")
+        .arg(command_json.to_string())
+        .kill_on_drop(true)
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to delete blob {}: {}",
+            blob_id,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+
+    cache.remove(blob_id);
+    Ok(())
+}
+
+/// Current epoch, epoch length, prices and size limits of the Walrus system.
+pub async fn walrus_info() -> Result<WalrusInfo, Box<dyn std::error::Error>> {
+    let command_json = serde_json::json!({
+        "command": {
+            "info": {},
+        }
+    });
+
+    let output = tokio::process::Command::new("walrus")
+        .arg("json")
+        .arg(command_json.to_string())
+        .kill_on_drop(true)
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to get Walrus system info: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+
+    let output_json = String::from_utf8_lossy(&output.stdout);
+    let info: InfoOutput = serde_json::from_str(&output_json)
+        .map_err(|e| format!("Failed to parse Walrus system info: {}", e))?;
+
+    Ok(WalrusInfo::from(info))
+}
+
+/// Reads a blob back into a temp file and returns its size, proving it is retrievable.
+/// The blob cache is skipped, a cached copy says nothing about Walrus.
+pub async fn verify_blob(blob_id: &str) -> Result<u64, Box<dyn std::error::Error>> {
+    let temp_file_path = temp_file_path("verify")?;
+    let temp_file = temp_file_path.to_string_lossy().to_string();
+
+    let result = fetch_blob(blob_id, &temp_file).await;
+    let size = std::fs::metadata(&temp_file_path).map(|metadata| metadata.len());
+    let _ = std::fs::remove_file(&temp_file_path);
+
+    if !result? {
+        return Err(format!("Blob {} could not be read", blob_id).into());
+    }
+    Ok(size?)
+}
+
+/// Extends the storage of the blob object `object_id` by `epochs` without
+/// writing it again. Returns false when the installed walrus CLI has no
+/// `extend` command.
+pub async fn extend_blob(object_id: &str, epochs: u16) -> Result<bool, Box<dyn std::error::Error>> {
+    let command_json = serde_json::json!({
+        "command": {
+            "extend": {
+                "blobObjId": object_id,
+                "epochsExtended": epochs,
+            },
+        }
+    });
+
+    let output = tokio::process::Command::new("walrus")
+        .arg("json")
+        .arg(command_json.to_string())
+        .kill_on_drop(true)
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if is_unsupported_command(&stderr) {
+            return Ok(false);
+        }
+
+        return Err(format!(
+            "Failed to extend blob object {}: {}",
+            object_id,
+            stderr.trim()
+        )
+        .into());
+    }
+
+    Ok(true)
+}
+
+// Older walrus releases reject commands they don't know while parsing the JSON
+fn is_unsupported_command(stderr: &str) -> bool {
+    stderr.contains("unknown variant `extend`") || stderr.contains("unrecognized subcommand")
+}
+
+/// Raw output of `walrus blob-status` for a blob.
+pub async fn blob_status(blob_id: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let output = tokio::process::Command::new("walrus")
+        .arg("blob-status")
+        .arg("--blob-id")
+        .arg(blob_id)
+        .kill_on_drop(true)
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to get the status of blob {}: {}",
+            blob_id,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -184,6 +522,7 @@ mod tests {
     #[tokio::test]
     async fn test_download_from_walrus() {
         let output = download_blob(
+            &BlobCache::open(),
             "DVZWz_QCEb2D_UPQzswv-DUqg-etmV6rEPzoERY4Tgg",
             "./test_files/download_test.txt",
         )
@@ -192,6 +531,19 @@ mod tests {
         assert!(output.is_ok());
     }
 
+    #[test]
+    fn test_unsupported_extend_detection() {
+        assert!(is_unsupported_command(
+            "Error: unknown variant `extend`, expected one of `store`, `read`, `info` at line 1 column 19"
+        ));
+        assert!(is_unsupported_command(
+            "error: unrecognized subcommand 'extend'"
+        ));
+        assert!(!is_unsupported_command(
+            "Error: the blob object 0x12 is not owned by the active address"
+        ));
+    }
+
     #[test]
     fn test_already_certified_deserialization() {
         let json = r#"{
@@ -250,6 +602,60 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_list_blobs_deserialization() {
+        let json = r#"[
+                            {
+                                "id": "0x6ddf05fbd44f522a49d1eef75dab70769b986857c192f108bd52ffd1bdb732d4",
+                                "registeredEpoch": 51,
+                                "blobId": "DVZWz_QCEb2D_UPQzswv-DUqg-etmV6rEPzoERY4Tgg",
+                                "size": 46,
+                                "encodingType": "RedStuff",
+                                "certifiedEpoch": 51,
+                                "storage": {
+                                    "id": "0xe9be566bec206862e3807225e1a190700fcfd144250d412f51d2776571050e13",
+                                    "startEpoch": 51,
+                                    "endEpoch": 52,
+                                    "storageSize": 65023000
+                                },
+                                "deletable": false
+                            },
+                            {
+                                "blob": {
+                                    "id": "0x1cb3b2d3c1c2ac2e28fc2a7fa4e1e0b4bb4d2b0d2c0bd5c1a5d0f6ab9a1d5e3f",
+                                    "registeredEpoch": 60,
+                                    "blobId": "WNj9kV-79ScIKYpGmXsBBT0PjjyCeTkZYvUNtwUEr-A",
+                                    "size": 6000000000,
+                                    "encodingType": "RS2",
+                                    "certifiedEpoch": null,
+                                    "storage": {
+                                        "id": "0x4f3e1e0fa9c1b0f1e5b0b2c1d1f2a3b4c5d6e7f8091a2b3c4d5e6f708192a3b4",
+                                        "startEpoch": 60,
+                                        "endEpoch": 70,
+                                        "storageSize": 31000000000
+                                    },
+                                    "deletable": true
+                                },
+                                "attribute": null
+                            }
+                        ]"#;
+
+        let blobs: Vec<ListedBlob> = serde_json::from_str(json).unwrap();
+        let blobs: Vec<BlobInfo> = blobs.into_iter().map(BlobInfo::from).collect();
+
+        assert_eq!(blobs.len(), 2);
+        assert_eq!(blobs[0].blob_id, "DVZWz_QCEb2D_UPQzswv-DUqg-etmV6rEPzoERY4Tgg");
+        assert!(blobs[0].is_certified);
+        assert_eq!(blobs[0].expiration_epoch, 52);
+        assert_eq!(blobs[1].unencoded_size, 6000000000);
+        assert!(!blobs[1].is_certified);
+        assert!(blobs[1].is_deletable);
+
+        let page = BlobPage::from_blobs(blobs, 1, 1);
+        assert_eq!(page.total_pages, 2);
+        assert_eq!(page.blobs[0].blob_id, "WNj9kV-79ScIKYpGmXsBBT0PjjyCeTkZYvUNtwUEr-A");
+    }
+
     #[tokio::test]
     async fn test_upload_to_walrus() {
         let output = upload_blob("test_files/uploadcopy.test", 10).await;
@@ -296,4 +702,38 @@ mod tests {
             .await;
         assert!(output.is_ok());
     }
+
+    #[test]
+    fn test_walrus_info_deserialization() {
+        let json = r#"{
+                            "storageInfo": { "nShards": 1000, "nNodes": 25 },
+                            "epochInfo": {
+                                "currentEpoch": 120,
+                                "startOfCurrentEpoch": { "DateTime": "2025-01-20T10:00:00Z" },
+                                "epochDuration": { "secs": 86400, "nanos": 0 },
+                                "maxEpochsAhead": 183
+                            },
+                            "sizeInfo": { "storageUnitSize": 1048576, "maxBlobSize": 14599692288 },
+                            "priceInfo": {
+                                "storagePricePerUnitSize": 100000,
+                                "writePricePerUnitSize": 20000,
+                                "encodingDependentPriceInfo": []
+                            }
+                        }"#;
+
+        let info = WalrusInfo::from(serde_json::from_str::<InfoOutput>(json).unwrap());
+        assert_eq!(info.current_epoch, 120);
+        assert_eq!(info.epoch_duration_secs, 86400);
+        assert_eq!(info.n_shards, 1000);
+        assert_eq!(info.max_blob_size, 14599692288);
+        assert_eq!(info.storage_price_per_unit_size, 100000);
+        assert_eq!(info.epochs_until(125), 5);
+        assert_eq!(info.epochs_until(100), 0);
+        assert_eq!(info.epochs_to_days(3), 3.0);
+
+        // Older versions serialized the epoch duration as milliseconds
+        let json = json.replace(r#"{ "secs": 86400, "nanos": 0 }"#, "1209600000");
+        let info = WalrusInfo::from(serde_json::from_str::<InfoOutput>(&json).unwrap());
+        assert_eq!(info.epoch_duration_secs, 1209600);
+    }
 }
@@ -2,14 +2,110 @@ use serde::{Deserialize, Serialize};
 use shellexpand;
 use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::encryptor::ChunkedHeader;
+
+pub mod watcher;
 
 const CONFIG_FILE_PATH: &str = "~/.walter/config.json";
+const DEFAULT_AGGREGATOR_URL: &str = "https://aggregator.walrus-testnet.walrus.space";
+// Uploads and extensions estimated above this many FROST (1 WAL) ask for confirmation
+const DEFAULT_COST_CONFIRMATION_THRESHOLD: u64 = 1_000_000_000;
+// Seconds a watched file has to stay unchanged before it is uploaded
+const DEFAULT_SETTLE_SECS: u64 = 10;
+// Bytes of downloaded blobs kept in the blob cache, 1 GiB
+const DEFAULT_CACHE_MAX_SIZE: u64 = 1024 * 1024 * 1024;
+// Reads of a config file that doesn't parse are retried this often, in case
+// a walter version that writes it in place is halfway through a write
+const LOAD_ATTEMPTS: u32 = 5;
+const LOAD_RETRY_DELAY: Duration = Duration::from_millis(20);
 
-#[derive(Serialize, Deserialize, Clone)]
+// Numbers the temp files of saves, so threads saving at once don't share one
+static SAVE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct FileInfo {
     pub is_encrypted: bool,
     pub blobs: Vec<String>,
+    // Bytes stored across all blobs and the size of every shard but the last,
+    // unknown for files registered before they were recorded
+    #[serde(default)]
+    pub size: Option<u64>,
+    #[serde(default)]
+    pub shard_size: Option<u64>,
+    // Set for files encrypted in chunks, older encrypted files are one ciphertext
+    #[serde(default)]
+    pub encryption: Option<ChunkedHeader>,
+    // IPFS CID of files migrated from IPFS, so links to the CID can be rewritten
+    #[serde(default)]
+    pub cid: Option<String>,
+}
+
+/// A directory whose new files are uploaded automatically. Settings left
+/// out fall back to the config defaults.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct WatchFolder {
+    pub path: String,
+    #[serde(default)]
+    pub epochs: Option<u16>,
+    // The password is given when watching starts, it is never stored here
+    #[serde(default)]
+    pub encrypt: bool,
+    #[serde(default)]
+    pub shard_size: Option<usize>,
+    #[serde(default = "default_settle_secs")]
+    pub settle_secs: u64,
+    #[serde(default)]
+    pub recursive: bool,
+}
+
+impl WatchFolder {
+    pub fn new(path: &str) -> Self {
+        WatchFolder {
+            path: path.to_string(),
+            epochs: None,
+            encrypt: false,
+            shard_size: None,
+            settle_secs: DEFAULT_SETTLE_SECS,
+            recursive: false,
+        }
+    }
+
+    pub fn get_path(&self) -> PathBuf {
+        return PathBuf::from(shellexpand::tilde(&self.path).to_string());
+    }
+
+    pub fn get_epochs(&self, config: &WalterConfig) -> u16 {
+        return self.epochs.unwrap_or(config.get_default_epochs());
+    }
+
+    pub fn get_shard_size(&self, config: &WalterConfig) -> usize {
+        return self.shard_size.unwrap_or(config.get_default_shard_size());
+    }
+}
+
+/// Where a blob sits inside the file registry.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlobLocation {
+    pub file_path: String,
+    pub shard_index: usize,
+    pub shard_count: usize,
+    pub is_encrypted: bool,
+}
+
+/// A file migrated from IPFS with the Walrus blobs that now hold it.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct CidMapping {
+    pub cid: String,
+    pub file_path: String,
+    pub blobs: Vec<String>,
+    pub is_encrypted: bool,
+    /// Aggregator URL of the file, only for unencrypted files stored in a single blob
+    pub url: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -19,7 +115,134 @@ pub struct WalterConfig {
     pub default_shard_size: usize,
     pub renew_epoch_threshold: u16,
     pub default_renewal_value: u16,
+    #[serde(default = "default_aggregator_url")]
+    pub aggregator_url: String,
+    #[serde(default = "default_cost_confirmation_threshold")]
+    pub cost_confirmation_threshold: u64,
+    // Blobs the pinner keeps alive on top of the ones belonging to registered files
+    #[serde(default)]
+    pub pinned_blobs: Vec<String>,
+    #[serde(default)]
+    pub watch_folders: Vec<WatchFolder>,
+    // Most bytes of blobs kept in the local blob cache
+    #[serde(default = "default_cache_max_size")]
+    pub cache_max_size: u64,
     pub files: HashMap<String, FileInfo>,
+    // The file as it was when this config was loaded or last saved, so saving
+    // only writes back what changed here instead of clobbering other writers
+    #[serde(skip)]
+    loaded: Option<Box<WalterConfig>>,
+}
+
+pub fn config_path() -> PathBuf {
+    PathBuf::from(shellexpand::tilde(CONFIG_FILE_PATH).to_string())
+}
+
+fn merge_field<T: PartialEq + Clone>(base: &T, ours: &T, theirs: &mut T) {
+    if ours != base {
+        *theirs = ours.clone();
+    }
+}
+
+/// Applies the changes made between `base` and `ours` on top of `theirs`, the
+/// config currently on disk. Anything `ours` didn't touch keeps its value on disk.
+fn merge(base: &WalterConfig, ours: &WalterConfig, mut theirs: WalterConfig) -> WalterConfig {
+    merge_field(
+        &base.default_file_download_dir,
+        &ours.default_file_download_dir,
+        &mut theirs.default_file_download_dir,
+    );
+    merge_field(&base.default_epochs, &ours.default_epochs, &mut theirs.default_epochs);
+    merge_field(
+        &base.default_shard_size,
+        &ours.default_shard_size,
+        &mut theirs.default_shard_size,
+    );
+    merge_field(
+        &base.renew_epoch_threshold,
+        &ours.renew_epoch_threshold,
+        &mut theirs.renew_epoch_threshold,
+    );
+    merge_field(
+        &base.default_renewal_value,
+        &ours.default_renewal_value,
+        &mut theirs.default_renewal_value,
+    );
+    merge_field(&base.aggregator_url, &ours.aggregator_url, &mut theirs.aggregator_url);
+    merge_field(
+        &base.cost_confirmation_threshold,
+        &ours.cost_confirmation_threshold,
+        &mut theirs.cost_confirmation_threshold,
+    );
+    merge_field(&base.watch_folders, &ours.watch_folders, &mut theirs.watch_folders);
+    merge_field(&base.cache_max_size, &ours.cache_max_size, &mut theirs.cache_max_size);
+
+    for (file_path, file_info) in &ours.files {
+        if base.files.get(file_path) != Some(file_info) {
+            theirs.files.insert(file_path.clone(), file_info.clone());
+        }
+    }
+    for file_path in base.files.keys() {
+        if !ours.files.contains_key(file_path) {
+            theirs.files.remove(file_path);
+        }
+    }
+
+    for blob_id in &ours.pinned_blobs {
+        if !base.pinned_blobs.contains(blob_id) {
+            theirs.pin_blob(blob_id);
+        }
+    }
+    for blob_id in &base.pinned_blobs {
+        if !ours.pinned_blobs.contains(blob_id) {
+            theirs.unpin_blob(blob_id);
+        }
+    }
+
+    theirs
+}
+
+fn load_retrying(path: &Path) -> Result<WalterConfig, String> {
+    let mut attempt = 1;
+    loop {
+        match WalterConfig::try_load_from(path) {
+            Ok(config) => return Ok(config),
+            Err(e) if attempt >= LOAD_ATTEMPTS => {
+                return Err(format!("{}: {}", path.display(), e));
+            }
+            Err(_) => attempt += 1,
+        }
+        std::thread::sleep(LOAD_RETRY_DELAY);
+    }
+}
+
+// Written aside and renamed, so readers never see a half written config
+fn write_atomically(path: &Path, contents: &str) -> std::io::Result<()> {
+    let temp_path = path.with_extension(format!(
+        "json.{}.{}.part",
+        std::process::id(),
+        SAVE_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::write(&temp_path, contents)?;
+    fs::rename(&temp_path, path).inspect_err(|_| {
+        let _ = fs::remove_file(&temp_path);
+    })
+}
+
+fn default_aggregator_url() -> String {
+    DEFAULT_AGGREGATOR_URL.to_string()
+}
+
+fn default_cost_confirmation_threshold() -> u64 {
+    DEFAULT_COST_CONFIRMATION_THRESHOLD
+}
+
+fn default_settle_secs() -> u64 {
+    DEFAULT_SETTLE_SECS
+}
+
+fn default_cache_max_size() -> u64 {
+    DEFAULT_CACHE_MAX_SIZE
 }
 
 impl WalterConfig {
@@ -27,14 +250,35 @@ impl WalterConfig {
         return &self.default_file_download_dir;
     }
 
+    pub fn get_download_dir_path(&self) -> PathBuf {
+        return PathBuf::from(shellexpand::tilde(&self.default_file_download_dir).to_string());
+    }
+
     pub fn get_default_epochs(&self) -> u16 {
         return self.default_epochs;
     }
 
+    pub fn get_renew_epoch_threshold(&self) -> u16 {
+        return self.renew_epoch_threshold;
+    }
+
+    pub fn get_default_renewal_value(&self) -> u16 {
+        return self.default_renewal_value;
+    }
+
+    pub fn get_cost_confirmation_threshold(&self) -> u64 {
+        return self.cost_confirmation_threshold;
+    }
+
     pub fn get_default_shard_size(&self) -> usize {
         return self.default_shard_size;
     }
 
+    /// Public URL of a blob on the configured aggregator.
+    pub fn get_blob_url(&self, blob_id: &str) -> String {
+        return format!("{}/v1/blobs/{}", self.aggregator_url.trim_end_matches('/'), blob_id);
+    }
+
     pub fn get_files(&self) -> &HashMap<String, FileInfo> {
         return &self.files;
     }
@@ -43,18 +287,167 @@ impl WalterConfig {
         return self.files.get(file_path).map(|file_info| &file_info.blobs);
     }
 
+    /// Maps every registered blob ID to the file and shard it belongs to.
+    pub fn get_blob_locations(&self) -> HashMap<String, BlobLocation> {
+        let mut locations = HashMap::new();
+
+        for (file_path, file_info) in &self.files {
+            for (shard_index, blob_id) in file_info.blobs.iter().enumerate() {
+                locations
+                    .entry(blob_id.clone())
+                    .or_insert_with(|| BlobLocation {
+                        file_path: file_path.clone(),
+                        shard_index,
+                        shard_count: file_info.blobs.len(),
+                        is_encrypted: file_info.is_encrypted,
+                    });
+            }
+        }
+
+        return locations;
+    }
+
     pub fn add_file(&mut self, file_path: &str, is_encrypted: bool, blobs: Vec<String>) {
         let file_info = FileInfo {
             is_encrypted,
             blobs,
+            size: None,
+            shard_size: None,
+            encryption: None,
+            cid: None,
         };
 
         self.files.insert(file_path.to_string(), file_info);
     }
 
+    /// Records how a registered file was split, so parts of it can be read
+    /// without fetching every shard.
+    pub fn set_file_layout(&mut self, file_path: &str, size: u64, shard_size: u64) {
+        if let Some(file_info) = self.files.get_mut(file_path) {
+            file_info.size = Some(size);
+            file_info.shard_size = Some(shard_size);
+        }
+    }
+
+    pub fn set_file_encryption(&mut self, file_path: &str, header: ChunkedHeader) {
+        if let Some(file_info) = self.files.get_mut(file_path) {
+            file_info.encryption = Some(header);
+        }
+    }
+
+    /// Records the IPFS CID a registered file was migrated from.
+    pub fn set_file_cid(&mut self, file_path: &str, cid: &str) {
+        if let Some(file_info) = self.files.get_mut(file_path) {
+            file_info.cid = Some(cid.to_string());
+        }
+    }
+
+    fn cid_mapping(&self, file_path: &str, file_info: &FileInfo) -> Option<CidMapping> {
+        let url = match file_info.blobs.as_slice() {
+            [blob_id] if !file_info.is_encrypted => Some(self.get_blob_url(blob_id)),
+            _ => None,
+        };
+
+        Some(CidMapping {
+            cid: file_info.cid.clone()?,
+            file_path: file_path.to_string(),
+            blobs: file_info.blobs.clone(),
+            is_encrypted: file_info.is_encrypted,
+            url,
+        })
+    }
+
+    /// Every registered file migrated from IPFS, sorted by CID.
+    pub fn get_cid_mappings(&self) -> Vec<CidMapping> {
+        let mut mappings: Vec<CidMapping> = self
+            .files
+            .iter()
+            .filter_map(|(file_path, file_info)| self.cid_mapping(file_path, file_info))
+            .collect();
+
+        mappings.sort_by(|a, b| a.cid.cmp(&b.cid).then(a.file_path.cmp(&b.file_path)));
+        mappings
+    }
+
+    /// Where the content of an IPFS CID is stored on Walrus now. When the
+    /// same CID was migrated more than once, the first file by path wins.
+    pub fn blob_for_cid(&self, cid: &str) -> Option<CidMapping> {
+        let cid = cid.trim();
+        self.get_cid_mappings()
+            .into_iter()
+            .find(|mapping| mapping.cid == cid)
+    }
+
+    pub fn remove_file(&mut self, file_path: &str) -> Option<FileInfo> {
+        return self.files.remove(file_path);
+    }
+
+    pub fn get_watch_folders(&self) -> &Vec<WatchFolder> {
+        return &self.watch_folders;
+    }
+
+    pub fn get_cache_max_size(&self) -> u64 {
+        return self.cache_max_size;
+    }
+
+    pub fn get_pinned_blobs(&self) -> &Vec<String> {
+        return &self.pinned_blobs;
+    }
+
+    /// Returns false when the blob was already pinned.
+    pub fn pin_blob(&mut self, blob_id: &str) -> bool {
+        if self.pinned_blobs.iter().any(|pinned| pinned == blob_id) {
+            return false;
+        }
+
+        self.pinned_blobs.push(blob_id.to_string());
+        return true;
+    }
+
+    /// Returns false when the blob wasn't pinned.
+    pub fn unpin_blob(&mut self, blob_id: &str) -> bool {
+        let len = self.pinned_blobs.len();
+        self.pinned_blobs.retain(|pinned| pinned != blob_id);
+        return self.pinned_blobs.len() != len;
+    }
+
+    /// Copy of the config without the loaded snapshot, as it is written to disk.
+    fn snapshot(&self) -> WalterConfig {
+        WalterConfig {
+            loaded: None,
+            ..self.clone()
+        }
+    }
+
+    /// Whether both configs would write the same file.
+    pub fn same_as(&self, other: &WalterConfig) -> bool {
+        return serde_json::to_value(self).ok() == serde_json::to_value(other).ok();
+    }
+
+    /// Reads the config at `path` without creating it or panicking, for
+    /// callers that can wait for a valid file, like the config watcher.
+    pub fn try_load_from(path: &Path) -> Result<WalterConfig, Box<dyn Error>> {
+        let config_json = fs::read_to_string(path)?;
+        let mut config: WalterConfig = serde_json::from_str(&config_json)?;
+        config.loaded = Some(Box::new(config.snapshot()));
+        return Ok(config);
+    }
+
+    /// `try_load_from` on the config file, retried for a moment when it can't
+    /// be read. For long-running processes that should report a broken config
+    /// instead of panicking. A missing config is created like `load_config_file` does.
+    pub fn try_load() -> Result<WalterConfig, String> {
+        let path = config_path();
+        if !path.exists() {
+            return Ok(Self::load_config_file());
+        }
+
+        load_retrying(&path)
+    }
+
     pub fn load_config_file() -> WalterConfig {
-        let path = shellexpand::tilde(CONFIG_FILE_PATH).to_string();
-        let path = Path::new(&path);
+        let path = config_path();
+        let path = path.as_path();
 
         if !path.exists() {
             if let Some(parent) = path.parent() {
@@ -67,23 +460,33 @@ impl WalterConfig {
                 default_shard_size: 1024 * 1024 * 10,
                 renew_epoch_threshold: 2,
                 default_renewal_value: 10,
+                aggregator_url: default_aggregator_url(),
+                cost_confirmation_threshold: default_cost_confirmation_threshold(),
+                pinned_blobs: Vec::new(),
+                watch_folders: Vec::new(),
+                cache_max_size: default_cache_max_size(),
                 files: HashMap::new(),
+                loaded: None,
             };
 
             let config_json = serde_json::to_string(&default_config)
                 .expect("Unable to serialize default config!");
-            fs::write(path, config_json).expect("Unable to write default config file!");
+            write_atomically(path, &config_json).expect("Unable to write default config file!");
 
-            return default_config;
+            return WalterConfig {
+                loaded: Some(Box::new(default_config.clone())),
+                ..default_config
+            };
         }
 
-        let config_json = fs::read_to_string(path).expect("Unable to read config file!");
-        serde_json::from_str(&config_json).expect("Unable to deserialize config file!")
+        load_retrying(path).expect("Unable to read config file!")
     }
 
-    pub fn save_config_file(&self) {
-        let path = shellexpand::tilde(CONFIG_FILE_PATH).to_string();
-        let path = Path::new(&path);
+    /// Writes the changes made since loading on top of the file currently on
+    /// disk, then takes on the merged result.
+    pub fn save_config_file(&mut self) {
+        let path = config_path();
+        let path = path.as_path();
 
         if !path.exists() {
             if let Some(parent) = path.parent() {
@@ -91,7 +494,168 @@ impl WalterConfig {
             }
         }
 
-        let config_json = serde_json::to_string(self).expect("Unable to serialize config!");
-        fs::write(path, config_json).expect("Unable to write config file!");
+        // A file that exists but can't be read is never overwritten, it may
+        // hold changes of another writer that would be lost
+        let on_disk = path
+            .exists()
+            .then(|| load_retrying(path).expect("Unable to read config file!"));
+        let merged = match (&self.loaded, on_disk) {
+            (Some(base), Some(theirs)) => merge(base, self, theirs),
+            _ => self.snapshot(),
+        };
+
+        let config_json = serde_json::to_string(&merged).expect("Unable to serialize config!");
+        write_atomically(path, &config_json).expect("Unable to write config file!");
+
+        *self = WalterConfig {
+            loaded: Some(Box::new(merged.clone())),
+            ..merged
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::test_config;
+
+    #[test]
+    fn test_blob_locations() {
+        let mut config = test_config();
+        config.add_file(
+            "db.sqlite",
+            true,
+            vec!["blob_a".to_string(), "blob_b".to_string()],
+        );
+
+        let locations = config.get_blob_locations();
+        assert_eq!(locations.len(), 2);
+        assert_eq!(
+            locations["blob_b"],
+            BlobLocation {
+                file_path: "db.sqlite".to_string(),
+                shard_index: 1,
+                shard_count: 2,
+                is_encrypted: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_cid_mappings() {
+        let mut config = test_config();
+        config.add_file("migrated/bafycat/cat.png", false, vec!["blob_a".to_string()]);
+        config.set_file_cid("migrated/bafycat/cat.png", "bafycat");
+        config.add_file(
+            "migrated/bafydb/db.sqlite",
+            true,
+            vec!["blob_b".to_string(), "blob_c".to_string()],
+        );
+        config.set_file_cid("migrated/bafydb/db.sqlite", "bafydb");
+        config.add_file("notes.txt", false, vec!["blob_d".to_string()]);
+        config.set_file_cid("missing.txt", "bafymissing");
+
+        let mappings = config.get_cid_mappings();
+        assert_eq!(mappings.len(), 2);
+        assert_eq!(
+            config.blob_for_cid(" bafycat\n"),
+            Some(CidMapping {
+                cid: "bafycat".to_string(),
+                file_path: "migrated/bafycat/cat.png".to_string(),
+                blobs: vec!["blob_a".to_string()],
+                is_encrypted: false,
+                url: Some(config.get_blob_url("blob_a")),
+            })
+        );
+        // Sharded or encrypted files can't be served from a single aggregator URL
+        assert_eq!(config.blob_for_cid("bafydb").unwrap().url, None);
+        assert_eq!(config.blob_for_cid("bafymissing"), None);
+
+        // Files registered before CIDs were recorded still load
+        let file_info: FileInfo =
+            serde_json::from_str(r#"{"is_encrypted":false,"blobs":["blob_a"]}"#).unwrap();
+        assert_eq!(file_info.cid, None);
+    }
+
+    #[test]
+    fn test_merge_keeps_other_writers_changes() {
+        let mut base = test_config();
+        base.add_file("kept.txt", false, vec!["blob_a".to_string()]);
+        base.add_file("removed.txt", false, vec!["blob_b".to_string()]);
+        base.pin_blob("pin_a");
+
+        // This process uploads a file, removes one, unpins and changes the epochs
+        let mut ours = base.clone();
+        ours.add_file("ours.txt", true, vec!["blob_c".to_string()]);
+        ours.remove_file("removed.txt");
+        ours.unpin_blob("pin_a");
+        ours.default_epochs = 20;
+
+        // Meanwhile another process registered a file, pinned a blob and changed the threshold
+        let mut theirs = base.clone();
+        theirs.add_file("theirs.txt", false, vec!["blob_d".to_string()]);
+        theirs.pin_blob("pin_b");
+        theirs.renew_epoch_threshold = 5;
+
+        let merged = merge(&base, &ours, theirs);
+
+        let mut files: Vec<&String> = merged.get_files().keys().collect();
+        files.sort();
+        assert_eq!(files, vec!["kept.txt", "ours.txt", "theirs.txt"]);
+        assert_eq!(merged.get_pinned_blobs(), &vec!["pin_b"]);
+        assert_eq!(merged.get_default_epochs(), 20);
+        assert_eq!(merged.get_renew_epoch_threshold(), 5);
+    }
+
+    #[test]
+    fn test_pinned_blobs() {
+        let mut config = test_config();
+
+        assert!(config.pin_blob("blob_a"));
+        assert!(!config.pin_blob("blob_a"));
+        assert!(config.pin_blob("blob_b"));
+        assert_eq!(config.get_pinned_blobs(), &vec!["blob_a", "blob_b"]);
+
+        assert!(config.unpin_blob("blob_a"));
+        assert!(!config.unpin_blob("blob_a"));
+        assert_eq!(config.get_pinned_blobs(), &vec!["blob_b"]);
+    }
+
+    #[test]
+    fn test_write_atomically() {
+        let dir = std::env::temp_dir().join(format!("walter_config_{}", std::process::id()));
+        let path = dir.join("config.json");
+        fs::create_dir_all(&dir).unwrap();
+
+        fs::write(&path, "{\"default_file_download_dir\":").unwrap();
+        assert!(load_retrying(&path).is_err());
+
+        let mut config = test_config();
+        config.add_file("notes.txt", false, vec!["blob_a".to_string()]);
+        write_atomically(&path, &serde_json::to_string(&config).unwrap()).unwrap();
+        let loaded = load_retrying(&path).unwrap();
+        assert!(loaded.same_as(&config));
+
+        // Only the config itself is left behind
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_defaults_for_old_configs() {
+        let config = test_config();
+
+        assert_eq!(config.aggregator_url, DEFAULT_AGGREGATOR_URL);
+        assert!(config.get_pinned_blobs().is_empty());
+        assert!(config.get_watch_folders().is_empty());
+        assert_eq!(config.get_cache_max_size(), DEFAULT_CACHE_MAX_SIZE);
+        assert_eq!(
+            config.get_cost_confirmation_threshold(),
+            DEFAULT_COST_CONFIRMATION_THRESHOLD
+        );
+        assert_eq!(
+            config.get_blob_url("blob_a"),
+            format!("{}/v1/blobs/blob_a", DEFAULT_AGGREGATOR_URL)
+        );
     }
 }
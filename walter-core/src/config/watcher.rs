@@ -0,0 +1,91 @@
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+
+use super::{config_path, WalterConfig};
+
+/// Watches the config file and queues the new config every time it is written,
+/// whether by this process, another walter-ui or the pinner.
+pub struct ConfigWatcher {
+    // Notifications stop once the watcher is dropped
+    _watcher: RecommendedWatcher,
+    receiver: Receiver<WalterConfig>,
+}
+
+impl ConfigWatcher {
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        Self::watch(config_path())
+    }
+
+    pub fn watch(path: PathBuf) -> Result<Self, Box<dyn Error>> {
+        // Editors often replace the file instead of writing to it, so the
+        // directory is watched rather than the file itself
+        let dir = path
+            .parent()
+            .map(Path::to_path_buf)
+            .ok_or("The config file has no parent directory")?;
+        std::fs::create_dir_all(&dir)?;
+
+        let (sender, receiver) = channel();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+            if !(event.kind.is_create() || event.kind.is_modify())
+                || !event.paths.iter().any(|changed| changed == &path)
+            {
+                return;
+            }
+
+            // Editors write in place, a half written file fails to parse and the
+            // event for the rest of the write follows
+            if let Ok(config) = WalterConfig::try_load_from(&path) {
+                let _ = sender.send(config);
+            }
+        })?;
+        watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+
+        Ok(ConfigWatcher {
+            _watcher: watcher,
+            receiver,
+        })
+    }
+
+    /// The most recent config written since the last call, if any.
+    pub fn poll(&self) -> Option<WalterConfig> {
+        self.receiver.try_iter().last()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::test_config;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_config_watcher() {
+        let dir = std::env::temp_dir().join(format!("walter_watch_{}", std::process::id()));
+        let path = dir.join("config.json");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let watcher = ConfigWatcher::watch(path.clone()).unwrap();
+        assert!(watcher.poll().is_none());
+
+        std::fs::write(dir.join("other.json"), "{}").unwrap();
+        let mut written = test_config();
+        written.default_epochs = 7;
+        std::fs::write(&path, serde_json::to_string(&written).unwrap()).unwrap();
+
+        let started = Instant::now();
+        let mut config = None;
+        while config.is_none() && started.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(50));
+            config = watcher.poll();
+        }
+
+        assert_eq!(config.unwrap().get_default_epochs(), 7);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
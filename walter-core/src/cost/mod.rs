@@ -0,0 +1,184 @@
+use serde::Serialize;
+
+use crate::encryptor::{chunked_stored_size, DEFAULT_CHUNK_SIZE};
+use crate::types::WalrusInfo;
+
+pub const FROST_PER_WAL: u64 = 1_000_000_000;
+
+// Blob ID and the two sliver hashes per shard stored in the blob metadata
+const DIGEST_LEN: u64 = 32;
+const BLOB_ID_LEN: u64 = 32;
+
+/// Estimated price of storing one blob, amounts are in FROST.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct CostEstimate {
+    pub unencoded_size: u64,
+    pub encoded_size: u64,
+    pub storage_units: u64,
+    pub epochs: u16,
+    pub storage_cost: u64,
+    pub write_cost: u64,
+}
+
+impl CostEstimate {
+    pub fn total(&self) -> u64 {
+        self.storage_cost + self.write_cost
+    }
+}
+
+/// Size of a blob once erasure coded across `n_shards`, following the
+/// RedStuff encoding used by Walrus.
+pub fn encoded_size(unencoded_size: u64, n_shards: u16) -> u64 {
+    let n_shards = n_shards.max(1) as u64;
+    let max_faulty = (n_shards - 1) / 3;
+    let primary_symbols = n_shards - 2 * max_faulty;
+    let secondary_symbols = n_shards - max_faulty;
+
+    // Symbols have to be an even number of bytes
+    let mut symbol_size = unencoded_size
+        .div_ceil(primary_symbols * secondary_symbols)
+        .max(1);
+    if symbol_size % 2 == 1 {
+        symbol_size += 1;
+    }
+
+    let slivers_size = (primary_symbols + secondary_symbols) * symbol_size * n_shards;
+    let metadata_size = n_shards * DIGEST_LEN * 2 + BLOB_ID_LEN;
+
+    slivers_size + n_shards * metadata_size
+}
+
+/// Cost of registering and writing a new blob for `epochs`.
+pub fn estimate_blob_cost(info: &WalrusInfo, unencoded_size: u64, epochs: u16) -> CostEstimate {
+    let encoded_size = encoded_size(unencoded_size, info.n_shards);
+    let storage_units = encoded_size.div_ceil(info.storage_unit_size.max(1));
+
+    CostEstimate {
+        unencoded_size,
+        encoded_size,
+        storage_units,
+        epochs,
+        storage_cost: storage_units * info.storage_price_per_unit_size * epochs as u64,
+        write_cost: storage_units * info.write_price_per_unit_size,
+    }
+}
+
+/// Cost of keeping an existing blob stored for `epochs` more, nothing is written again.
+pub fn estimate_extension_cost(
+    info: &WalrusInfo,
+    unencoded_size: u64,
+    epochs: u16,
+) -> CostEstimate {
+    CostEstimate {
+        write_cost: 0,
+        ..estimate_blob_cost(info, unencoded_size, epochs)
+    }
+}
+
+/// Per shard costs of uploading a file the way `WalrusClient::upload_file` does.
+pub fn estimate_file_cost(
+    info: &WalrusInfo,
+    file_size: u64,
+    shard_size: u64,
+    encrypted: bool,
+    epochs: u16,
+) -> Vec<CostEstimate> {
+    let mut remaining = if encrypted {
+        chunked_stored_size(file_size, DEFAULT_CHUNK_SIZE)
+    } else {
+        file_size
+    };
+    let shard_size = shard_size.max(1);
+
+    let mut shards = Vec::new();
+    while remaining > 0 {
+        let size = remaining.min(shard_size);
+        shards.push(estimate_blob_cost(info, size, epochs));
+        remaining -= size;
+    }
+    shards
+}
+
+pub fn total_cost(estimates: &[CostEstimate]) -> u64 {
+    estimates.iter().map(CostEstimate::total).sum()
+}
+
+/// Formats an amount of FROST as WAL, e.g. "0.0125 WAL".
+pub fn format_wal(frost: u64) -> String {
+    let wal = frost as f64 / FROST_PER_WAL as f64;
+    let formatted = format!("{:.9}", wal);
+    format!(
+        "{} WAL",
+        formatted.trim_end_matches('0').trim_end_matches('.')
+    )
+}
+
+/// Formats an amount of FROST in both units, e.g. "0.0125 WAL (12500000 FROST)".
+pub fn format_cost(frost: u64) -> String {
+    format!("{} ({} FROST)", format_wal(frost), frost)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::walrus_info;
+
+    #[test]
+    fn test_encoded_size() {
+        // 1000 shards: 334 primary and 667 secondary symbols, 64 KiB of metadata per shard
+        let metadata = 1000 * (1000 * 64 + 32);
+        assert_eq!(encoded_size(1, 1000), 1001 * 2 * 1000 + metadata);
+        assert_eq!(encoded_size(0, 1000), encoded_size(1, 1000));
+
+        let size: u64 = 10 * 1024 * 1024;
+        let symbol_size: u64 = size.div_ceil(334 * 667);
+        let symbol_size = symbol_size + symbol_size % 2;
+        assert_eq!(
+            encoded_size(size, 1000),
+            1001 * symbol_size * 1000 + metadata
+        );
+    }
+
+    #[test]
+    fn test_estimate_costs() {
+        let info = walrus_info();
+
+        let upload = estimate_blob_cost(&info, 1024, 5);
+        assert_eq!(
+            upload.storage_units,
+            upload.encoded_size.div_ceil(1024 * 1024)
+        );
+        assert_eq!(upload.storage_cost, upload.storage_units * 100_000 * 5);
+        assert_eq!(upload.write_cost, upload.storage_units * 20_000);
+
+        let extension = estimate_extension_cost(&info, 1024, 5);
+        assert_eq!(extension.write_cost, 0);
+        assert_eq!(extension.total(), upload.storage_cost);
+
+        let shards = estimate_file_cost(&info, 25, 10, false, 1);
+        let sizes: Vec<u64> = shards.iter().map(|shard| shard.unencoded_size).collect();
+        assert_eq!(sizes, vec![10, 10, 5]);
+        assert_eq!(
+            total_cost(&shards),
+            shards.iter().map(|s| s.total()).sum::<u64>()
+        );
+
+        // The 28 byte header, then a 28 byte nonce and tag for each of the 3 chunks
+        let file_size = DEFAULT_CHUNK_SIZE * 2 + 25;
+        let encrypted = estimate_file_cost(&info, file_size, DEFAULT_CHUNK_SIZE, true, 1);
+        let sizes: Vec<u64> = encrypted.iter().map(|shard| shard.unencoded_size).collect();
+        assert_eq!(
+            sizes,
+            vec![DEFAULT_CHUNK_SIZE, DEFAULT_CHUNK_SIZE, 25 + 28 + 3 * 28]
+        );
+    }
+
+    #[test]
+    fn test_format_wal() {
+        assert_eq!(format_wal(FROST_PER_WAL), "1 WAL");
+        assert_eq!(format_wal(12_500_000), "0.0125 WAL");
+        assert_eq!(format_wal(1), "0.000000001 WAL");
+        assert_eq!(format_wal(0), "0 WAL");
+        assert_eq!(format_cost(12_500_000), "0.0125 WAL (12500000 FROST)");
+    }
+}
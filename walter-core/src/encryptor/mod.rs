@@ -1,15 +1,134 @@
 use openssl::pkcs5::pbkdf2_hmac;
-use openssl::symm::{decrypt, encrypt, Cipher};
+use openssl::symm::{decrypt, decrypt_aead, encrypt, encrypt_aead, Cipher};
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 use std::error::Error;
 use std::fs::File;
-use std::io::{Read, Write};
+use std::io::{BufWriter, Read, Write};
+use std::ops::Range;
 
 const SALT_LEN: usize = 16; // Salt length in bytes
 const IV_LEN: usize = 16; // AES-256-CBC IV length
 const KEY_LEN: usize = 32; // AES-256 requires a 256-bit (32 bytes) key
 const PBKDF2_ITERATIONS: usize = 10000;
 
+// Files encrypted in chunks start with this, followed by the salt and the chunk size
+const CHUNKED_MAGIC: &[u8; 8] = b"WALTERC1";
+pub const CHUNKED_HEADER_LEN: u64 = (CHUNKED_MAGIC.len() + SALT_LEN + 4) as u64;
+pub const DEFAULT_CHUNK_SIZE: u64 = 64 * 1024;
+
+// Files encrypted as a whole start with the salt, the size of a file is in
+// its last two cipher blocks
+pub const LEGACY_SALT_LEN: u64 = SALT_LEN as u64;
+pub const LEGACY_TAIL_LEN: u64 = 2 * IV_LEN as u64;
+const NONCE_LEN: usize = 12; // AES-256-GCM nonce length
+const TAG_LEN: usize = 16; // AES-256-GCM authentication tag length
+
+// Every chunk is stored as its nonce, the ciphertext and the tag
+const CHUNK_OVERHEAD: u64 = (NONCE_LEN + TAG_LEN) as u64;
+
+/// Salt and chunk size of a file written by `encrypt_file_chunked`. Also kept
+/// in the registry, so ranges can be decrypted without reading the header.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ChunkedHeader {
+    pub salt: Vec<u8>,
+    pub chunk_size: u64,
+}
+
+impl ChunkedHeader {
+    pub fn parse(data: &[u8]) -> Option<ChunkedHeader> {
+        let rest = data.strip_prefix(CHUNKED_MAGIC)?;
+        let salt = rest.get(..SALT_LEN)?.to_vec();
+        let chunk_size = u32::from_be_bytes(rest.get(SALT_LEN..SALT_LEN + 4)?.try_into().ok()?);
+
+        (chunk_size > 0).then_some(ChunkedHeader {
+            salt,
+            chunk_size: chunk_size as u64,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = CHUNKED_MAGIC.to_vec();
+        bytes.extend_from_slice(&self.salt);
+        bytes.extend_from_slice(&(self.chunk_size as u32).to_be_bytes());
+        bytes
+    }
+
+    fn stored_chunk_size(&self) -> u64 {
+        self.chunk_size + CHUNK_OVERHEAD
+    }
+
+    /// Where chunk `index` starts in the encrypted file.
+    pub fn chunk_offset(&self, index: u64) -> u64 {
+        CHUNKED_HEADER_LEN + index * self.stored_chunk_size()
+    }
+
+    /// Number of chunks in an encrypted file of `stored_size` bytes.
+    pub fn chunk_count(&self, stored_size: u64) -> Option<u64> {
+        let body = stored_size.checked_sub(CHUNKED_HEADER_LEN)?;
+        let full = body / self.stored_chunk_size();
+
+        match body % self.stored_chunk_size() {
+            0 if full > 0 => Some(full),
+            rest if rest >= CHUNK_OVERHEAD => Some(full + 1),
+            _ => None,
+        }
+    }
+
+    /// Size of the plaintext in an encrypted file of `stored_size` bytes.
+    pub fn plain_size(&self, stored_size: u64) -> Option<u64> {
+        let chunks = self.chunk_count(stored_size)?;
+        Some(stored_size - CHUNKED_HEADER_LEN - chunks * CHUNK_OVERHEAD)
+    }
+
+    /// The chunks holding the plaintext bytes in `range` and where they are
+    /// stored in an encrypted file of `stored_size` bytes. `None` when the
+    /// range is empty or past the end of the file.
+    pub fn chunks_for_range(
+        &self,
+        range: Range<u64>,
+        stored_size: u64,
+    ) -> Option<(Range<u64>, Range<u64>)> {
+        let end = range.end.min(self.plain_size(stored_size)?);
+        if range.start >= end {
+            return None;
+        }
+
+        let chunks = range.start / self.chunk_size..(end - 1) / self.chunk_size + 1;
+        let stored =
+            self.chunk_offset(chunks.start)..self.chunk_offset(chunks.end).min(stored_size);
+        Some((chunks, stored))
+    }
+}
+
+/// Size of the file `encrypt_file_chunked` writes for `plain_size` bytes split
+/// in chunks of `chunk_size`.
+pub fn chunked_stored_size(plain_size: u64, chunk_size: u64) -> u64 {
+    // An empty file still gets one chunk
+    let chunks = plain_size.div_ceil(chunk_size.max(1)).max(1);
+    CHUNKED_HEADER_LEN + chunks * CHUNK_OVERHEAD + plain_size
+}
+
+fn derive_key(password: &str, salt: &[u8]) -> Result<[u8; KEY_LEN], Box<dyn Error>> {
+    let mut key = [0u8; KEY_LEN];
+    pbkdf2_hmac(
+        password.as_bytes(),
+        salt,
+        PBKDF2_ITERATIONS,
+        openssl::hash::MessageDigest::sha256(),
+        &mut key,
+    )?;
+    Ok(key)
+}
+
+// Binds every chunk to its position, so chunks can't be reordered or the file cut short
+fn chunk_aad(index: u64, is_last: bool) -> [u8; 9] {
+    let mut aad = [0u8; 9];
+    aad[..8].copy_from_slice(&index.to_be_bytes());
+    aad[8] = is_last as u8;
+    aad
+}
+
 pub fn encrypt_file(
     input_file: &str,
     output_file: &str,
@@ -54,6 +173,90 @@ pub fn encrypt_file(
     Ok(())
 }
 
+/// Encrypts a file in independently decryptable chunks with AES-256-GCM, so
+/// any part of it can be decrypted without the rest.
+pub fn encrypt_file_chunked(
+    input_file: &str,
+    output_file: &str,
+    password: &str,
+) -> Result<ChunkedHeader, Box<dyn Error>> {
+    let mut file = File::open(input_file)?;
+    let size = file.metadata()?.len();
+
+    let mut salt = [0u8; SALT_LEN];
+    rand::thread_rng().fill(&mut salt);
+    let header = ChunkedHeader {
+        salt: salt.to_vec(),
+        chunk_size: DEFAULT_CHUNK_SIZE,
+    };
+    let key = derive_key(password, &salt)?;
+
+    let mut out_file = BufWriter::new(File::create(output_file)?);
+    out_file.write_all(&header.to_bytes())?;
+
+    // An empty file still gets one chunk, so it can't be confused with a cut off one
+    let chunk_count = size.div_ceil(header.chunk_size).max(1);
+    let mut chunk = Vec::new();
+    for index in 0..chunk_count {
+        chunk.clear();
+        (&mut file)
+            .take(header.chunk_size)
+            .read_to_end(&mut chunk)?;
+
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::thread_rng().fill(&mut nonce);
+        let mut tag = [0u8; TAG_LEN];
+        let ciphertext = encrypt_aead(
+            Cipher::aes_256_gcm(),
+            &key,
+            Some(&nonce),
+            &chunk_aad(index, index + 1 == chunk_count),
+            &chunk,
+            &mut tag,
+        )?;
+
+        out_file.write_all(&nonce)?;
+        out_file.write_all(&ciphertext)?;
+        out_file.write_all(&tag)?;
+    }
+    out_file.flush()?;
+
+    Ok(header)
+}
+
+/// Decrypts consecutive chunks of a file written by `encrypt_file_chunked`,
+/// `data` holding them as stored starting with chunk `first_chunk`.
+pub fn decrypt_chunks(
+    data: &[u8],
+    header: &ChunkedHeader,
+    first_chunk: u64,
+    chunk_count: u64,
+    password: &str,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let key = derive_key(password, &header.salt)?;
+    let mut plaintext = Vec::new();
+
+    for (i, chunk) in data.chunks(header.stored_chunk_size() as usize).enumerate() {
+        if chunk.len() < CHUNK_OVERHEAD as usize {
+            return Err("Encrypted chunk is cut short".into());
+        }
+
+        let index = first_chunk + i as u64;
+        let (nonce, rest) = chunk.split_at(NONCE_LEN);
+        let (ciphertext, tag) = rest.split_at(rest.len() - TAG_LEN);
+        plaintext.extend(decrypt_aead(
+            Cipher::aes_256_gcm(),
+            &key,
+            Some(nonce),
+            &chunk_aad(index, index + 1 == chunk_count),
+            ciphertext,
+            tag,
+        )?);
+    }
+
+    Ok(plaintext)
+}
+
 pub fn decrypt_file(
     input_file: &str,
     output_file: &str,
@@ -63,6 +266,34 @@ pub fn decrypt_file(
     let mut data = Vec::new();
     file.read_to_end(&mut data)?;
 
+    let plaintext = decrypt_data(&data, password)?;
+
+    let mut out_file = File::create(output_file)?;
+    out_file.write_all(&plaintext)?;
+
+    Ok(())
+}
+
+/// Decrypts the contents of a file written by `encrypt_file` or
+/// `encrypt_file_chunked` held in memory.
+pub fn decrypt_data(data: &[u8], password: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    if let Some(header) = ChunkedHeader::parse(data) {
+        let chunk_count = header
+            .chunk_count(data.len() as u64)
+            .ok_or("Encrypted data is cut short")?;
+        return decrypt_chunks(
+            &data[CHUNKED_HEADER_LEN as usize..],
+            &header,
+            0,
+            chunk_count,
+            password,
+        );
+    }
+
+    if data.len() < SALT_LEN + IV_LEN {
+        return Err("Encrypted data is too short".into());
+    }
+
     let salt = &data[0..SALT_LEN];
     let iv = &data[SALT_LEN..SALT_LEN + IV_LEN];
     let ciphertext = &data[SALT_LEN + IV_LEN..];
@@ -76,12 +307,33 @@ pub fn decrypt_file(
         &mut key,
     )?;
 
-    let plaintext = decrypt(Cipher::aes_256_cbc(), &key, Some(iv), ciphertext)?;
+    Ok(decrypt(Cipher::aes_256_cbc(), &key, Some(iv), ciphertext)?)
+}
 
-    let mut out_file = File::create(output_file)?;
-    out_file.write_all(&plaintext)?;
+/// The size before encryption of a `stored_size` byte file written by
+/// `encrypt_file`, from its salt and its last `LEGACY_TAIL_LEN` bytes. Only
+/// the last block is decrypted, its padding tells how much of it is data.
+pub fn legacy_plain_size(
+    stored_size: u64,
+    salt: &[u8],
+    tail: &[u8],
+    password: &str,
+) -> Result<u64, Box<dyn Error>> {
+    let block_len = IV_LEN as u64;
+    let ciphertext_len = stored_size
+        .checked_sub((SALT_LEN + IV_LEN) as u64)
+        .filter(|len| *len >= block_len && len % block_len == 0)
+        .ok_or("The encrypted file has an unexpected size")?;
+    if salt.len() != SALT_LEN || tail.len() as u64 != LEGACY_TAIL_LEN {
+        return Err("Expected the salt and the last two blocks".into());
+    }
 
-    Ok(())
+    // The block before the last one is its IV, the IV itself for a single block
+    let key = derive_key(password, salt)?;
+    let (previous, last) = tail.split_at(IV_LEN);
+    let last = decrypt(Cipher::aes_256_cbc(), &key, Some(previous), last)?;
+
+    Ok(ciphertext_len - block_len + last.len() as u64)
 }
 
 #[cfg(test)]
@@ -116,4 +368,106 @@ mod tests {
 
         assert_eq!(decrypted_content, original_contents);
     }
+
+    #[test]
+    fn test_legacy_plain_size() {
+        let dir = std::env::temp_dir();
+        let input_file = dir.join(format!("walter_legacy_{}.txt", std::process::id()));
+        let encrypted_file = dir.join(format!("walter_legacy_{}.enc", std::process::id()));
+
+        for plain_size in [0, 1, 15, 16, 17, 100] {
+            fs::write(&input_file, vec![7u8; plain_size]).unwrap();
+            encrypt_file(
+                input_file.to_str().unwrap(),
+                encrypted_file.to_str().unwrap(),
+                "password",
+            )
+            .unwrap();
+            let data = fs::read(&encrypted_file).unwrap();
+
+            let salt = &data[..LEGACY_SALT_LEN as usize];
+            let tail = &data[data.len() - LEGACY_TAIL_LEN as usize..];
+            let size = legacy_plain_size(data.len() as u64, salt, tail, "password");
+            assert_eq!(size.unwrap(), plain_size as u64);
+            assert!(legacy_plain_size(data.len() as u64 + 1, salt, tail, "password").is_err());
+        }
+
+        fs::remove_file(&input_file).unwrap();
+        fs::remove_file(&encrypted_file).unwrap();
+    }
+
+    #[test]
+    fn test_chunked_encryption() {
+        let dir = std::env::temp_dir();
+        let input_file = dir.join(format!("walter_chunked_{}.txt", std::process::id()));
+        let encrypted_file = dir.join(format!("walter_chunked_{}.enc", std::process::id()));
+        let plaintext: Vec<u8> = (0..DEFAULT_CHUNK_SIZE * 2 + 100)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        fs::write(&input_file, &plaintext).unwrap();
+
+        let header = encrypt_file_chunked(
+            input_file.to_str().unwrap(),
+            encrypted_file.to_str().unwrap(),
+            "password",
+        )
+        .unwrap();
+        let data = fs::read(&encrypted_file).unwrap();
+        fs::remove_file(&input_file).unwrap();
+        fs::remove_file(&encrypted_file).unwrap();
+
+        let stored_size = data.len() as u64;
+        assert_eq!(
+            chunked_stored_size(plaintext.len() as u64, header.chunk_size),
+            stored_size
+        );
+        assert_eq!(ChunkedHeader::parse(&data), Some(header.clone()));
+        assert_eq!(header.chunk_count(stored_size), Some(3));
+        assert_eq!(header.plain_size(stored_size), Some(plaintext.len() as u64));
+        assert_eq!(decrypt_data(&data, "password").unwrap(), plaintext);
+        assert!(decrypt_data(&data, "wrong").is_err());
+
+        // The middle chunk decrypts on its own
+        let (chunks, stored) = header
+            .chunks_for_range(
+                DEFAULT_CHUNK_SIZE + 10..DEFAULT_CHUNK_SIZE + 20,
+                stored_size,
+            )
+            .unwrap();
+        assert_eq!(chunks, 1..2);
+        let middle = decrypt_chunks(
+            &data[stored.start as usize..stored.end as usize],
+            &header,
+            1,
+            3,
+            "password",
+        )
+        .unwrap();
+        assert_eq!(
+            middle,
+            &plaintext[DEFAULT_CHUNK_SIZE as usize..DEFAULT_CHUNK_SIZE as usize * 2]
+        );
+
+        // Chunks are bound to their position
+        assert!(decrypt_chunks(
+            &data[stored.start as usize..stored.end as usize],
+            &header,
+            0,
+            3,
+            "password"
+        )
+        .is_err());
+
+        // Dropping the last chunk is noticed
+        let cut = &data[..header.chunk_offset(2) as usize];
+        assert!(decrypt_data(cut, "password").is_err());
+
+        assert_eq!(
+            header.chunks_for_range(0..u64::MAX, stored_size),
+            Some((0..3, header.chunk_offset(0)..stored_size))
+        );
+        assert!(header
+            .chunks_for_range(plaintext.len() as u64..u64::MAX, stored_size)
+            .is_none());
+    }
 }
@@ -1,20 +1,74 @@
 use super::client::*;
+use crate::blob_cache::BlobCache;
+use crate::types::BlobInfo;
 use std::error::Error;
 
-pub async fn extend_epoch(blob_id: &str, epochs: u16) -> Result<(), Box<dyn Error>> {
-    let output = download_blob(blob_id, "/tmp/epoch_extender").await;
+/// Extends a blob by `epochs` and returns its new end epoch.
+///
+/// Only blobs owned by the active address can be extended, any other blob ID,
+/// including expired blobs, is an error rather than paying for a new blob.
+pub async fn extend_epoch(
+    cache: &BlobCache,
+    blob_id: &str,
+    epochs: u16,
+) -> Result<u64, Box<dyn Error>> {
+    let blobs = list_blobs(false).await?;
+    match latest_blob(&blobs, blob_id) {
+        Some(blob) => extend_epoch_for(cache, blob, epochs).await,
+        None => Err(format!("{} not found among your blobs", blob_id).into()),
+    }
+}
 
-    if output.is_err() {
-        return Err(output.err().unwrap());
+/// Same as `extend_epoch` for a blob taken from a listing the caller already
+/// has, so extending many blobs doesn't list them again for each one.
+///
+/// The blob is extended in place through its blob object. It is only read
+/// back and stored again when the walrus CLI has no `extend` command, which
+/// pays for a new blob, reading it through `cache`.
+pub async fn extend_epoch_for(
+    cache: &BlobCache,
+    blob: &BlobInfo,
+    epochs: u16,
+) -> Result<u64, Box<dyn Error>> {
+    if extend_blob(&blob.object_id, epochs).await? {
+        return Ok(blob.expiration_epoch + epochs as u64);
     }
 
-    let output = upload_blob("/tmp/epoch_extender", epochs).await;
+    reupload_blob(cache, &blob.blob_id, epochs).await
+}
 
-    if output.is_err() {
-        return Err(output.err().unwrap());
-    }
+/// The blob object of `blob_id` that lasts the longest.
+pub fn latest_blob<'a>(blobs: &'a [BlobInfo], blob_id: &str) -> Option<&'a BlobInfo> {
+    blobs
+        .iter()
+        .filter(|blob| blob.blob_id == blob_id)
+        .max_by_key(|blob| blob.expiration_epoch)
+}
+
+async fn reupload_blob(
+    cache: &BlobCache,
+    blob_id: &str,
+    epochs: u16,
+) -> Result<u64, Box<dyn Error>> {
+    let temp_file_path = temp_file_path("extend")?;
+    let temp_file = temp_file_path.to_string_lossy().to_string();
+
+    // Each call gets its own temp file, so extensions can run side by side
+    let downloaded = download_blob(cache, blob_id, &temp_file)
+        .await
+        .map_err(|e| e.to_string());
+    let uploaded = match downloaded {
+        Ok(_) => upload_blob(&temp_file, epochs)
+            .await
+            .map_err(|e| e.to_string()),
+        Err(e) => Err(e),
+    };
+    let _ = std::fs::remove_file(&temp_file_path);
+    uploaded?;
 
-    Ok(())
+    // A newly stored blob lasts `epochs` from the current one
+    let info = walrus_info().await?;
+    Ok(info.current_epoch + epochs as u64)
 }
 
 #[cfg(test)]
@@ -23,7 +77,12 @@ mod tests {
 
     #[tokio::test]
     async fn test_epoch_extender() {
-        let output = extend_epoch("DVZWz_QCEb2D_UPQzswv-DUqg-etmV6rEPzoERY4Tgg", 1).await;
+        let output = extend_epoch(
+            &BlobCache::open(),
+            "DVZWz_QCEb2D_UPQzswv-DUqg-etmV6rEPzoERY4Tgg",
+            1,
+        )
+        .await;
         assert!(output.is_ok());
     }
 }
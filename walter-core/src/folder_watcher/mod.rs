@@ -0,0 +1,420 @@
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::client::WalrusClient;
+use crate::config::{WalterConfig, WatchFolder};
+
+// How often the headless watcher checks whether pending files have settled
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The outcome of uploading one watched file.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct WatchUpload {
+    pub timestamp: u64,
+    pub file_path: String,
+    pub size: u64,
+    pub epochs: u16,
+    pub encrypted: bool,
+    pub blobs: Vec<String>,
+    pub error: Option<String>,
+}
+
+impl WatchUpload {
+    pub fn describe(&self) -> String {
+        match &self.error {
+            Some(e) => format!("{}: upload failed, {}", self.file_path, e),
+            None => format!(
+                "{}: uploaded {} bytes as {} blob(s) for {} epochs{}",
+                self.file_path,
+                self.size,
+                self.blobs.len(),
+                self.epochs,
+                if self.encrypted { ", encrypted" } else { "" }
+            ),
+        }
+    }
+}
+
+// Size and modification time, a change in either means the file is still being written
+type FileState = (u64, Option<SystemTime>);
+
+fn file_state(path: &Path) -> Option<FileState> {
+    let metadata = fs::metadata(path).ok()?;
+    if !metadata.is_file() {
+        return None;
+    }
+    Some((metadata.len(), metadata.modified().ok()))
+}
+
+/// Hidden files and the temporary files editors and dump tools write before
+/// renaming them into place are never uploaded.
+fn is_ignored(path: &Path) -> bool {
+    let Some(name) = path.file_name().map(|name| name.to_string_lossy()) else {
+        return true;
+    };
+
+    name.starts_with('.')
+        || name.ends_with('~')
+        || [".tmp", ".part", ".partial", ".swp", ".crdownload"]
+            .iter()
+            .any(|suffix| name.ends_with(suffix))
+}
+
+struct PendingFile {
+    folder: usize,
+    state: FileState,
+    changed_at: Instant,
+}
+
+/// Files that changed recently, each held back until it has stayed the same
+/// for its folder's `settle_secs`.
+#[derive(Default)]
+pub struct SettleTracker {
+    pending: HashMap<PathBuf, PendingFile>,
+}
+
+impl SettleTracker {
+    pub fn new() -> Self {
+        SettleTracker::default()
+    }
+
+    /// Records a change to `path`, which belongs to the folder at index `folder`.
+    pub fn touch(&mut self, path: &Path, folder: usize, now: Instant) {
+        let Some(state) = file_state(path) else {
+            return;
+        };
+
+        self.pending.insert(
+            path.to_path_buf(),
+            PendingFile {
+                folder,
+                state,
+                changed_at: now,
+            },
+        );
+    }
+
+    /// Removes and returns the files that haven't changed for their folder's
+    /// settle time, with the index of their folder. Files that were deleted
+    /// in the meantime are dropped.
+    pub fn settled(&mut self, folders: &[WatchFolder], now: Instant) -> Vec<(PathBuf, usize)> {
+        let mut settled = Vec::new();
+
+        self.pending.retain(|path, pending| {
+            let Some(state) = file_state(path) else {
+                return false;
+            };
+
+            // Writes that didn't produce an event still restart the wait
+            if state != pending.state {
+                pending.state = state;
+                pending.changed_at = now;
+                return true;
+            }
+
+            let settle = Duration::from_secs(folders[pending.folder].settle_secs);
+            if now.duration_since(pending.changed_at) < settle {
+                return true;
+            }
+
+            settled.push((path.clone(), pending.folder));
+            false
+        });
+
+        settled.sort();
+        settled
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+/// Index of the folder `path` belongs to. The deepest folder wins when
+/// watched folders are nested.
+fn owning_folder(roots: &[(PathBuf, bool)], path: &Path) -> Option<usize> {
+    roots
+        .iter()
+        .enumerate()
+        .filter(|(_, (root, recursive))| match recursive {
+            true => path.starts_with(root) && path != root,
+            false => path.parent() == Some(root.as_path()),
+        })
+        .max_by_key(|(_, (root, _))| root.components().count())
+        .map(|(i, _)| i)
+}
+
+/// Watches the configured folders and hands back files once they have
+/// stopped changing, along with the folder settings to upload them with.
+pub struct FolderWatcher {
+    folders: Vec<WatchFolder>,
+    // Notifications stop once the watcher is dropped
+    _watcher: RecommendedWatcher,
+    receiver: Receiver<(PathBuf, usize)>,
+    tracker: SettleTracker,
+}
+
+impl FolderWatcher {
+    pub fn new(folders: Vec<WatchFolder>) -> Result<Self, Box<dyn Error>> {
+        if folders.is_empty() {
+            return Err(
+                "No watch folders configured, add some to watch_folders in the config".into(),
+            );
+        }
+
+        // Events carry canonical paths, the folders keep the paths as configured
+        let mut roots = Vec::new();
+        for folder in &folders {
+            let root = fs::canonicalize(folder.get_path())
+                .map_err(|e| format!("Can't watch {}: {}", folder.path, e))?;
+            if !root.is_dir() {
+                return Err(format!("Can't watch {}: not a directory", folder.path).into());
+            }
+            roots.push((root, folder.recursive));
+        }
+
+        let (sender, receiver) = channel();
+        let event_roots = roots.clone();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+            if !(event.kind.is_create() || event.kind.is_modify()) {
+                return;
+            }
+
+            for path in event.paths {
+                if is_ignored(&path) {
+                    continue;
+                }
+                if let Some(folder) = owning_folder(&event_roots, &path) {
+                    let _ = sender.send((path, folder));
+                }
+            }
+        })?;
+
+        for (root, recursive) in &roots {
+            let mode = match recursive {
+                true => RecursiveMode::Recursive,
+                false => RecursiveMode::NonRecursive,
+            };
+            watcher.watch(root, mode)?;
+        }
+
+        Ok(FolderWatcher {
+            folders,
+            _watcher: watcher,
+            receiver,
+            tracker: SettleTracker::new(),
+        })
+    }
+
+    pub fn folders(&self) -> &[WatchFolder] {
+        &self.folders
+    }
+
+    /// Number of files that changed and are waiting to settle.
+    pub fn pending_count(&self) -> usize {
+        self.tracker.len()
+    }
+
+    /// Applies the changes seen since the last call and returns the files that
+    /// are ready to upload, each with its folder.
+    pub fn poll(&mut self) -> Vec<(PathBuf, WatchFolder)> {
+        let now = Instant::now();
+        for (path, folder) in self.receiver.try_iter() {
+            self.tracker.touch(&path, folder, now);
+        }
+
+        self.tracker
+            .settled(&self.folders, now)
+            .into_iter()
+            .map(|(path, folder)| (path, self.folders[folder].clone()))
+            .collect()
+    }
+
+    /// Uploads settled files one at a time until the process is stopped.
+    /// `on_upload` gets the outcome of each upload.
+    pub async fn run<F>(&mut self, password: Option<String>, mut on_upload: F)
+    where
+        F: FnMut(WatchUpload),
+    {
+        loop {
+            for (path, folder) in self.poll() {
+                on_upload(upload_watched_file(&path, &folder, password.clone()).await);
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+}
+
+/// Uploads `path` with the settings of the folder it was found in and
+/// registers it under its full path. Encrypted folders need `password`.
+pub async fn upload_watched_file(
+    path: &Path,
+    folder: &WatchFolder,
+    password: Option<String>,
+) -> WatchUpload {
+    let file_path = path.to_string_lossy().to_string();
+
+    let mut upload = WatchUpload {
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0),
+        file_path: file_path.clone(),
+        size: fs::metadata(path)
+            .map(|metadata| metadata.len())
+            .unwrap_or(0),
+        epochs: folder.epochs.unwrap_or_default(),
+        encrypted: folder.encrypt,
+        blobs: Vec::new(),
+        error: None,
+    };
+
+    // Reloaded for every file so uploads from other processes aren't lost
+    let config = match WalterConfig::try_load() {
+        Ok(config) => config,
+        Err(e) => {
+            upload.error = Some(format!("Unable to read config file: {}", e));
+            return upload;
+        }
+    };
+    let epochs = folder.get_epochs(&config);
+    let shard_size = folder.get_shard_size(&config);
+    upload.epochs = epochs;
+
+    let password = match (folder.encrypt, password) {
+        (true, None) => {
+            upload.error = Some(format!(
+                "{} is encrypted but no password was given",
+                folder.path
+            ));
+            return upload;
+        }
+        (true, password) => password,
+        (false, _) => None,
+    };
+
+    let mut client = WalrusClient::new(config);
+    let result = client
+        .upload_file_with_options(&file_path, password, epochs, shard_size)
+        .await
+        .map_err(|e| e.to_string());
+
+    match result {
+        Ok(_) => {
+            upload.blobs = client
+                .config
+                .get_file_blobs(&file_path)
+                .cloned()
+                .unwrap_or_default();
+        }
+        Err(e) => upload.error = Some(e),
+    }
+    upload
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::test_config;
+
+    #[test]
+    fn test_settle_tracker() {
+        let dir = std::env::temp_dir().join(format!("walter_settle_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let dump = dir.join("dump.sql");
+        let gone = dir.join("gone.sql");
+        fs::write(&dump, "create table").unwrap();
+        fs::write(&gone, "create table").unwrap();
+
+        let mut quick = WatchFolder::new(dir.to_str().unwrap());
+        quick.settle_secs = 5;
+        let folders = vec![quick];
+
+        let start = Instant::now();
+        let mut tracker = SettleTracker::new();
+        tracker.touch(&dump, 0, start);
+        tracker.touch(&gone, 0, start);
+        tracker.touch(&dir.join("missing.sql"), 0, start);
+        assert_eq!(tracker.len(), 2);
+
+        assert!(tracker
+            .settled(&folders, start + Duration::from_secs(2))
+            .is_empty());
+
+        // Still being written, the wait starts over
+        fs::write(&dump, "create table dumps").unwrap();
+        fs::remove_file(&gone).unwrap();
+        assert!(tracker
+            .settled(&folders, start + Duration::from_secs(6))
+            .is_empty());
+        assert_eq!(tracker.len(), 1);
+        assert!(tracker
+            .settled(&folders, start + Duration::from_secs(10))
+            .is_empty());
+
+        assert_eq!(
+            tracker.settled(&folders, start + Duration::from_secs(11)),
+            vec![(dump.clone(), 0)]
+        );
+        assert!(tracker.is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_owning_folder() {
+        let roots = vec![
+            (PathBuf::from("/dumps"), false),
+            (PathBuf::from("/backups"), true),
+            (PathBuf::from("/backups/nightly"), false),
+        ];
+
+        assert_eq!(owning_folder(&roots, Path::new("/dumps/a.sql")), Some(0));
+        assert_eq!(owning_folder(&roots, Path::new("/dumps/old/a.sql")), None);
+        assert_eq!(
+            owning_folder(&roots, Path::new("/backups/old/a.sql")),
+            Some(1)
+        );
+        assert_eq!(
+            owning_folder(&roots, Path::new("/backups/nightly/a.sql")),
+            Some(2)
+        );
+        assert_eq!(owning_folder(&roots, Path::new("/backups")), None);
+        assert_eq!(owning_folder(&roots, Path::new("/other/a.sql")), None);
+
+        assert!(is_ignored(Path::new("/dumps/.a.sql")));
+        assert!(is_ignored(Path::new("/dumps/a.sql.tmp")));
+        assert!(is_ignored(Path::new("/dumps/a.sql~")));
+        assert!(!is_ignored(Path::new("/dumps/a.sql")));
+    }
+
+    #[test]
+    fn test_watch_folder_defaults() {
+        let mut config = test_config();
+        config.watch_folders = serde_json::from_str(
+            r#"[{"path":"/dumps"},{"path":"/backups","epochs":30,"encrypt":true,"shard_size":100,"settle_secs":60,"recursive":true}]"#,
+        )
+        .unwrap();
+        let folders = config.get_watch_folders();
+
+        assert_eq!(folders[0], WatchFolder::new("/dumps"));
+        assert_eq!(folders[0].get_epochs(&config), 10);
+        assert_eq!(folders[0].get_shard_size(&config), 5);
+        assert_eq!(folders[1].get_epochs(&config), 30);
+        assert_eq!(folders[1].get_shard_size(&config), 100);
+        assert!(folders[1].encrypt && folders[1].recursive);
+    }
+}
@@ -1,8 +1,15 @@
+pub mod blob_cache;
 pub mod client;
 pub mod config;
+pub mod cost;
 pub mod encryptor;
 pub mod epoch_extender;
+pub mod folder_watcher;
 pub mod migrator;
+pub mod reader;
+pub mod renewal;
 mod sharder;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
 pub mod types;
 pub mod updater;
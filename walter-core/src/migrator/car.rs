@@ -0,0 +1,442 @@
+use cid::Cid;
+use iroh_car::CarReader;
+use quick_protobuf::BytesReader;
+use std::collections::HashMap;
+use std::error::Error;
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncRead, AsyncWriteExt};
+
+// Multicodec codes of the blocks UnixFS files are made of
+const RAW: u64 = 0x55;
+const DAG_PB: u64 = 0x70;
+const SHA2_256: u64 = 0x12;
+const IDENTITY: u64 = 0x00;
+
+// UnixFS node types
+const UNIXFS_RAW: u64 = 0;
+const UNIXFS_DIRECTORY: u64 = 1;
+const UNIXFS_FILE: u64 = 2;
+const UNIXFS_HAMT_SHARD: u64 = 5;
+
+// Far deeper than the layouts IPFS builds, even for the largest files
+const MAX_DEPTH: usize = 128;
+
+fn verify(cid: &Cid, data: &[u8]) -> Result<(), Box<dyn Error>> {
+    let hash = cid.hash();
+    let matches = match hash.code() {
+        SHA2_256 => openssl::sha::sha256(data)[..] == *hash.digest(),
+        IDENTITY => data == hash.digest(),
+        code => return Err(format!("Unsupported hash function 0x{:x}", code).into()),
+    };
+    if !matches {
+        return Err("A block of the file doesn't match its CID".into());
+    }
+    Ok(())
+}
+
+enum Field<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+}
+
+// The varint and length-delimited fields of a protobuf message, in order
+fn fields(bytes: &[u8]) -> Result<Vec<(u32, Field<'_>)>, Box<dyn Error>> {
+    let mut reader = BytesReader::from_bytes(bytes);
+    let mut fields = Vec::new();
+    while !reader.is_eof() {
+        let tag = reader.next_tag(bytes)?;
+        let value = match tag & 7 {
+            0 => Field::Varint(reader.read_uint64(bytes)?),
+            2 => Field::Bytes(reader.read_bytes(bytes)?),
+            _ => {
+                reader.read_unknown(bytes, tag)?;
+                continue;
+            }
+        };
+        fields.push((tag >> 3, value));
+    }
+    Ok(fields)
+}
+
+// A block of a UnixFS file
+struct FileNode {
+    // What the block adds to the file itself
+    content: Vec<u8>,
+    links: Vec<Cid>,
+    // Size of the whole file below the block, when the block says so
+    size: Option<u64>,
+}
+
+fn file_node(data: &[u8]) -> Result<FileNode, Box<dyn Error>> {
+    let mut links = Vec::new();
+    let mut unixfs = None;
+    for (field, value) in fields(data)? {
+        match (field, value) {
+            (1, Field::Bytes(bytes)) => unixfs = Some(bytes),
+            (2, Field::Bytes(link)) => {
+                for (field, value) in fields(link)? {
+                    if let (1, Field::Bytes(hash)) = (field, value) {
+                        links.push(Cid::try_from(hash)?);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let (mut kind, mut content, mut size) = (None, Vec::new(), None);
+    for (field, value) in fields(unixfs.ok_or("A dag-pb block without UnixFS data")?)? {
+        match (field, value) {
+            (1, Field::Varint(value)) => kind = Some(value),
+            (2, Field::Bytes(bytes)) => content = bytes.to_vec(),
+            (3, Field::Varint(value)) => size = Some(value),
+            _ => {}
+        }
+    }
+    match kind {
+        Some(UNIXFS_RAW | UNIXFS_FILE) => Ok(FileNode {
+            content,
+            links,
+            size,
+        }),
+        Some(UNIXFS_DIRECTORY | UNIXFS_HAMT_SHARD) => {
+            Err("The CID is a directory, not a file".into())
+        }
+        _ => Err("Unsupported UnixFS node".into()),
+    }
+}
+
+// Verified blocks spooled to disk, read back by CID while the file is written
+struct Blocks {
+    file: File,
+    index: HashMap<Cid, (u64, usize)>,
+}
+
+impl Blocks {
+    fn get(&mut self, cid: &Cid) -> Result<Vec<u8>, Box<dyn Error>> {
+        if cid.hash().code() == IDENTITY {
+            return Ok(cid.hash().digest().to_vec());
+        }
+
+        let &(offset, len) = self
+            .index
+            .get(cid)
+            .ok_or("The CAR is missing a block of the file")?;
+        let mut data = vec![0; len];
+        self.file.seek(SeekFrom::Start(offset))?;
+        self.file.read_exact(&mut data)?;
+        Ok(data)
+    }
+}
+
+// Writes the contents under `root` depth first, the order UnixFS lays files
+// out in. Blocks may be linked any number of times, so the file is capped at
+// `max_size` and at the size its root declares.
+fn write_file(
+    root: &Cid,
+    blocks: &mut Blocks,
+    max_size: Option<u64>,
+    out: &mut impl Write,
+) -> Result<u64, Box<dyn Error>> {
+    let mut limit = max_size;
+    let mut written: u64 = 0;
+    // Blocks that add nothing are only worth visiting on the way to content
+    let mut empty_visits: u64 = 0;
+    let mut pending = vec![(*root, 0)];
+
+    while let Some((cid, depth)) = pending.pop() {
+        if depth > MAX_DEPTH {
+            return Err("The file's DAG is too deep".into());
+        }
+
+        let data = blocks.get(&cid)?;
+        let FileNode {
+            content,
+            links,
+            size,
+        } = match cid.codec() {
+            RAW => FileNode {
+                content: data,
+                links: Vec::new(),
+                size: None,
+            },
+            DAG_PB => file_node(&data)?,
+            codec => return Err(format!("Unsupported codec 0x{:x}", codec).into()),
+        };
+        if depth == 0 {
+            if let Some(size) = size {
+                limit = Some(limit.map_or(size, |limit| limit.min(size)));
+            }
+        }
+
+        if content.is_empty() {
+            empty_visits += 1;
+            if empty_visits > blocks.index.len() as u64 + written {
+                return Err("The file links to the same blocks over and over".into());
+            }
+        }
+        written += content.len() as u64;
+        if limit.is_some_and(|limit| written > limit) {
+            return Err("The file is larger than its declared size".into());
+        }
+
+        out.write_all(&content)?;
+        pending.extend(links.into_iter().rev().map(|link| (link, depth + 1)));
+    }
+
+    Ok(written)
+}
+
+// Reads every block of the CAR into the spool, then writes the file from it
+async fn unpack_with_spool(
+    car: impl AsyncRead + Send + Unpin,
+    root: Cid,
+    max_size: Option<u64>,
+    out: &Path,
+    spool_path: &Path,
+) -> Result<u64, Box<dyn Error>> {
+    let mut reader = CarReader::new(car).await?;
+    let spool = tokio::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(spool_path)
+        .await?;
+    let mut spool = tokio::io::BufWriter::new(spool);
+    let mut index = HashMap::new();
+    let mut offset = 0;
+
+    while let Some((cid, data)) = reader.next_block().await? {
+        verify(&cid, &data)?;
+        if index.contains_key(&cid) {
+            continue;
+        }
+        spool.write_all(&data).await?;
+        index.insert(cid, (offset, data.len()));
+        offset += data.len() as u64;
+    }
+    spool.flush().await?;
+
+    let mut blocks = Blocks {
+        file: spool.into_inner().into_std().await,
+        index,
+    };
+    let out = out.to_path_buf();
+    let written = tokio::task::spawn_blocking(move || {
+        File::create(&out)
+            .map_err(Into::into)
+            .and_then(|file| {
+                let mut file = BufWriter::new(file);
+                let written = write_file(&root, &mut blocks, max_size, &mut file)?;
+                file.flush()?;
+                Ok(written)
+            })
+            .map_err(|e: Box<dyn Error>| e.to_string())
+    })
+    .await??;
+
+    Ok(written)
+}
+
+/// Writes the file `root` out of a CAR stream, as trustless gateways and
+/// `dag export` return, to `out`. Every block is checked against its CID as
+/// it arrives and kept on disk next to `out` until the file is written, the
+/// CAR is never held in memory. Returns the number of bytes written.
+pub async fn unpack(
+    car: impl AsyncRead + Send + Unpin,
+    root: &str,
+    max_size: Option<u64>,
+    out: &Path,
+) -> Result<u64, Box<dyn Error>> {
+    let root = Cid::try_from(root)?;
+    let mut spool_path = OsString::from(out.as_os_str());
+    spool_path.push(".blocks");
+    let spool_path = PathBuf::from(spool_path);
+
+    // Errors aren't Send, they can't be held across the await below
+    let result = unpack_with_spool(car, root, max_size, out, &spool_path)
+        .await
+        .map_err(|e| e.to_string());
+    let _ = tokio::fs::remove_file(&spool_path).await;
+    Ok(result?)
+}
+
+#[cfg(test)]
+pub(super) mod fixtures {
+    use super::*;
+    use cid::multihash::Multihash;
+
+    fn varint_bytes(mut value: u64) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        while value >= 0x80 {
+            bytes.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        bytes.push(value as u8);
+        bytes
+    }
+
+    pub fn cid_of(codec: u64, data: &[u8]) -> Cid {
+        let hash = Multihash::wrap(SHA2_256, &openssl::sha::sha256(data)).unwrap();
+        Cid::new_v1(codec, hash)
+    }
+
+    pub fn car(root: &Cid, blocks: &[(Cid, Vec<u8>)]) -> Vec<u8> {
+        // {"roots": [root], "version": 1} in DAG-CBOR, where a CID is tag 42
+        // over its bytes with a zero in front
+        let root = root.to_bytes();
+        let mut header = b"\xa2\x65roots\x81\xd8\x2a\x58".to_vec();
+        header.extend([root.len() as u8 + 1, 0]);
+        header.extend(root);
+        header.extend(b"\x67version\x01");
+
+        let mut car = varint_bytes(header.len() as u64);
+        car.extend(header);
+        for (cid, data) in blocks {
+            let cid = cid.to_bytes();
+            car.extend(varint_bytes((cid.len() + data.len()) as u64));
+            car.extend(cid);
+            car.extend(data);
+        }
+        car
+    }
+
+    /// The CID and CAR of a file stored as a single raw block.
+    pub fn raw_file(data: &[u8]) -> (String, Vec<u8>) {
+        let cid = cid_of(RAW, data);
+        (cid.to_string(), car(&cid, &[(cid, data.to_vec())]))
+    }
+
+    fn length_delimited(field: u8, bytes: &[u8]) -> Vec<u8> {
+        let mut encoded = vec![field << 3 | 2];
+        encoded.extend(varint_bytes(bytes.len() as u64));
+        encoded.extend(bytes);
+        encoded
+    }
+
+    /// A dag-pb file node of `size` bytes linking to `leaves` in order.
+    pub fn dag_pb_file(leaves: &[Cid], size: u64) -> Vec<u8> {
+        let mut node = Vec::new();
+        for leaf in leaves {
+            node.extend(length_delimited(2, &length_delimited(1, &leaf.to_bytes())));
+        }
+        let mut unixfs = vec![0x08, UNIXFS_FILE as u8, 0x18];
+        unixfs.extend(varint_bytes(size));
+        node.extend(length_delimited(1, &unixfs));
+        node
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fixtures::*;
+    use super::*;
+
+    async fn unpacked(car: &[u8], root: &str, max_size: Option<u64>) -> Result<Vec<u8>, String> {
+        let out = std::env::temp_dir().join(format!("walter_car_{}", std::process::id()));
+        let written = unpack(car, root, max_size, &out)
+            .await
+            .map_err(|e| e.to_string())?;
+        let data = std::fs::read(&out).unwrap();
+        std::fs::remove_file(&out).unwrap();
+
+        assert_eq!(written, data.len() as u64);
+        Ok(data)
+    }
+
+    #[tokio::test]
+    async fn test_unpack() {
+        let (cid, archive) = raw_file(b"hello");
+        assert_eq!(unpacked(&archive, &cid, None).await.unwrap(), b"hello");
+
+        // Two raw leaves under a dag-pb root
+        let leaves = [b"hello ".to_vec(), b"world".to_vec()];
+        let leaf_cids: Vec<Cid> = leaves.iter().map(|leaf| cid_of(RAW, leaf)).collect();
+        let root = dag_pb_file(&leaf_cids, 11);
+        let root_cid = cid_of(DAG_PB, &root);
+        let mut blocks = vec![(root_cid, root)];
+        blocks.extend(leaf_cids.iter().cloned().zip(leaves.iter().cloned()));
+
+        let root = root_cid.to_string();
+        assert_eq!(
+            unpacked(&car(&root_cid, &blocks), &root, Some(11))
+                .await
+                .unwrap(),
+            b"hello world"
+        );
+
+        // A tampered leaf, a leaf left out and a source that knows the file is smaller
+        let mut tampered = blocks.clone();
+        tampered[2].1 = b"w0rld".to_vec();
+        assert_eq!(
+            unpacked(&car(&root_cid, &tampered), &root, None)
+                .await
+                .unwrap_err(),
+            "A block of the file doesn't match its CID"
+        );
+        assert!(unpacked(&car(&root_cid, &blocks[..2]), &root, None)
+            .await
+            .is_err());
+        assert!(unpacked(&car(&root_cid, &blocks), &root, Some(10))
+            .await
+            .is_err());
+
+        // CIDv0 of an empty file and of an empty directory
+        let empty_file = vec![0x0a, 0x04, 0x08, 0x02, 0x18, 0x00];
+        let v0: Cid = "QmbFMke1KXqnYyBBWxB74N4c5SBnJMVAiMNRcGu6x1AwQH"
+            .parse()
+            .unwrap();
+        let archive = car(&v0, &[(v0, empty_file)]);
+        assert!(unpacked(&archive, &v0.to_string(), None)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let empty_dir = vec![0x0a, 0x02, 0x08, 0x01];
+        let v0: Cid = "QmUNLLsPACCz1vLxQVkXqqLX5R1X345qqfHbsf67hvA3Nn"
+            .parse()
+            .unwrap();
+        let archive = car(&v0, &[(v0, empty_dir)]);
+        assert_eq!(
+            unpacked(&archive, &v0.to_string(), None).await.unwrap_err(),
+            "The CID is a directory, not a file"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unpack_repeated_links() {
+        let leaf = b"0123456789".to_vec();
+        let leaf_cid = cid_of(RAW, &leaf);
+
+        // Each level links the one below it twice, 2^40 leaves from 41 blocks
+        let mut blocks = vec![(leaf_cid, leaf)];
+        let mut below = leaf_cid;
+        for _ in 0..40 {
+            let node = dag_pb_file(&[below, below], 0);
+            below = cid_of(DAG_PB, &node);
+            blocks.push((below, node));
+        }
+        let archive = car(&below, &blocks);
+        assert_eq!(
+            unpacked(&archive, &below.to_string(), None)
+                .await
+                .unwrap_err(),
+            "The file is larger than its declared size"
+        );
+
+        // Repeated leaves are fine as long as the file is as big as it says
+        let node = dag_pb_file(&[leaf_cid, leaf_cid], 20);
+        let root = cid_of(DAG_PB, &node);
+        let archive = car(&root, &[(root, node), blocks[0].clone()]);
+        assert_eq!(
+            unpacked(&archive, &root.to_string(), Some(20))
+                .await
+                .unwrap(),
+            b"01234567890123456789"
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use std::error::Error;
+
+use crate::config::CidMapping;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn from_name(name: &str) -> Option<ExportFormat> {
+        match name.to_lowercase().as_str() {
+            "csv" => Some(ExportFormat::Csv),
+            "json" => Some(ExportFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+// Quotes a CSV field when it holds a separator, a quote or a line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// The CID to blob mapping of migrated files as a document other services
+/// can load. In CSV the blobs of a sharded file are joined with `;` in shard order.
+pub fn export_cid_mappings(
+    mappings: &[CidMapping],
+    format: ExportFormat,
+) -> Result<String, Box<dyn Error>> {
+    match format {
+        ExportFormat::Json => Ok(serde_json::to_string_pretty(mappings)?),
+        ExportFormat::Csv => {
+            let mut lines = vec!["cid,blob_ids,file_path,encrypted,url".to_string()];
+            for mapping in mappings {
+                lines.push(
+                    [
+                        csv_field(&mapping.cid),
+                        csv_field(&mapping.blobs.join(";")),
+                        csv_field(&mapping.file_path),
+                        mapping.is_encrypted.to_string(),
+                        csv_field(mapping.url.as_deref().unwrap_or("")),
+                    ]
+                    .join(","),
+                );
+            }
+            Ok(lines.join("\n") + "\n")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_cid_mappings() {
+        let mappings = vec![
+            CidMapping {
+                cid: "bafycat".to_string(),
+                file_path: "migrated/bafycat/cat, \"the\" original.png".to_string(),
+                blobs: vec!["blob_a".to_string()],
+                is_encrypted: false,
+                url: Some("https://aggregator/v1/blobs/blob_a".to_string()),
+            },
+            CidMapping {
+                cid: "bafydb".to_string(),
+                file_path: "migrated/bafydb/db.sqlite".to_string(),
+                blobs: vec!["blob_b".to_string(), "blob_c".to_string()],
+                is_encrypted: true,
+                url: None,
+            },
+        ];
+
+        assert_eq!(
+            export_cid_mappings(&mappings, ExportFormat::Csv).unwrap(),
+            "cid,blob_ids,file_path,encrypted,url\n\
+             bafycat,blob_a,\"migrated/bafycat/cat, \"\"the\"\" original.png\",false,https://aggregator/v1/blobs/blob_a\n\
+             bafydb,blob_b;blob_c,migrated/bafydb/db.sqlite,true,\n"
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&export_cid_mappings(&mappings, ExportFormat::Json).unwrap())
+                .unwrap();
+        assert_eq!(json[1]["blobs"][1], "blob_c");
+        assert_eq!(json[1]["url"], serde_json::Value::Null);
+
+        assert_eq!(ExportFormat::from_name("CSV"), Some(ExportFormat::Csv));
+        assert_eq!(ExportFormat::from_name("xml"), None);
+    }
+}
@@ -0,0 +1,169 @@
+use reqwest::header::CONTENT_LENGTH;
+use reqwest::Client;
+use std::error::Error;
+use std::path::Path;
+
+use super::{save_car, MigrationSource, SourceFile};
+
+pub const IPFS_GATEWAY_URL: &str = "https://ipfs.io";
+
+// Reads the CID of `file` as a CAR through the /ipfs/ path of a trustless
+// HTTP gateway, so every block can be checked against the CID
+pub(super) async fn fetch_cid(
+    client: &Client,
+    gateway_url: &str,
+    file: &SourceFile,
+    out: &Path,
+) -> Result<(), Box<dyn Error>> {
+    let url = format!("{}/ipfs/{}", gateway_url.trim_end_matches('/'), file.id);
+    let response = client
+        .get(&url)
+        .query(&[("format", "car")])
+        .header("Accept", "application/vnd.ipld.car")
+        .send()
+        .await?;
+    save_car(response, file, out).await
+}
+
+/// A list of CIDs read through a public or self-hosted IPFS HTTP gateway.
+pub struct GatewaySource {
+    gateway_url: String,
+    files: Vec<SourceFile>,
+    client: Client,
+}
+
+impl GatewaySource {
+    /// Files named after their CIDs.
+    pub fn new(gateway_url: &str, cids: &[String]) -> Self {
+        GatewaySource {
+            gateway_url: gateway_url.to_string(),
+            files: cids
+                .iter()
+                .map(|cid| SourceFile {
+                    id: cid.clone(),
+                    name: cid.clone(),
+                    cid: Some(cid.clone()),
+                    size: None,
+                    created_at: None,
+                })
+                .collect(),
+            client: Client::new(),
+        }
+    }
+
+    /// Files from a CID list with one CID per line, optionally followed by
+    /// the name to give the file. Blank lines and lines starting with `#`
+    /// are skipped.
+    pub fn from_cid_list(gateway_url: &str, cid_list: &str) -> Self {
+        let mut source = GatewaySource::new(gateway_url, &[]);
+        source.files = cid_list
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                let (cid, name) = line.split_once(char::is_whitespace).unwrap_or((line, line));
+                SourceFile {
+                    id: cid.to_string(),
+                    name: name.trim().to_string(),
+                    cid: Some(cid.to_string()),
+                    size: None,
+                    created_at: None,
+                }
+            })
+            .collect();
+        source
+    }
+}
+
+impl MigrationSource for GatewaySource {
+    fn name(&self) -> &str {
+        "IPFS gateway"
+    }
+
+    /// The listed CIDs, with their sizes when the gateway answers a HEAD request.
+    async fn list_files(&self) -> Result<Vec<SourceFile>, Box<dyn Error>> {
+        let mut files = self.files.clone();
+
+        for file in &mut files {
+            let url = format!(
+                "{}/ipfs/{}",
+                self.gateway_url.trim_end_matches('/'),
+                file.id
+            );
+            if let Ok(response) = self.client.head(&url).send().await {
+                file.size = response
+                    .headers()
+                    .get(CONTENT_LENGTH)
+                    .and_then(|length| length.to_str().ok()?.parse().ok())
+                    .filter(|_| response.status().is_success());
+            }
+        }
+
+        Ok(files)
+    }
+
+    async fn fetch(&self, file: &SourceFile, out: &Path) -> Result<(), Box<dyn Error>> {
+        fetch_cid(&self.client, &self.gateway_url, file, out).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::car::fixtures::raw_file;
+    use super::*;
+    use httpmock::prelude::*;
+
+    #[tokio::test]
+    async fn test_gateway_source() {
+        let (cid, car) = raw_file(b"hello");
+        let (tampered_cid, _) = raw_file(b"hullo");
+        let server = MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method(httpmock::Method::HEAD)
+                    .path(format!("/ipfs/{}", cid));
+                then.status(200).header("content-length", "5");
+            })
+            .await;
+        for path in [format!("/ipfs/{}", cid), format!("/ipfs/{}", tampered_cid)] {
+            server
+                .mock_async(|when, then| {
+                    when.method(GET)
+                        .path(path)
+                        .query_param("format", "car")
+                        .header("Accept", "application/vnd.ipld.car");
+                    then.status(200).body(&car);
+                })
+                .await;
+        }
+
+        let source = GatewaySource::from_cid_list(
+            &server.base_url(),
+            &format!(
+                "# exported pins\n{}  notes.txt\n\n{}\nbafymissing\n",
+                cid, tampered_cid
+            ),
+        );
+        let files = source.list_files().await.unwrap();
+        assert_eq!(
+            files
+                .iter()
+                .map(|file| (file.name.as_str(), file.size))
+                .collect::<Vec<_>>(),
+            vec![
+                ("notes.txt", Some(5)),
+                (tampered_cid.as_str(), None),
+                ("bafymissing", None)
+            ]
+        );
+
+        let out = std::env::temp_dir().join(format!("walter_gateway_{}", std::process::id()));
+        source.fetch(&files[0], &out).await.unwrap();
+        assert_eq!(std::fs::read(&out).unwrap(), b"hello");
+
+        // Content that doesn't match the CID is never written
+        assert!(source.fetch(&files[1], &out).await.is_err());
+        assert!(!out.exists());
+        assert!(source.fetch(&files[2], &out).await.is_err());
+    }
+}
@@ -0,0 +1,172 @@
+use reqwest::Client;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::Path;
+
+use super::{save_car, MigrationSource, SourceFile};
+
+pub const KUBO_API_URL: &str = "http://127.0.0.1:5001";
+
+#[derive(Deserialize)]
+struct PinList {
+    #[serde(rename = "Keys", default)]
+    keys: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct FileStat {
+    #[serde(rename = "Size")]
+    size: u64,
+    #[serde(rename = "Type")]
+    kind: String,
+}
+
+/// Everything pinned recursively on an IPFS node, read through its Kubo RPC API.
+pub struct KuboSource {
+    api_url: String,
+    client: Client,
+}
+
+impl KuboSource {
+    pub fn new(api_url: &str) -> Self {
+        KuboSource {
+            api_url: api_url.trim_end_matches('/').to_string(),
+            client: Client::new(),
+        }
+    }
+
+    // Every RPC call is a POST
+    async fn call(
+        &self,
+        command: &str,
+        arg: Option<&str>,
+    ) -> Result<reqwest::Response, Box<dyn Error>> {
+        let mut request = self
+            .client
+            .post(format!("{}/api/v0/{}", self.api_url, command));
+        if let Some(arg) = arg {
+            request = request.query(&[("arg", arg)]);
+        }
+        Ok(request.send().await?.error_for_status()?)
+    }
+}
+
+impl MigrationSource for KuboSource {
+    fn name(&self) -> &str {
+        "IPFS node"
+    }
+
+    /// Recursive pins named after their CIDs. Pinned directories are skipped,
+    /// only files can be stored as blobs.
+    async fn list_files(&self) -> Result<Vec<SourceFile>, Box<dyn Error>> {
+        let pins: PinList = self
+            .client
+            .post(format!("{}/api/v0/pin/ls", self.api_url))
+            .query(&[("type", "recursive")])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let mut cids: Vec<String> = pins.keys.into_keys().collect();
+        cids.sort();
+
+        let mut files = Vec::new();
+        for cid in cids {
+            let response = self
+                .call("files/stat", Some(&format!("/ipfs/{}", cid)))
+                .await
+                .ok();
+            let stat = match response {
+                Some(response) => response.json::<FileStat>().await.ok(),
+                None => None,
+            };
+            if matches!(&stat, Some(stat) if stat.kind == "directory") {
+                continue;
+            }
+
+            files.push(SourceFile {
+                id: cid.clone(),
+                name: cid.clone(),
+                cid: Some(cid),
+                size: stat.map(|stat| stat.size),
+                created_at: None,
+            });
+        }
+
+        Ok(files)
+    }
+
+    async fn fetch(&self, file: &SourceFile, out: &Path) -> Result<(), Box<dyn Error>> {
+        let response = self.call("dag/export", Some(&file.id)).await?;
+        save_car(response, file, out).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::car::fixtures::raw_file;
+    use super::*;
+    use httpmock::prelude::*;
+
+    #[tokio::test]
+    async fn test_kubo_source() {
+        let (cid, car) = raw_file(b"content");
+        let server = MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .path("/api/v0/pin/ls")
+                    .query_param("type", "recursive");
+                then.status(200).json_body(serde_json::json!({
+                    "Keys": {
+                        cid.clone(): {"Type": "recursive"},
+                        "bafydir": {"Type": "recursive"},
+                    }
+                }));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .path("/api/v0/files/stat")
+                    .query_param("arg", format!("/ipfs/{}", cid));
+                then.status(200)
+                    .json_body(serde_json::json!({"Hash": cid, "Size": 7, "Type": "file"}));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .path("/api/v0/files/stat")
+                    .query_param("arg", "/ipfs/bafydir");
+                then.status(200).json_body(
+                    serde_json::json!({"Hash": "bafydir", "Size": 0, "Type": "directory"}),
+                );
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .path("/api/v0/dag/export")
+                    .query_param("arg", &cid);
+                then.status(200).body(&car);
+            })
+            .await;
+
+        let source = KuboSource::new(&server.base_url());
+        let files = source.list_files().await.unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(
+            (files[0].id.as_str(), files[0].size),
+            (cid.as_str(), Some(7))
+        );
+
+        let out = std::env::temp_dir().join(format!("walter_kubo_{}", std::process::id()));
+        source.fetch(&files[0], &out).await.unwrap();
+        assert_eq!(std::fs::read(&out).unwrap(), b"content");
+        std::fs::remove_file(&out).unwrap();
+    }
+}
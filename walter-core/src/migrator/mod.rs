@@ -1,64 +1,311 @@
-use crate::client::WalrusClient;
+use crate::client::{walrus_info, WalrusClient};
 use crate::config::WalterConfig;
-use reqwest::Client;
+use futures_util::TryStreamExt;
+use serde::Serialize;
 use std::error::Error;
-use std::fs::write as write_file;
+use std::future::Future;
 use std::path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::io::AsyncWriteExt;
+use tokio_util::io::StreamReader;
 
-const PINATA_URL: &str = "https://api.pinata.cloud/v3/";
+mod car;
+mod export;
+mod gateway;
+mod kubo;
+mod pinata;
+mod plan;
+mod s3;
+mod state;
 
-pub async fn get_file_list(jwt: &str) -> Result<serde_json::Value, Box<dyn Error>> {
-    let client = Client::new();
+pub use export::{export_cid_mappings, ExportFormat};
+pub use gateway::{GatewaySource, IPFS_GATEWAY_URL};
+pub use kubo::{KuboSource, KUBO_API_URL};
+pub use pinata::PinataSource;
+pub use plan::{estimate_migration_cost, MigrationPlan, PlannedFile};
+pub use s3::{S3Source, S3_ENDPOINT, S3_REGION};
+pub use state::{
+    default_state_path, MigrationRecord, MigrationReport, MigrationState, MigrationStatus,
+};
 
-    let response = client
-        .get(format!("{}files", PINATA_URL))
-        .header("Authorization", format!("Bearer {}", jwt))
-        .header("Content-Type", "application/json")
-        .send()
-        .await?;
+/// A file held by a migration source.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SourceFile {
+    /// What the source fetches the file by, its CID or its S3 key
+    pub id: String,
+    pub name: String,
+    pub cid: Option<String>,
+    pub size: Option<u64>,
+    /// When the file was added to the source, as the source reports it
+    pub created_at: Option<String>,
+}
+
+impl SourceFile {
+    /// `created_at` in seconds since the Unix epoch, for RFC 3339 dates in UTC
+    /// like the sources report them.
+    pub fn created_secs(&self) -> Option<u64> {
+        let date = self.created_at.as_deref()?;
+        let field = |range: std::ops::Range<usize>| date.get(range)?.parse::<i64>().ok();
+        let (year, month, day) = (field(0..4)?, field(5..7)?, field(8..10)?);
+        let (hour, minute, second) = match date.get(10..11) {
+            Some("T") | Some(" ") => (field(11..13)?, field(14..16)?, field(17..19)?),
+            _ => (0, 0, 0),
+        };
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return None;
+        }
+
+        // Days since 1970-01-01 in the proleptic Gregorian calendar
+        let year = if month <= 2 { year - 1 } else { year };
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        let days = era * 146097 + day_of_era - 719468;
+        u64::try_from(days * 86400 + hour * 3600 + minute * 60 + second).ok()
+    }
+}
+
+/// How the files of one migration batch are uploaded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MigrationOptions {
+    pub epochs: u16,
+    pub shard_size: usize,
+    /// Encrypts every file of the batch with this password
+    pub password: Option<String>,
+    /// IDs of the files to migrate, every file of the source when empty
+    pub only: Vec<String>,
+}
+
+impl MigrationOptions {
+    /// Every file, unencrypted, with the default epochs and shard size.
+    pub fn from_config(config: &WalterConfig) -> Self {
+        MigrationOptions {
+            epochs: config.get_default_epochs(),
+            shard_size: config.get_default_shard_size(),
+            password: None,
+            only: Vec::new(),
+        }
+    }
 
-    let response = response.json::<serde_json::Value>().await?;
-    Ok(response)
+    pub fn includes(&self, file: &SourceFile) -> bool {
+        self.only.is_empty() || self.only.contains(&file.id)
+    }
+}
+
+/// Another storage provider whose files can be migrated to Walrus.
+pub trait MigrationSource {
+    /// Shown in progress messages and errors.
+    fn name(&self) -> &str;
+
+    /// Every file the source holds.
+    fn list_files(&self) -> impl Future<Output = Result<Vec<SourceFile>, Box<dyn Error>>> + Send;
+
+    /// Writes the contents of `file` to `out`.
+    fn fetch(
+        &self,
+        file: &SourceFile,
+        out: &path::Path,
+    ) -> impl Future<Output = Result<(), Box<dyn Error>>> + Send;
 }
 
-pub async fn download_ipfs_file(file_path: &str, cid: &str) -> Result<(), Box<dyn Error>> {
-    let url = format!("https://ipfs.io/ipfs/{}", cid);
-    let client = reqwest::Client::new();
-    let response = client.get(&url).send().await?;
-    let body = response.bytes().await?;
-    write_file(file_path, body)?;
+// Streams the body of a response into `out`, failing on error statuses
+async fn save_response(
+    response: reqwest::Response,
+    out: &path::Path,
+) -> Result<(), Box<dyn Error>> {
+    let mut response = response.error_for_status()?;
+    let mut file = tokio::fs::File::create(out).await?;
+    while let Some(chunk) = response.chunk().await? {
+        file.write_all(&chunk).await?;
+    }
+    file.flush().await?;
     Ok(())
 }
 
-pub async fn migrate_files(jwt: &str) -> Result<(), Box<dyn Error>> {
-    let files = get_file_list(jwt).await?;
+// Unpacks a CAR response into `file`, failing on error statuses, on any
+// block that doesn't match its CID and on more data than the file can hold
+async fn save_car(
+    response: reqwest::Response,
+    file: &SourceFile,
+    out: &path::Path,
+) -> Result<(), Box<dyn Error>> {
+    let body = response
+        .error_for_status()?
+        .bytes_stream()
+        .map_err(std::io::Error::other);
+    let result = car::unpack(StreamReader::new(body), &file.id, file.size, out)
+        .await
+        .map_err(|e| e.to_string());
+    if result.is_err() {
+        let _ = tokio::fs::remove_file(out).await;
+    }
+    result.map(|_| ()).map_err(Into::into)
+}
 
-    if !files.get("data").is_some() {
-        return Err("Invalid API key.".into());
+// Keeps names from the source from escaping the download dir
+fn sanitize(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c == '/' || c == '\\' || c.is_control() {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    match name.as_str() {
+        "" | "." | ".." => "file".to_string(),
+        _ => name,
     }
+}
+
+/// Where a migrated file is downloaded to, in a directory of its own so
+/// files with the same name don't overwrite each other.
+pub fn download_path(download_dir: &path::Path, record_key: &str, name: &str) -> path::PathBuf {
+    download_dir
+        .join("migrated")
+        .join(sanitize(record_key))
+        .join(sanitize(name))
+}
 
+async fn migrate_file(
+    source: &(impl MigrationSource + Sync),
+    file: &SourceFile,
+    file_path: &path::Path,
+    options: &MigrationOptions,
+    walrus_client: &mut WalrusClient,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    if let Some(dir) = file_path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    source.fetch(file, file_path).await?;
+
+    let file_path = file_path.to_string_lossy().to_string();
+    walrus_client
+        .upload_file_with_options(
+            &file_path,
+            options.password.clone(),
+            options.epochs,
+            options.shard_size,
+        )
+        .await?;
+    // Kept with the file so links to the CID can be rewritten to its blobs
+    if let Some(cid) = &file.cid {
+        walrus_client.config.set_file_cid(&file_path, cid);
+        walrus_client.config.save_config_file();
+    }
+    Ok(walrus_client
+        .config
+        .get_file_blobs(&file_path)
+        .cloned()
+        .unwrap_or_default())
+}
+
+/// Lists the files of `source` that `migrate_from` would upload with
+/// `options`, with their estimated cost, without downloading anything.
+pub async fn plan_migration(
+    source: &(impl MigrationSource + Sync),
+    options: &MigrationOptions,
+) -> Result<MigrationPlan, Box<dyn Error>> {
+    let files = source.list_files().await?;
+    let state = MigrationState::load(&default_state_path())?;
+    // Without the walrus CLI the files are still listed, just without a cost
+    let info = walrus_info().await.ok();
+    Ok(MigrationPlan::new(
+        source.name(),
+        files,
+        options,
+        info.as_ref(),
+        &state,
+    ))
+}
+
+/// Downloads the files of `source` picked by `options` into the download
+/// dir and uploads them to Walrus. Files migrated by an earlier run are
+/// skipped, a file that fails is recorded and the run moves on to the next one.
+pub async fn migrate_from(
+    source: &(impl MigrationSource + Sync),
+    options: &MigrationOptions,
+) -> Result<MigrationReport, Box<dyn Error>> {
+    let files: Vec<SourceFile> = source
+        .list_files()
+        .await?
+        .into_iter()
+        .filter(|file| options.includes(file))
+        .collect();
+
+    let state_path = default_state_path();
+    let mut state = MigrationState::load(&state_path)?;
     let config: WalterConfig = WalterConfig::load_config_file();
-    let default_file_download_dir = config.get_default_file_download_dir();
-    let download_dir = path::Path::new(&default_file_download_dir);
-    let download_dir = shellexpand::tilde(&download_dir.to_string_lossy()).to_string();
-    let download_dir = path::Path::new(&download_dir);
+    let download_dir = config.get_download_dir_path();
 
     let mut walrus_client = WalrusClient::new(config.clone());
-    if let Some(file_list) = files["data"]["files"].as_array() {
-        for file in file_list {
-            if let (Some(name), Some(cid)) = (file["name"].as_str(), file["cid"].as_str()) {
-                let file_path = download_dir.join(name).to_string_lossy().to_string();
-                if !path::Path::new(&download_dir).exists() {
-                    std::fs::create_dir_all(&download_dir)?;
-                }
-                download_ipfs_file(&file_path, cid).await?;
-                walrus_client.upload_file(&file_path, None).await?;
+    let mut report = MigrationReport::default();
+    for file in &files {
+        let mut record = MigrationRecord {
+            source: source.name().to_string(),
+            id: file.id.clone(),
+            cid: file.cid.clone(),
+            name: file.name.clone(),
+            file_path: None,
+            blobs: Vec::new(),
+            status: MigrationStatus::Failed,
+            error: None,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or(0),
+        };
+        if let Some(done) = state.done(&record.key()) {
+            // Files migrated before CIDs were kept in the config get theirs now
+            if let (Some(cid), Some(file_path)) = (&done.cid, &done.file_path) {
+                walrus_client.config.set_file_cid(file_path, cid);
             }
+            report.skipped.push(done.clone());
+            continue;
         }
+
+        let file_path = download_path(&download_dir, &record.key(), &file.name);
+        let result = migrate_file(source, file, &file_path, options, &mut walrus_client)
+            .await
+            .map_err(|e| e.to_string());
+        match result {
+            Ok(blobs) => {
+                record.status = MigrationStatus::Done;
+                record.file_path = Some(file_path.to_string_lossy().to_string());
+                record.blobs = blobs;
+                report.migrated.push(record.clone());
+            }
+            Err(e) => {
+                record.error = Some(e);
+                report.failed.push(record.clone());
+            }
+        }
+
+        state.record(record);
+        state.save(&state_path)?;
     }
 
     walrus_client.config.save_config_file();
-    Ok(())
+    Ok(report)
+}
+
+/// Migrates the files pinned on Pinata picked by `options`.
+pub async fn migrate_files(
+    jwt: &str,
+    options: &MigrationOptions,
+) -> Result<MigrationReport, Box<dyn Error>> {
+    migrate_from(&PinataSource::new(jwt), options).await
+}
+
+/// Dry run of `migrate_files`, see `plan_migration`.
+pub async fn plan_files(
+    jwt: &str,
+    options: &MigrationOptions,
+) -> Result<MigrationPlan, Box<dyn Error>> {
+    plan_migration(&PinataSource::new(jwt), options).await
 }
 
 #[cfg(test)]
@@ -66,9 +313,50 @@ mod tests {
 
     use super::*;
     const JWT : &str = "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.eyJ1c2VySW5mb3JtYXRpb24iOnsiaWQiOiI4YzAxZGVjYy1iZmFiLTQ4Y2UtOTQyMy05NjJkMWNkYjlhODYiLCJlbWFpbCI6InByYW5lZXRoc2Fyb2RlQGdtYWlsLmNvbSIsImVtYWlsX3ZlcmlmaWVkIjp0cnVlLCJwaW5fcG9saWN5Ijp7InJlZ2lvbnMiOlt7ImRlc2lyZWRSZXBsaWNhdGlvbkNvdW50IjoxLCJpZCI6IkZSQTEifSx7ImRlc2lyZWRSZXBsaWNhdGlvbkNvdW50IjoxLCJpZCI6Ik5ZQzEifV0sInZlcnNpb24iOjF9LCJtZmFfZW5hYmxlZCI6ZmFsc2UsInN0YXR1cyI6IkFDVElWRSJ9LCJhdXRoZW50aWNhdGlvblR5cGUiOiJzY29wZWRLZXkiLCJzY29wZWRLZXlLZXkiOiJmOTg4MzJhZDZkZmI0Mzk0NWM3MyIsInNjb3BlZEtleVNlY3JldCI6IjhlMTE3NTFlMjE2ZTczYWI4MWIxYWQ5NDkwYjliYWYyN2RiNDVhNjU3NzQzNzVhZTNjMzI2N2U4NDMzODBhNDUiLCJleHAiOjE3NjUxMTQ2OTF9.Gl5_t61lvIF4jds9ZNnXiEZdE_O4E9_imFeuYPiJqEE";
+    #[test]
+    fn test_download_path() {
+        let dir = path::Path::new("/downloads");
+        assert_eq!(
+            download_path(dir, "bafyone", "cat.png"),
+            path::Path::new("/downloads/migrated/bafyone/cat.png")
+        );
+        assert_ne!(
+            download_path(dir, "bafyone", "cat.png"),
+            download_path(dir, "bafytwo", "cat.png")
+        );
+        assert_eq!(
+            download_path(dir, "s3://photos/2024/a.jpg", "../a.jpg"),
+            path::Path::new("/downloads/migrated/s3:__photos_2024_a.jpg/.._a.jpg")
+        );
+        assert_eq!(
+            download_path(dir, "bafyone", ".."),
+            path::Path::new("/downloads/migrated/bafyone/file")
+        );
+    }
+
+    #[test]
+    fn test_created_secs() {
+        let file = |created_at: &str| SourceFile {
+            id: "bafyone".to_string(),
+            name: "cat.png".to_string(),
+            cid: None,
+            size: None,
+            created_at: Some(created_at.to_string()),
+        };
+        assert_eq!(file("1970-01-01T00:00:00Z").created_secs(), Some(0));
+        assert_eq!(
+            file("2024-11-20T10:30:15.000Z").created_secs(),
+            Some(1732098615)
+        );
+        assert_eq!(file("2000-02-29").created_secs(), Some(951782400));
+        assert_eq!(file("yesterday").created_secs(), None);
+        assert_eq!(file("2024-13-01").created_secs(), None);
+    }
+
     #[tokio::test]
     async fn test_migration() {
-        let result = migrate_files(JWT).await;
+        let config = WalterConfig::load_config_file();
+        let result = migrate_files(JWT, &MigrationOptions::from_config(&config)).await;
         assert!(result.is_ok());
     }
 }
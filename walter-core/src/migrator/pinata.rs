@@ -0,0 +1,177 @@
+use reqwest::Client;
+use std::error::Error;
+use std::path::Path;
+
+use super::gateway::{fetch_cid, IPFS_GATEWAY_URL};
+use super::{MigrationSource, SourceFile};
+
+const PINATA_URL: &str = "https://api.pinata.cloud/v3/";
+const PAGE_SIZE: usize = 1000;
+
+/// Files pinned on Pinata, listed through its v3 API and read through an
+/// IPFS gateway.
+pub struct PinataSource {
+    jwt: String,
+    api_url: String,
+    gateway_url: String,
+    client: Client,
+}
+
+impl PinataSource {
+    pub fn new(jwt: &str) -> Self {
+        PinataSource::with_urls(jwt, PINATA_URL, IPFS_GATEWAY_URL)
+    }
+
+    /// Against another API and gateway, e.g. a dedicated Pinata gateway.
+    pub fn with_urls(jwt: &str, api_url: &str, gateway_url: &str) -> Self {
+        PinataSource {
+            jwt: jwt.to_string(),
+            api_url: api_url.to_string(),
+            gateway_url: gateway_url.to_string(),
+            client: Client::new(),
+        }
+    }
+}
+
+impl MigrationSource for PinataSource {
+    fn name(&self) -> &str {
+        "Pinata"
+    }
+
+    /// Every page of the file list, `PAGE_SIZE` files at a time.
+    async fn list_files(&self) -> Result<Vec<SourceFile>, Box<dyn Error>> {
+        let mut files = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let mut query = vec![("limit", PAGE_SIZE.to_string())];
+            if let Some(token) = &page_token {
+                query.push(("pageToken", token.clone()));
+            }
+            let response = self
+                .client
+                .get(format!("{}/files", self.api_url.trim_end_matches('/')))
+                .query(&query)
+                .header("Authorization", format!("Bearer {}", self.jwt))
+                .header("Content-Type", "application/json")
+                .send()
+                .await?
+                .json::<serde_json::Value>()
+                .await?;
+
+            let Some(page) = response["data"]["files"].as_array() else {
+                return Err("Invalid API key.".into());
+            };
+            files.extend(page.iter().filter_map(|file| {
+                let cid = file["cid"].as_str()?;
+                Some(SourceFile {
+                    id: cid.to_string(),
+                    name: file["name"].as_str().unwrap_or(cid).to_string(),
+                    cid: Some(cid.to_string()),
+                    size: file["size"].as_u64(),
+                    created_at: file["created_at"].as_str().map(str::to_string),
+                })
+            }));
+
+            // The last page has no token, or repeats the one it was asked for
+            let next_token = response["data"]["next_page_token"]
+                .as_str()
+                .filter(|token| !token.is_empty())
+                .map(str::to_string);
+            if page.is_empty() || next_token.is_none() || next_token == page_token {
+                break;
+            }
+            page_token = next_token;
+        }
+
+        Ok(files)
+    }
+
+    async fn fetch(&self, file: &SourceFile, out: &Path) -> Result<(), Box<dyn Error>> {
+        fetch_cid(&self.client, &self.gateway_url, file, out).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::car::fixtures::raw_file;
+    use super::*;
+    use httpmock::prelude::*;
+
+    #[tokio::test]
+    async fn test_pinata_source() {
+        let (cat_cid, cat_car) = raw_file(b"cat");
+        let server = MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/v3/files")
+                    .query_param_missing("pageToken")
+                    .header("Authorization", "Bearer jwt");
+                then.status(200).json_body(serde_json::json!({
+                    "data": {
+                        "files": [
+                            {"name": "cat.png", "cid": cat_cid, "size": 3, "created_at": "2024-11-20T10:00:00Z"},
+                        ],
+                        "next_page_token": "page2",
+                    }
+                }));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/v3/files")
+                    .query_param("pageToken", "page2")
+                    .header("Authorization", "Bearer jwt");
+                then.status(200).json_body(serde_json::json!({
+                    "data": {
+                        "files": [{"name": null, "cid": "bafyunnamed", "size": 4}],
+                        "next_page_token": null,
+                    }
+                }));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/v3/files")
+                    .header("Authorization", "Bearer wrong");
+                then.status(401)
+                    .json_body(serde_json::json!({"error": "Unauthorized"}));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET).path(format!("/ipfs/{}", cat_cid));
+                then.status(200).body(&cat_car);
+            })
+            .await;
+
+        let api_url = server.url("/v3/");
+        let source = PinataSource::with_urls("jwt", &api_url, &server.base_url());
+        let files = source.list_files().await.unwrap();
+        assert_eq!(
+            files[0],
+            SourceFile {
+                id: cat_cid.clone(),
+                name: "cat.png".to_string(),
+                cid: Some(cat_cid.clone()),
+                size: Some(3),
+                created_at: Some("2024-11-20T10:00:00Z".to_string()),
+            }
+        );
+        assert_eq!(files[1].name, "bafyunnamed");
+
+        let out = std::env::temp_dir().join(format!("walter_pinata_{}", std::process::id()));
+        source.fetch(&files[0], &out).await.unwrap();
+        assert_eq!(std::fs::read(&out).unwrap(), b"cat");
+        std::fs::remove_file(&out).unwrap();
+
+        let source = PinataSource::with_urls("wrong", &api_url, &server.base_url());
+        assert_eq!(
+            source.list_files().await.unwrap_err().to_string(),
+            "Invalid API key."
+        );
+    }
+}
@@ -0,0 +1,195 @@
+use serde::Serialize;
+
+use super::state::{state_key, MigrationState};
+use super::{MigrationOptions, SourceFile};
+use crate::cost::{estimate_file_cost, format_cost, total_cost};
+use crate::types::WalrusInfo;
+
+/// A file a migration would upload, as listed by a dry run.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct PlannedFile {
+    #[serde(flatten)]
+    pub file: SourceFile,
+    /// In FROST, unknown when the source doesn't list the size
+    pub estimated_cost: Option<u64>,
+    /// Migrated by an earlier run, a migration would skip it
+    pub migrated: bool,
+}
+
+/// What migrating a source would do, without downloading or uploading anything.
+#[derive(Serialize, Debug, Default, Clone, PartialEq)]
+pub struct MigrationPlan {
+    pub source: String,
+    pub files: Vec<PlannedFile>,
+}
+
+/// Estimated cost of uploading `file` with `options`, the way `migrate_from` does.
+pub fn estimate_migration_cost(
+    file: &SourceFile,
+    options: &MigrationOptions,
+    info: Option<&WalrusInfo>,
+) -> Option<u64> {
+    Some(total_cost(&estimate_file_cost(
+        info?,
+        file.size?,
+        options.shard_size as u64,
+        options.password.is_some(),
+        options.epochs,
+    )))
+}
+
+impl MigrationPlan {
+    pub fn new(
+        source: &str,
+        files: Vec<SourceFile>,
+        options: &MigrationOptions,
+        info: Option<&WalrusInfo>,
+        state: &MigrationState,
+    ) -> Self {
+        MigrationPlan {
+            source: source.to_string(),
+            files: files
+                .into_iter()
+                .filter(|file| options.includes(file))
+                .map(|file| PlannedFile {
+                    estimated_cost: estimate_migration_cost(&file, options, info),
+                    migrated: state
+                        .done(&state_key(source, &file.id, file.cid.as_deref()))
+                        .is_some(),
+                    file,
+                })
+                .collect(),
+        }
+    }
+
+    /// Estimated cost of the files that still need migrating, and how many
+    /// of them have no estimate.
+    pub fn total_cost(&self) -> (u64, usize) {
+        let pending: Vec<&PlannedFile> = self.files.iter().filter(|file| !file.migrated).collect();
+        (
+            pending.iter().filter_map(|file| file.estimated_cost).sum(),
+            pending
+                .iter()
+                .filter(|file| file.estimated_cost.is_none())
+                .count(),
+        )
+    }
+
+    pub fn describe(&self) -> String {
+        let mut lines: Vec<String> = self
+            .files
+            .iter()
+            .map(|planned| {
+                format!(
+                    "{}  {}  {}  {}{}",
+                    planned.file.name,
+                    planned
+                        .file
+                        .size
+                        .map(|size| format!("{} bytes", size))
+                        .unwrap_or("unknown size".to_string()),
+                    planned.file.cid.as_deref().unwrap_or(&planned.file.id),
+                    planned
+                        .estimated_cost
+                        .map(format_cost)
+                        .unwrap_or("unknown cost".to_string()),
+                    if planned.migrated {
+                        ", already migrated"
+                    } else {
+                        ""
+                    }
+                )
+            })
+            .collect();
+
+        let (cost, unknown) = self.total_cost();
+        let pending = self.files.iter().filter(|file| !file.migrated).count();
+        lines.push(format!(
+            "{} of {} files from {} to migrate, estimated cost {}{}",
+            pending,
+            self.files.len(),
+            self.source,
+            format_cost(cost),
+            if unknown > 0 {
+                format!(" plus {} files of unknown size", unknown)
+            } else {
+                String::new()
+            }
+        ));
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::state::{MigrationRecord, MigrationStatus};
+    use super::*;
+    use crate::test_util::walrus_info;
+
+    fn source_file(cid: &str, size: Option<u64>) -> SourceFile {
+        SourceFile {
+            id: cid.to_string(),
+            name: format!("{}.bin", cid),
+            cid: Some(cid.to_string()),
+            size,
+            created_at: None,
+        }
+    }
+
+    #[test]
+    fn test_migration_plan() {
+        let info = walrus_info();
+        let mut state = MigrationState::default();
+        state.record(MigrationRecord {
+            source: "Pinata".to_string(),
+            id: "bafydone".to_string(),
+            cid: Some("bafydone".to_string()),
+            name: "bafydone.bin".to_string(),
+            file_path: None,
+            blobs: vec!["blob".to_string()],
+            status: MigrationStatus::Done,
+            error: None,
+            timestamp: 1,
+        });
+        let files = vec![
+            source_file("bafynew", Some(1000)),
+            source_file("bafydone", Some(1000)),
+            source_file("bafyunknown", None),
+            source_file("bafyleftout", Some(1000)),
+        ];
+        let mut options = MigrationOptions {
+            epochs: 5,
+            shard_size: 100,
+            password: None,
+            only: vec![
+                "bafynew".to_string(),
+                "bafydone".to_string(),
+                "bafyunknown".to_string(),
+            ],
+        };
+
+        let plan = MigrationPlan::new("Pinata", files.clone(), &options, Some(&info), &state);
+        assert_eq!(plan.files.len(), 3);
+        assert!(!plan.files[0].migrated);
+        assert!(plan.files[1].migrated);
+        assert_eq!(plan.files[2].estimated_cost, None);
+
+        // Ten 100 byte shards, the migrated file isn't counted
+        let new_cost = plan.files[0].estimated_cost.unwrap();
+        assert_eq!(
+            new_cost,
+            total_cost(&estimate_file_cost(&info, 1000, 100, false, 5))
+        );
+        assert_eq!(plan.total_cost(), (new_cost, 1));
+        assert!(plan.describe().ends_with(&format!(
+            "2 of 3 files from Pinata to migrate, estimated cost {} plus 1 files of unknown size",
+            format_cost(new_cost)
+        )));
+
+        // Encryption and more epochs cost more, and without system info nothing is known
+        options.password = Some("secret".to_string());
+        options.epochs = 10;
+        assert!(estimate_migration_cost(&files[0], &options, Some(&info)).unwrap() > new_cost);
+        assert_eq!(estimate_migration_cost(&files[0], &options, None), None);
+    }
+}
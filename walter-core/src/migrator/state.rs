@@ -0,0 +1,208 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const MIGRATION_STATE_PATH: &str = "~/.walter/migration.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum MigrationStatus {
+    Done,
+    Failed,
+}
+
+/// The outcome of the last attempt to migrate one file.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MigrationRecord {
+    pub source: String,
+    pub id: String,
+    pub cid: Option<String>,
+    pub name: String,
+    /// Where the file was registered
+    pub file_path: Option<String>,
+    pub blobs: Vec<String>,
+    pub status: MigrationStatus,
+    pub error: Option<String>,
+    pub timestamp: u64,
+}
+
+/// The key the record of a file is kept under, the file's CID when it has one.
+pub(super) fn state_key(source: &str, id: &str, cid: Option<&str>) -> String {
+    match cid {
+        Some(cid) => cid.to_string(),
+        None => format!("{}/{}", source, id),
+    }
+}
+
+impl MigrationRecord {
+    /// The key the record is kept under, see `state_key`.
+    pub fn key(&self) -> String {
+        state_key(&self.source, &self.id, self.cid.as_deref())
+    }
+
+    pub fn describe(&self) -> String {
+        let name = match &self.cid {
+            Some(cid) => format!("{} ({})", cid, self.name),
+            None => format!("{}/{}", self.source, self.id),
+        };
+
+        match (&self.status, &self.error) {
+            (MigrationStatus::Failed, Some(e)) => format!("{}: failed, {}", name, e),
+            (MigrationStatus::Failed, None) => format!("{}: failed", name),
+            (MigrationStatus::Done, _) => format!("{} -> {}", name, self.blobs.join(", ")),
+        }
+    }
+}
+
+/// Every file a migration has tried, persisted after each file so a rerun
+/// skips what is already on Walrus and retries what failed.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct MigrationState {
+    pub files: BTreeMap<String, MigrationRecord>,
+}
+
+pub fn default_state_path() -> PathBuf {
+    PathBuf::from(shellexpand::tilde(MIGRATION_STATE_PATH).to_string())
+}
+
+impl MigrationState {
+    /// The state saved at `path`, empty if nothing was migrated yet.
+    pub fn load(path: &Path) -> Result<MigrationState, Box<dyn Error>> {
+        if !path.exists() {
+            return Ok(MigrationState::default());
+        }
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // Written aside and renamed so an interrupted run can't truncate it
+        let temp_path = path.with_extension("json.part");
+        fs::write(&temp_path, serde_json::to_string_pretty(self)?)?;
+        fs::rename(&temp_path, path)?;
+        Ok(())
+    }
+
+    /// The record of a file that was migrated successfully.
+    pub fn done(&self, key: &str) -> Option<&MigrationRecord> {
+        self.files
+            .get(key)
+            .filter(|record| record.status == MigrationStatus::Done)
+    }
+
+    pub fn record(&mut self, record: MigrationRecord) {
+        self.files.insert(record.key(), record);
+    }
+}
+
+/// What a migration run did with each file of the source.
+#[derive(Serialize, Debug, Default, Clone, PartialEq)]
+pub struct MigrationReport {
+    pub migrated: Vec<MigrationRecord>,
+    /// Migrated by an earlier run
+    pub skipped: Vec<MigrationRecord>,
+    pub failed: Vec<MigrationRecord>,
+}
+
+impl MigrationReport {
+    /// Every file now on Walrus with its blob IDs, keyed by CID.
+    pub fn blobs_by_cid(&self) -> BTreeMap<String, Vec<String>> {
+        self.migrated
+            .iter()
+            .chain(&self.skipped)
+            .filter_map(|record| Some((record.cid.clone()?, record.blobs.clone())))
+            .collect()
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "Migrated {} files, skipped {} already migrated, {} failed",
+            self.migrated.len(),
+            self.skipped.len(),
+            self.failed.len()
+        )
+    }
+
+    pub fn describe(&self) -> String {
+        let mut lines: Vec<String> = self
+            .migrated
+            .iter()
+            .chain(&self.skipped)
+            .chain(&self.failed)
+            .map(MigrationRecord::describe)
+            .collect();
+        lines.push(self.summary());
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(cid: &str, status: MigrationStatus) -> MigrationRecord {
+        MigrationRecord {
+            source: "Pinata".to_string(),
+            id: cid.to_string(),
+            cid: Some(cid.to_string()),
+            name: "cat.png".to_string(),
+            file_path: None,
+            blobs: vec![format!("blob-{}", cid)],
+            status,
+            error: None,
+            timestamp: 1,
+        }
+    }
+
+    #[test]
+    fn test_migration_state() {
+        let path = std::env::temp_dir()
+            .join(format!("walter_migration_{}", std::process::id()))
+            .join("migration.json");
+        let mut state = MigrationState::load(&path).unwrap();
+        assert!(state.files.is_empty());
+
+        state.record(record("bafydone", MigrationStatus::Done));
+        state.record(record("bafyfailed", MigrationStatus::Failed));
+        state.save(&path).unwrap();
+
+        let mut state = MigrationState::load(&path).unwrap();
+        assert!(state.done("bafydone").is_some());
+        assert!(state.done("bafyfailed").is_none());
+
+        // A retry that succeeds replaces the failure
+        state.record(record("bafyfailed", MigrationStatus::Done));
+        assert!(state.done("bafyfailed").is_some());
+        assert_eq!(state.files.len(), 2);
+
+        let s3 = MigrationRecord {
+            source: "s3://photos".to_string(),
+            id: "2024/a.jpg".to_string(),
+            cid: None,
+            ..record("", MigrationStatus::Done)
+        };
+        assert_eq!(s3.key(), "s3://photos/2024/a.jpg");
+
+        let report = MigrationReport {
+            migrated: vec![record("bafynew", MigrationStatus::Done), s3],
+            skipped: vec![record("bafydone", MigrationStatus::Done)],
+            failed: vec![record("bafybad", MigrationStatus::Failed)],
+        };
+        assert_eq!(
+            report.blobs_by_cid().into_iter().collect::<Vec<_>>(),
+            vec![
+                ("bafydone".to_string(), vec!["blob-bafydone".to_string()]),
+                ("bafynew".to_string(), vec!["blob-bafynew".to_string()]),
+            ]
+        );
+        assert!(report
+            .describe()
+            .ends_with("Migrated 2 files, skipped 1 already migrated, 1 failed"));
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,267 @@
+use std::error::Error;
+use std::ops::Range;
+
+use crate::blob_cache::BlobCache;
+use crate::client::{list_blobs, read_blob};
+use crate::config::FileInfo;
+use crate::encryptor::{
+    decrypt_chunks, decrypt_data, legacy_plain_size, LEGACY_SALT_LEN, LEGACY_TAIL_LEN,
+};
+use crate::types::BlobInfo;
+
+/// How the bytes stored for a registered file are spread over its shards.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileLayout {
+    pub shard_sizes: Vec<u64>,
+}
+
+impl FileLayout {
+    /// The layout recorded in the registry when the file was uploaded.
+    pub fn from_file_info(file_info: &FileInfo) -> Option<FileLayout> {
+        let (size, shard_size) = (file_info.size?, file_info.shard_size?);
+        if shard_size == 0 {
+            return None;
+        }
+
+        let mut remaining = size;
+        let shard_sizes: Vec<u64> = file_info
+            .blobs
+            .iter()
+            .map(|_| {
+                let shard = remaining.min(shard_size);
+                remaining -= shard;
+                shard
+            })
+            .collect();
+
+        // A size that doesn't fit the blobs means the entry was edited by hand
+        (remaining == 0).then_some(FileLayout { shard_sizes })
+    }
+
+    /// The layout from the sizes Walrus reports for the file's blobs, for
+    /// files registered before their layout was recorded.
+    pub fn from_blobs(file_info: &FileInfo, blobs: &[BlobInfo]) -> Option<FileLayout> {
+        let shard_sizes = file_info
+            .blobs
+            .iter()
+            .map(|blob_id| {
+                blobs
+                    .iter()
+                    .find(|blob| &blob.blob_id == blob_id)
+                    .map(|blob| blob.unencoded_size)
+            })
+            .collect::<Option<Vec<u64>>>()?;
+
+        Some(FileLayout { shard_sizes })
+    }
+
+    pub fn size(&self) -> u64 {
+        self.shard_sizes.iter().sum()
+    }
+
+    /// The shards overlapping `range`, each with the part of the shard that
+    /// falls inside it. The range is clipped to the end of the file.
+    pub fn shards_for_range(&self, range: Range<u64>) -> Vec<(usize, Range<u64>)> {
+        let mut shards = Vec::new();
+        let mut shard_start = 0;
+        if range.is_empty() {
+            return shards;
+        }
+
+        for (index, &shard_size) in self.shard_sizes.iter().enumerate() {
+            let shard_end = shard_start + shard_size;
+            if shard_end > range.start && shard_start < range.end {
+                let start = range.start.max(shard_start) - shard_start;
+                let end = range.end.min(shard_end) - shard_start;
+                shards.push((index, start..end));
+            }
+            shard_start = shard_end;
+        }
+
+        shards
+    }
+}
+
+/// The layout of a registered file, asking Walrus for the blob sizes when
+/// the registry doesn't have it.
+pub async fn file_layout(file_info: &FileInfo) -> Result<FileLayout, Box<dyn Error>> {
+    if let Some(layout) = FileLayout::from_file_info(file_info) {
+        return Ok(layout);
+    }
+
+    let blobs = list_blobs(false).await?;
+    FileLayout::from_blobs(file_info, &blobs)
+        .ok_or("Some of the file's blobs aren't owned by the active address".into())
+}
+
+/// Size of a registered file as it was before encryption. `None` for files
+/// encrypted as a whole, whose size needs the password, see `legacy_file_size`.
+pub async fn file_size(file_info: &FileInfo) -> Result<Option<u64>, Box<dyn Error>> {
+    if file_info.is_encrypted && file_info.encryption.is_none() {
+        return Ok(None);
+    }
+
+    let stored_size = file_layout(file_info).await?.size();
+    match &file_info.encryption {
+        Some(header) => Ok(Some(
+            header
+                .plain_size(stored_size)
+                .ok_or("The encrypted file has an unexpected size")?,
+        )),
+        None => Ok(Some(stored_size)),
+    }
+}
+
+/// The size of a file encrypted as a whole, which `file_size` can't tell.
+/// Only the shards holding its salt and its last two cipher blocks are read.
+pub async fn legacy_file_size(
+    cache: &BlobCache,
+    file_info: &FileInfo,
+    password: &str,
+) -> Result<u64, Box<dyn Error>> {
+    let layout = file_layout(file_info).await?;
+    let stored_size = layout.size();
+    let salt = read_stored_range(cache, file_info, &layout, 0..LEGACY_SALT_LEN).await?;
+    let tail_start = stored_size.saturating_sub(LEGACY_TAIL_LEN);
+    let tail = read_stored_range(cache, file_info, &layout, tail_start..stored_size).await?;
+
+    legacy_plain_size(stored_size, &salt, &tail, password)
+}
+
+// The stored bytes in `range`, read from the shards covering it
+async fn read_stored_range(
+    cache: &BlobCache,
+    file_info: &FileInfo,
+    layout: &FileLayout,
+    range: Range<u64>,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut data = Vec::new();
+
+    for (index, part) in layout.shards_for_range(range) {
+        let blob_id = &file_info.blobs[index];
+        let shard = read_blob(cache, blob_id).await?;
+        let part = shard
+            .get(part.start as usize..part.end as usize)
+            .ok_or(format!("Blob {} is shorter than expected", blob_id))?;
+        data.extend_from_slice(part);
+    }
+
+    Ok(data)
+}
+
+/// Reads `range` of a registered file as it was before encryption, fetching
+/// only the shards holding it. The range is clipped to the end of the file.
+/// Files encrypted as a whole are read and decrypted in full first.
+pub async fn read_range(
+    cache: &BlobCache,
+    file_info: &FileInfo,
+    range: Range<u64>,
+    password: Option<&str>,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    if !file_info.is_encrypted {
+        let layout = file_layout(file_info).await?;
+        return read_stored_range(cache, file_info, &layout, range).await;
+    }
+    let password = password.ok_or("The file is encrypted, a password is needed")?;
+
+    let Some(header) = &file_info.encryption else {
+        let data = read_whole(cache, file_info, Some(password)).await?;
+        let end = range.end.min(data.len() as u64);
+        let start = range.start.min(end);
+        return Ok(data[start as usize..end as usize].to_vec());
+    };
+
+    let layout = file_layout(file_info).await?;
+    let stored_size = layout.size();
+    let chunk_count = header
+        .chunk_count(stored_size)
+        .ok_or("The encrypted file has an unexpected size")?;
+    let Some((chunks, stored)) = header.chunks_for_range(range.clone(), stored_size) else {
+        return Ok(Vec::new());
+    };
+
+    let data = read_stored_range(cache, file_info, &layout, stored).await?;
+    let plaintext = decrypt_chunks(&data, header, chunks.start, chunk_count, password)?;
+
+    // The chunks start at a chunk boundary, the range may not
+    let start = (range.start - chunks.start * header.chunk_size) as usize;
+    let end = plaintext
+        .len()
+        .min(start.saturating_add((range.end - range.start) as usize));
+    Ok(plaintext[start..end].to_vec())
+}
+
+/// A whole registered file, decrypted when it is encrypted.
+pub async fn read_whole(
+    cache: &BlobCache,
+    file_info: &FileInfo,
+    password: Option<&str>,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut data = Vec::new();
+    for blob_id in &file_info.blobs {
+        data.extend(read_blob(cache, blob_id).await?);
+    }
+
+    if !file_info.is_encrypted {
+        return Ok(data);
+    }
+    let password = password.ok_or("The file is encrypted, a password is needed")?;
+    decrypt_data(&data, password)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::blob;
+
+    fn file_info(size: Option<u64>, shard_size: Option<u64>) -> FileInfo {
+        FileInfo {
+            is_encrypted: false,
+            blobs: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            size,
+            shard_size,
+            encryption: None,
+            cid: None,
+        }
+    }
+
+    #[test]
+    fn test_file_layout() {
+        let layout = FileLayout::from_file_info(&file_info(Some(12), Some(5))).unwrap();
+        assert_eq!(layout.shard_sizes, vec![5, 5, 2]);
+        assert_eq!(layout.size(), 12);
+
+        assert!(FileLayout::from_file_info(&file_info(None, None)).is_none());
+        assert!(FileLayout::from_file_info(&file_info(Some(20), Some(5))).is_none());
+
+        let blobs: Vec<BlobInfo> = [("a", 5), ("c", 2), ("b", 5)]
+            .iter()
+            .map(|&(blob_id, unencoded_size)| BlobInfo {
+                unencoded_size,
+                ..blob(blob_id, 10)
+            })
+            .collect();
+        assert_eq!(
+            FileLayout::from_blobs(&file_info(None, None), &blobs),
+            Some(layout)
+        );
+        assert!(FileLayout::from_blobs(&file_info(None, None), &blobs[..2]).is_none());
+    }
+
+    #[test]
+    fn test_shards_for_range() {
+        let layout = FileLayout {
+            shard_sizes: vec![5, 5, 2],
+        };
+
+        assert_eq!(
+            layout.shards_for_range(0..12),
+            vec![(0, 0..5), (1, 0..5), (2, 0..2)]
+        );
+        assert_eq!(layout.shards_for_range(3..7), vec![(0, 3..5), (1, 0..2)]);
+        assert_eq!(layout.shards_for_range(5..10), vec![(1, 0..5)]);
+        assert_eq!(layout.shards_for_range(11..100), vec![(2, 1..2)]);
+        assert!(layout.shards_for_range(12..20).is_empty());
+        assert!(layout.shards_for_range(4..4).is_empty());
+    }
+}
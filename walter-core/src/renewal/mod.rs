@@ -0,0 +1,317 @@
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+
+use crate::blob_cache::BlobCache;
+use crate::client::{list_blobs, walrus_info};
+use crate::config::WalterConfig;
+use crate::epoch_extender::{extend_epoch_for, latest_blob};
+use crate::types::BlobInfo;
+
+const RENEWAL_LOG_PATH: &str = "~/.walter/renewals.jsonl";
+
+// Held for a whole pass, so a pass started while another one runs in the same
+// process, like a triggered one in the pinner, can't extend a blob twice
+static PASS_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// A registered blob that is close enough to expiry to be renewed.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct RenewalCandidate {
+    pub blob_id: String,
+    pub file_path: Option<String>,
+    pub expiration_epoch: u64,
+    pub epochs_left: u64,
+}
+
+/// One line of the renewal log.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RenewalRecord {
+    pub timestamp: u64,
+    pub blob_id: String,
+    pub file_path: Option<String>,
+    pub previous_end_epoch: u64,
+    pub epochs: u16,
+    pub new_end_epoch: Option<u64>,
+    pub dry_run: bool,
+    pub error: Option<String>,
+}
+
+impl RenewalRecord {
+    pub fn describe(&self) -> String {
+        let name = match &self.file_path {
+            Some(file_path) => format!("{} ({})", self.blob_id, file_path),
+            None => format!("{} (pinned)", self.blob_id),
+        };
+
+        match (&self.error, self.new_end_epoch) {
+            (Some(e), _) => format!("{}: failed, {}", name, e),
+            (None, Some(end_epoch)) => format!(
+                "{}: extended by {} epochs, {} -> {}",
+                name, self.epochs, self.previous_end_epoch, end_epoch
+            ),
+            (None, None) => format!(
+                "{}: would extend by {} epochs from epoch {}",
+                name, self.epochs, self.previous_end_epoch
+            ),
+        }
+    }
+}
+
+pub fn default_log_path() -> PathBuf {
+    PathBuf::from(shellexpand::tilde(RENEWAL_LOG_PATH).to_string())
+}
+
+/// Every blob that is renewed automatically: the shards of registered files
+/// and the pinned blobs, each with the file it belongs to.
+pub fn kept_alive_blobs(config: &WalterConfig) -> Vec<(String, Option<String>)> {
+    let mut blobs: Vec<(String, Option<String>)> = config
+        .get_blob_locations()
+        .into_iter()
+        .map(|(blob_id, location)| (blob_id, Some(location.file_path)))
+        .collect();
+
+    for blob_id in config.get_pinned_blobs() {
+        if !blobs.iter().any(|(kept, _)| kept == blob_id) {
+            blobs.push((blob_id.clone(), None));
+        }
+    }
+
+    blobs.sort();
+    blobs
+}
+
+/// Kept alive blobs ending within `renew_epoch_threshold` epochs of `current_epoch`.
+/// Blobs that are no longer listed, e.g. expired ones, can't be extended and are skipped.
+pub fn renewal_candidates(
+    config: &WalterConfig,
+    blobs: &[BlobInfo],
+    current_epoch: u64,
+) -> Vec<RenewalCandidate> {
+    let threshold = config.get_renew_epoch_threshold() as u64;
+    let mut candidates: Vec<RenewalCandidate> = kept_alive_blobs(config)
+        .into_iter()
+        .filter_map(|(blob_id, file_path)| {
+            let expiration_epoch = latest_blob(blobs, &blob_id)?.expiration_epoch;
+            let epochs_left = expiration_epoch.saturating_sub(current_epoch);
+
+            (epochs_left <= threshold).then_some(RenewalCandidate {
+                blob_id,
+                file_path,
+                expiration_epoch,
+                epochs_left,
+            })
+        })
+        .collect();
+
+    candidates.sort_by(|a, b| {
+        a.expiration_epoch
+            .cmp(&b.expiration_epoch)
+            .then_with(|| a.blob_id.cmp(&b.blob_id))
+    });
+    candidates
+}
+
+pub fn append_log(path: &Path, records: &[RenewalRecord]) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    for record in records {
+        writeln!(file, "{}", serde_json::to_string(record)?)?;
+    }
+    Ok(())
+}
+
+/// Every record in the log, oldest first. Lines that don't parse are skipped.
+pub fn read_log(path: &Path) -> Result<Vec<RenewalRecord>, Box<dyn Error>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Periodically extends registered blobs that are about to expire by
+/// `default_renewal_value` epochs, recording every attempt in the renewal log.
+pub struct RenewalScheduler {
+    pub dry_run: bool,
+    pub log_path: PathBuf,
+    /// Read through when a blob has to be stored again to extend it
+    pub cache: BlobCache,
+}
+
+impl RenewalScheduler {
+    pub fn new(dry_run: bool) -> Self {
+        RenewalScheduler {
+            dry_run,
+            log_path: default_log_path(),
+            cache: BlobCache::open(),
+        }
+    }
+
+    /// Checks every registered blob once and renews the ones under the threshold.
+    pub async fn run_once(&self, config: &WalterConfig) -> Result<Vec<RenewalRecord>, String> {
+        let _pass = PASS_LOCK.lock().await;
+        let info = walrus_info().await.map_err(|e| e.to_string())?;
+        let blobs = list_blobs(false).await.map_err(|e| e.to_string())?;
+        let epochs = config.get_default_renewal_value();
+
+        let mut records = Vec::new();
+        for candidate in renewal_candidates(config, &blobs, info.current_epoch) {
+            // Candidates are picked from `blobs`, so their blob is always listed
+            let blob = latest_blob(&blobs, &candidate.blob_id);
+            let (new_end_epoch, error) = match blob.filter(|_| !self.dry_run) {
+                Some(blob) => match extend_epoch_for(&self.cache, blob, epochs)
+                    .await
+                    .map_err(|e| e.to_string())
+                {
+                    Ok(end_epoch) => (Some(end_epoch), None),
+                    Err(e) => (None, Some(e)),
+                },
+                None => (None, None),
+            };
+
+            records.push(RenewalRecord {
+                timestamp: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|duration| duration.as_secs())
+                    .unwrap_or(0),
+                blob_id: candidate.blob_id,
+                file_path: candidate.file_path,
+                previous_end_epoch: candidate.expiration_epoch,
+                epochs,
+                new_end_epoch,
+                dry_run: self.dry_run,
+                error,
+            });
+        }
+
+        append_log(&self.log_path, &records).map_err(|e| e.to_string())?;
+        Ok(records)
+    }
+
+    /// Runs a check every `interval`, reloading the config each time so newly
+    /// registered files are picked up. `on_pass` gets the outcome of each check.
+    pub async fn run<F>(&self, interval: Duration, mut on_pass: F)
+    where
+        F: FnMut(Result<Vec<RenewalRecord>, String>),
+    {
+        loop {
+            // A config that can't be read fails this pass only, the next one reads it again
+            match WalterConfig::try_load() {
+                Ok(config) => on_pass(self.run_once(&config).await),
+                Err(e) => on_pass(Err(format!("Unable to read config file: {}", e))),
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{self, blob};
+
+    fn test_config() -> WalterConfig {
+        let mut config = test_util::test_config();
+        config.add_file(
+            "db.sqlite",
+            false,
+            vec!["soon".to_string(), "later".to_string()],
+        );
+        config.add_file("notes.txt", true, vec!["gone".to_string()]);
+        config.pin_blob("pinned");
+        config.pin_blob("soon");
+        config
+    }
+
+    #[test]
+    fn test_renewal_candidates() {
+        let config = test_config();
+        let blobs = vec![
+            blob("soon", 12),
+            blob("later", 20),
+            blob("unregistered", 11),
+            blob("pinned", 10),
+        ];
+
+        let candidates = renewal_candidates(&config, &blobs, 10);
+        assert_eq!(
+            candidates,
+            vec![
+                RenewalCandidate {
+                    blob_id: "pinned".to_string(),
+                    file_path: None,
+                    expiration_epoch: 10,
+                    epochs_left: 0,
+                },
+                RenewalCandidate {
+                    blob_id: "soon".to_string(),
+                    file_path: Some("db.sqlite".to_string()),
+                    expiration_epoch: 12,
+                    epochs_left: 2,
+                },
+            ]
+        );
+
+        // A second copy with a later end epoch means the blob is already renewed
+        let blobs = vec![blob("soon", 12), blob("soon", 30)];
+        assert!(renewal_candidates(&config, &blobs, 10).is_empty());
+    }
+
+    #[test]
+    fn test_renewal_log() {
+        let path =
+            std::env::temp_dir().join(format!("walter_renewals_{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let record = RenewalRecord {
+            timestamp: 1,
+            blob_id: "soon".to_string(),
+            file_path: Some("db.sqlite".to_string()),
+            previous_end_epoch: 12,
+            epochs: 10,
+            new_end_epoch: Some(22),
+            dry_run: false,
+            error: None,
+        };
+        let dry_run = RenewalRecord {
+            new_end_epoch: None,
+            dry_run: true,
+            ..record.clone()
+        };
+
+        append_log(&path, std::slice::from_ref(&record)).unwrap();
+        append_log(&path, std::slice::from_ref(&dry_run)).unwrap();
+        assert_eq!(
+            read_log(&path).unwrap(),
+            vec![record.clone(), dry_run.clone()]
+        );
+        assert_eq!(
+            record.describe(),
+            "soon (db.sqlite): extended by 10 epochs, 12 -> 22"
+        );
+        assert_eq!(
+            dry_run.describe(),
+            "soon (db.sqlite): would extend by 10 epochs from epoch 12"
+        );
+        assert_eq!(
+            RenewalRecord {
+                file_path: None,
+                ..dry_run
+            }
+            .describe(),
+            "soon (pinned): would extend by 10 epochs from epoch 12"
+        );
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,41 @@
+//! Fixtures shared by the tests of walter-core and the crates built on it,
+//! which get them through the `test-util` feature.
+
+use crate::config::WalterConfig;
+use crate::types::{BlobInfo, WalrusInfo};
+
+/// A config with no files, as older versions wrote it, so every setting added
+/// since then has its default.
+pub fn test_config() -> WalterConfig {
+    serde_json::from_str(
+        r#"{"default_file_download_dir":"~/.walter/downloads","default_epochs":10,"default_shard_size":5,"renew_epoch_threshold":2,"default_renewal_value":10,"files":{}}"#,
+    )
+    .unwrap()
+}
+
+/// A listed, certified blob of 10 bytes ending at `expiration_epoch`.
+pub fn blob(blob_id: &str, expiration_epoch: u64) -> BlobInfo {
+    BlobInfo {
+        blob_id: blob_id.to_string(),
+        unencoded_size: 10,
+        is_certified: true,
+        is_deletable: false,
+        expiration_epoch,
+        object_id: format!("0x{}", blob_id),
+    }
+}
+
+/// System info with 1000 shards, 1 MiB storage units and one day epochs,
+/// at epoch 10.
+pub fn walrus_info() -> WalrusInfo {
+    WalrusInfo {
+        current_epoch: 10,
+        epoch_duration_secs: 86400,
+        max_epochs_ahead: 53,
+        n_shards: 1000,
+        storage_unit_size: 1024 * 1024,
+        max_blob_size: 13 * 1024 * 1024 * 1024,
+        storage_price_per_unit_size: 100_000,
+        write_price_per_unit_size: 20_000,
+    }
+}
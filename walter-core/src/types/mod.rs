@@ -33,26 +33,194 @@ pub struct Storage {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct BlobInfo {
     pub blob_id: String,
-    pub unencoded_size: String, // Keep as string to preserve original format
+    pub unencoded_size: u64,
     pub is_certified: bool,
     pub is_deletable: bool,
     pub expiration_epoch: u64,
     pub object_id: String,
 }
 
+impl From<BlobObject> for BlobInfo {
+    fn from(blob: BlobObject) -> Self {
+        BlobInfo {
+            blob_id: blob.blobId,
+            unencoded_size: blob.size,
+            is_certified: blob.certifiedEpoch.is_some(),
+            is_deletable: blob.deletable,
+            expiration_epoch: blob.storage.endEpoch as u64,
+            object_id: blob.id,
+        }
+    }
+}
+
 #[allow(non_snake_case)]
 #[derive(Deserialize, Debug)]
 pub struct BlobObject {
     pub id: String,
     pub registeredEpoch: u32,
     pub blobId: String,
-    pub size: u32,
+    pub size: u64,
     pub encodingType: String,
-    pub certifiedEpoch: u32,
+    pub certifiedEpoch: Option<u32>,
     pub storage: Storage,
     pub deletable: bool,
 }
 
+// Entries of `list-blobs` in JSON mode, newer Walrus versions wrap each blob
+// together with its attributes
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum ListedBlob {
+    WithAttribute { blob: BlobObject },
+    Plain(BlobObject),
+}
+
+impl From<ListedBlob> for BlobInfo {
+    fn from(listed: ListedBlob) -> Self {
+        match listed {
+            ListedBlob::WithAttribute { blob } => blob.into(),
+            ListedBlob::Plain(blob) => blob.into(),
+        }
+    }
+}
+
+/// One page of the blobs owned by the active address. The walrus CLI can't
+/// list a page at a time, so pages are cut from the full list.
+#[derive(Debug, Serialize, Clone)]
+pub struct BlobPage {
+    pub blobs: Vec<BlobInfo>,
+    pub page: usize,
+    pub page_size: usize,
+    pub total_pages: usize,
+    pub total_blobs: usize,
+}
+
+impl BlobPage {
+    pub fn from_blobs(blobs: Vec<BlobInfo>, page: usize, page_size: usize) -> Self {
+        let page_size = page_size.max(1);
+        let total_blobs = blobs.len();
+        let total_pages = total_blobs.div_ceil(page_size);
+
+        BlobPage {
+            blobs: blobs
+                .into_iter()
+                .skip(page * page_size)
+                .take(page_size)
+                .collect(),
+            page,
+            page_size,
+            total_pages,
+            total_blobs,
+        }
+    }
+}
+
+/// A shard read back from Walrus while downloading a file.
+#[derive(Debug, Serialize, Clone)]
+pub struct ShardDownload {
+    pub blob_id: String,
+    pub size: u64,
+}
+
+/// What a download wrote to disk, used to check it against the listed blobs.
+#[derive(Debug, Serialize, Clone)]
+pub struct DownloadReport {
+    pub out_path: String,
+    pub shards: Vec<ShardDownload>,
+    pub decrypted: bool,
+    pub size: u64,
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize, Debug)]
+pub struct InfoStorage {
+    pub nShards: u16,
+}
+
+/// Walrus has serialized durations both as milliseconds and as secs/nanos.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum InfoDuration {
+    Millis(u64),
+    SecsNanos { secs: u64, nanos: u32 },
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize, Debug)]
+pub struct InfoEpoch {
+    pub currentEpoch: u64,
+    pub epochDuration: InfoDuration,
+    pub maxEpochsAhead: u32,
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize, Debug)]
+pub struct InfoSize {
+    pub storageUnitSize: u64,
+    pub maxBlobSize: u64,
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize, Debug)]
+pub struct InfoPrice {
+    pub storagePricePerUnitSize: u64,
+    pub writePricePerUnitSize: u64,
+}
+
+/// The parts of the `info` JSON output that walter uses.
+#[allow(non_snake_case)]
+#[derive(Deserialize, Debug)]
+pub struct InfoOutput {
+    pub storageInfo: InfoStorage,
+    pub epochInfo: InfoEpoch,
+    pub sizeInfo: InfoSize,
+    pub priceInfo: InfoPrice,
+}
+
+/// Walrus system parameters, prices are in FROST per storage unit.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct WalrusInfo {
+    pub current_epoch: u64,
+    pub epoch_duration_secs: u64,
+    pub max_epochs_ahead: u32,
+    pub n_shards: u16,
+    pub storage_unit_size: u64,
+    pub max_blob_size: u64,
+    pub storage_price_per_unit_size: u64,
+    pub write_price_per_unit_size: u64,
+}
+
+impl From<InfoOutput> for WalrusInfo {
+    fn from(info: InfoOutput) -> Self {
+        let epoch_duration_secs = match info.epochInfo.epochDuration {
+            InfoDuration::Millis(millis) => millis / 1000,
+            InfoDuration::SecsNanos { secs, .. } => secs,
+        };
+
+        WalrusInfo {
+            current_epoch: info.epochInfo.currentEpoch,
+            epoch_duration_secs,
+            max_epochs_ahead: info.epochInfo.maxEpochsAhead,
+            n_shards: info.storageInfo.nShards,
+            storage_unit_size: info.sizeInfo.storageUnitSize,
+            max_blob_size: info.sizeInfo.maxBlobSize,
+            storage_price_per_unit_size: info.priceInfo.storagePricePerUnitSize,
+            write_price_per_unit_size: info.priceInfo.writePricePerUnitSize,
+        }
+    }
+}
+
+impl WalrusInfo {
+    /// Epochs left before `end_epoch`, zero once it has passed.
+    pub fn epochs_until(&self, end_epoch: u64) -> u64 {
+        end_epoch.saturating_sub(self.current_epoch)
+    }
+
+    pub fn epochs_to_days(&self, epochs: u64) -> f64 {
+        (epochs * self.epoch_duration_secs) as f64 / 86400.0
+    }
+}
+
 #[allow(non_snake_case)]
 #[derive(Deserialize, Debug)]
 pub struct ResourceOperation {
@@ -1,6 +1,7 @@
 use rusqlite::{params, Connection, Result};
 use rustyline::error::ReadlineError;
 use rustyline::Editor;
+use walter_core::blob_cache::BlobCache;
 mod walrus_io;
 
 pub fn main() -> anyhow::Result<()> {
@@ -9,14 +10,22 @@ pub fn main() -> anyhow::Result<()> {
         eprintln!("Usage: {} <blobID>", args[0]);
         std::process::exit(1);
     }
+    run(&args[2])
+}
+
+pub fn run(blob_id: &str) -> anyhow::Result<()> {
     std::fs::File::create("/tmp/sqlite.db").expect("Unable to create file");
-    let blob_id = &args[2];
     let blob_id = blob_id.to_string();
-    let mut blob_id =
-        match walrus_io::download_and_extract_id(blob_id.clone(), "/tmp/sqlite.db".to_string()) {
-            Some(blob_id) => blob_id,
-            None => "".to_string(),
-        };
+    // One cache for the whole shell, so rollbacks to a saved state read it locally
+    let cache = BlobCache::open();
+    let mut blob_id = match walrus_io::download_and_extract_id(
+        &cache,
+        blob_id.clone(),
+        "/tmp/sqlite.db".to_string(),
+    ) {
+        Some(blob_id) => blob_id,
+        None => "".to_string(),
+    };
     let conn = Connection::open("/tmp/sqlite.db")?;
     let mut rl = Editor::<(), _>::new()?;
 
@@ -38,7 +47,7 @@ pub fn main() -> anyhow::Result<()> {
                         describe_table(&conn, table_name)?;
                     }
                     query if !query.is_empty() => {
-                        execute_query(&conn, query, &mut blob_id)?;
+                        execute_query(&conn, &cache, query, &mut blob_id)?;
                     }
                     _ => {}
                 }
@@ -120,7 +129,12 @@ fn describe_table(conn: &Connection, table_name: &str) -> Result<()> {
     Ok(())
 }
 
-fn execute_query(conn: &Connection, query: &str, blob_id_prev: &mut String) -> Result<()> {
+fn execute_query(
+    conn: &Connection,
+    cache: &BlobCache,
+    query: &str,
+    blob_id_prev: &mut String,
+) -> Result<()> {
     if query.trim().to_uppercase().starts_with("SELECT") {
         let mut stmt = conn.prepare(query)?;
 
@@ -165,8 +179,11 @@ fn execute_query(conn: &Connection, query: &str, blob_id_prev: &mut String) -> R
         *blob_id_prev = walrus_io::get_blob_id(value.unwrap()).unwrap();
         println!("CURRENT Blob ID: {}", blob_id_prev);
     } else if query.trim().to_uppercase().starts_with("ROLLBACK") {
-        let value =
-            walrus_io::download_and_extract_id(blob_id_prev.clone(), "/tmp/sqlite.db".to_string());
+        let value = walrus_io::download_and_extract_id(
+            cache,
+            blob_id_prev.clone(),
+            "/tmp/sqlite.db".to_string(),
+        );
         // *blob_id_prev = value.unwrap();
         println!("Blob ID on ROLLBACK: {}", value.unwrap());
     } else {
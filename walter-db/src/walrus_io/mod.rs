@@ -1,5 +1,8 @@
 use std::fs::File;
 use std::io::{Read, Write};
+use std::path::Path;
+
+use walter_core::blob_cache::BlobCache;
 
 mod types;
 use types::*;
@@ -48,9 +51,14 @@ pub fn upload_to_walrus(file_name: String, epochs: Option<u16>) -> Option<serde_
 }
 
 //Input: blobId: String
-//Process: Reads from Walrus and outputs into a file at given path
+//Process: Reads from the blob cache, or from Walrus into the cache, and outputs into a file at given path
 //Output: success or failure bool
-pub fn download_from_walrus(blob_id: String, file_location: String) -> Option<bool> {
+pub fn download_from_walrus(cache: &BlobCache, blob_id: String, file_location: String) -> Option<bool> {
+    if cache.copy_to(&blob_id, Path::new(&file_location)) {
+        println!("Read blob from the local cache");
+        return Some(true);
+    }
+
     let command_json = serde_json::json!({
         "command": {
             "read": {
@@ -82,7 +90,11 @@ pub fn download_from_walrus(blob_id: String, file_location: String) -> Option<bo
         }
     };
 
-    Some(output["success"].as_bool().unwrap_or(true))
+    let success = output["success"].as_bool().unwrap_or(true);
+    if success {
+        let _ = cache.insert_file(&blob_id, Path::new(&file_location));
+    }
+    Some(success)
 }
 
 fn get_blob_id_from_response(response: NewlyCreated) -> Option<String> {
@@ -125,9 +137,9 @@ pub fn append_id_and_upload(file_location: String, blob_id: String, epochs: Opti
     upload_to_walrus(file_location, epochs)
 }
 
-pub fn download_and_extract_id(blob_id: String, file_location: String) -> Option<String> {
+pub fn download_and_extract_id(cache: &BlobCache, blob_id: String, file_location: String) -> Option<String> {
     // Download the file from walrus
-    let success = download_from_walrus(blob_id.clone(), file_location.clone());
+    let success = download_from_walrus(cache, blob_id.clone(), file_location.clone());
 
     // Read the file content
     let mut file = File::open(&file_location).expect("Unable to open file");
@@ -159,7 +171,7 @@ mod tests {
         let mut file = File::create(&file_location).expect("Unable to create file");
         file.write_all(content.as_bytes()).expect("Unable to write to file");
 
-        let extracted_blob_id = download_and_extract_id(blob_id.clone(), file_location.clone());
+        let extracted_blob_id = download_and_extract_id(&BlobCache::open(), blob_id.clone(), file_location.clone());
         assert_eq!(extracted_blob_id, Some(blob_id));
     }
 
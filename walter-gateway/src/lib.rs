@@ -0,0 +1,420 @@
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::{header, HeaderMap, HeaderName, StatusCode},
+    response::{IntoResponse, Redirect, Response},
+    routing::get,
+    Json, Router,
+};
+use futures_util::stream::{self, StreamExt};
+use serde::Serialize;
+use serde_json::json;
+use std::error::Error;
+use std::ops::Range;
+
+use walter_core::blob_cache::BlobCache;
+use walter_core::client::read_blob;
+use walter_core::config::{CidMapping, FileInfo, WalterConfig};
+use walter_core::reader::{file_layout, file_size, read_range};
+
+pub const DEFAULT_ADDR: &str = "127.0.0.1:1338";
+
+/// A registered file as listed by the gateway.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct GatewayFile {
+    pub name: String,
+    pub url: String,
+    pub shards: usize,
+    pub encrypted: bool,
+    pub size: Option<u64>,
+}
+
+#[derive(Clone)]
+struct GatewayState {
+    // Key for encrypted files, they can't be served without one
+    password: Option<String>,
+    cache: BlobCache,
+}
+
+type ApiError = (StatusCode, Json<serde_json::Value>);
+// 416 with the size of the file, for ranges that lie past its end
+type Unsatisfiable = (StatusCode, [(HeaderName, String); 1]);
+
+fn api_error(status: StatusCode, message: impl ToString) -> ApiError {
+    (status, Json(json!({ "error": message.to_string() })))
+}
+
+// Where the gateway serves a registered file, percent-encoded except for
+// unreserved characters and slashes so it is also a valid Location
+fn file_url(file_path: &str) -> String {
+    let mut url = String::from("/files/");
+    for byte in file_path.trim_start_matches('/').bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                url.push(byte as char)
+            }
+            _ => url.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    url
+}
+
+/// Every registered file, sorted by name.
+pub fn gateway_files(config: &WalterConfig) -> Vec<GatewayFile> {
+    let mut files: Vec<GatewayFile> = config
+        .get_files()
+        .iter()
+        .map(|(file_path, file_info)| GatewayFile {
+            name: file_path.clone(),
+            url: file_url(file_path),
+            shards: file_info.blobs.len(),
+            encrypted: file_info.is_encrypted,
+            // The stored size of an encrypted file is the size of the ciphertext
+            size: file_info.size.filter(|_| !file_info.is_encrypted),
+        })
+        .collect();
+
+    files.sort_by(|a, b| a.name.cmp(&b.name));
+    files
+}
+
+/// The registered file called `name`, matched on its full path first and
+/// then on its file name, as long as only one registered file has it.
+pub fn find_file<'a>(config: &'a WalterConfig, name: &str) -> Option<(&'a String, &'a FileInfo)> {
+    let name = name.trim_start_matches('/');
+    let files = config.get_files();
+
+    if let Some(found) = files
+        .iter()
+        .find(|(file_path, _)| file_path.trim_start_matches('/') == name)
+    {
+        return Some(found);
+    }
+
+    let mut matches = files.iter().filter(|(file_path, _)| {
+        std::path::Path::new(file_path)
+            .file_name()
+            .is_some_and(|file_name| file_name.to_string_lossy() == name)
+    });
+    match (matches.next(), matches.next()) {
+        (Some(found), None) => Some(found),
+        _ => None,
+    }
+}
+
+/// The bytes asked for by a `Range` header on a file of `size` bytes. `None`
+/// means the header is ignored and the whole file is served, which includes
+/// requests for several ranges. `Some(Err)` means the range is past the end.
+fn parse_range(value: &str, size: u64) -> Option<Result<Range<u64>, ()>> {
+    let spec = value.trim().strip_prefix("bytes=")?;
+    if spec.contains(',') {
+        return None;
+    }
+
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = (start.trim(), end.trim());
+
+    let range = if start.is_empty() {
+        // bytes=-N asks for the last N bytes
+        let suffix: u64 = end.parse().ok()?;
+        if suffix == 0 {
+            return Some(Err(()));
+        }
+        size.saturating_sub(suffix)..size
+    } else {
+        let start: u64 = start.parse().ok()?;
+        let end = match end {
+            "" => size,
+            end => {
+                let end: u64 = end.parse().ok()?;
+                if end < start {
+                    return None;
+                }
+                end.saturating_add(1).min(size)
+            }
+        };
+        start..end
+    };
+
+    if range.start >= size {
+        return Some(Err(()));
+    }
+    Some(Ok(range))
+}
+
+fn content_type(file_path: &str) -> &'static str {
+    let extension = std::path::Path::new(file_path)
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "html" | "htm" => "text/html; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "js" => "text/javascript; charset=utf-8",
+        "json" => "application/json",
+        "txt" | "md" | "csv" | "log" => "text/plain; charset=utf-8",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        "pdf" => "application/pdf",
+        "mp3" => "audio/mpeg",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "wasm" => "application/wasm",
+        _ => "application/octet-stream",
+    }
+}
+
+fn file_response(file_path: &str, size: u64, range: Option<Range<u64>>, body: Body) -> Response {
+    let mut builder = Response::builder()
+        .header(header::CONTENT_TYPE, content_type(file_path))
+        .header(header::ACCEPT_RANGES, "bytes");
+
+    builder = match range {
+        Some(range) => builder
+            .status(StatusCode::PARTIAL_CONTENT)
+            .header(header::CONTENT_LENGTH, range.end - range.start)
+            .header(
+                header::CONTENT_RANGE,
+                format!("bytes {}-{}/{}", range.start, range.end - 1, size),
+            ),
+        None => builder
+            .status(StatusCode::OK)
+            .header(header::CONTENT_LENGTH, size),
+    };
+
+    builder
+        .body(body)
+        .unwrap_or_else(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, e).into_response())
+}
+
+/// The range to serve for an optional `Range` header, or the response
+/// refusing it when it lies past the end of the file.
+fn requested_range(value: Option<String>, size: u64) -> Result<Option<Range<u64>>, Unsatisfiable> {
+    match value.and_then(|value| parse_range(&value, size)) {
+        Some(Ok(range)) => Ok(Some(range)),
+        Some(Err(())) => Err((
+            StatusCode::RANGE_NOT_SATISFIABLE,
+            [(header::CONTENT_RANGE, format!("bytes */{}", size))],
+        )),
+        None => Ok(None),
+    }
+}
+
+async fn health() -> Json<serde_json::Value> {
+    Json(json!({
+        "status": "ok",
+        "version": env!("CARGO_PKG_VERSION"),
+    }))
+}
+
+// Loaded per request so files registered after startup are served too
+fn load_config() -> Result<WalterConfig, ApiError> {
+    WalterConfig::try_load().map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, e))
+}
+
+async fn list_files() -> Result<Json<Vec<GatewayFile>>, ApiError> {
+    Ok(Json(gateway_files(&load_config()?)))
+}
+
+async fn list_cids() -> Result<Json<Vec<CidMapping>>, ApiError> {
+    Ok(Json(load_config()?.get_cid_mappings()))
+}
+
+/// Sends links to an IPFS CID on to the file it was migrated to.
+async fn redirect_cid(Path(cid): Path<String>) -> Result<Redirect, ApiError> {
+    let mapping = load_config()?.blob_for_cid(&cid).ok_or(api_error(
+        StatusCode::NOT_FOUND,
+        format!("{} was not migrated", cid),
+    ))?;
+    Ok(Redirect::temporary(&file_url(&mapping.file_path)))
+}
+
+async fn serve_file(
+    State(state): State<GatewayState>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let config = load_config()?;
+    let (file_path, file_info) = find_file(&config, &name)
+        .map(|(file_path, file_info)| (file_path.clone(), file_info.clone()))
+        .ok_or(api_error(
+            StatusCode::NOT_FOUND,
+            format!("{} is not a registered file", name),
+        ))?;
+    let range_header = headers
+        .get(header::RANGE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+
+    if file_info.is_encrypted {
+        let password = state.password.as_ref().ok_or(api_error(
+            StatusCode::FORBIDDEN,
+            format!("{} is encrypted and the gateway has no key", file_path),
+        ))?;
+
+        // Files encrypted in chunks only need the chunks covering the range,
+        // older ones are a single ciphertext that has to be decrypted in full
+        let size = file_size(&file_info)
+            .await
+            .map_err(|e| api_error(StatusCode::BAD_GATEWAY, e))?;
+        let (size, whole) = match size {
+            Some(size) => (size, None),
+            None => {
+                let data = read_range(&state.cache, &file_info, 0..u64::MAX, Some(password))
+                    .await
+                    .map_err(|e| api_error(StatusCode::BAD_GATEWAY, e))?;
+                (data.len() as u64, Some(data))
+            }
+        };
+
+        let range = match requested_range(range_header, size) {
+            Ok(range) => range,
+            Err(response) => return Ok(response.into_response()),
+        };
+        let wanted = range.clone().unwrap_or(0..size);
+        let body = match whole {
+            Some(data) => data[wanted.start as usize..wanted.end as usize].to_vec(),
+            None => read_range(&state.cache, &file_info, wanted, Some(password))
+                .await
+                .map_err(|e| api_error(StatusCode::BAD_GATEWAY, e))?,
+        };
+        return Ok(file_response(&file_path, size, range, Body::from(body)));
+    }
+
+    let layout = file_layout(&file_info)
+        .await
+        .map_err(|e| api_error(StatusCode::BAD_GATEWAY, e))?;
+    let size = layout.size();
+    let range = match requested_range(range_header, size) {
+        Ok(range) => range,
+        Err(response) => return Ok(response.into_response()),
+    };
+
+    // Only the shards covering the range are read, one at a time as the body is sent
+    let (blobs, cache) = (file_info.blobs, state.cache);
+    let shards = layout.shards_for_range(range.clone().unwrap_or(0..size));
+    let body = stream::iter(shards).then(move |(index, part)| {
+        let (blob_id, cache) = (blobs[index].clone(), cache.clone());
+        async move {
+            let shard = read_blob(&cache, &blob_id)
+                .await
+                .map_err(|e| e.to_string())?;
+            shard
+                .get(part.start as usize..part.end as usize)
+                .map(<[u8]>::to_vec)
+                .ok_or(format!("Blob {} is shorter than expected", blob_id))
+        }
+    });
+
+    Ok(file_response(
+        &file_path,
+        size,
+        range,
+        Body::from_stream(body),
+    ))
+}
+
+fn router(state: GatewayState) -> Router {
+    Router::new()
+        .route("/health", get(health))
+        .route("/files", get(list_files))
+        .route("/files/{*name}", get(serve_file))
+        .route("/cids", get(list_cids))
+        .route("/ipfs/{cid}", get(redirect_cid))
+        .with_state(state)
+}
+
+/// Serves the registered files on `addr` until the process is stopped.
+/// Encrypted files are decrypted with `password`.
+pub async fn serve(addr: &str, password: Option<String>) -> Result<(), Box<dyn Error>> {
+    let state = GatewayState {
+        password,
+        cache: BlobCache::open(),
+    };
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    println!("Gateway listening on http://{}", listener.local_addr()?);
+    axum::serve(listener, router(state)).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use walter_core::test_util;
+
+    fn test_config() -> WalterConfig {
+        let mut config = test_util::test_config();
+        config.add_file("/home/walter/dumps/db.sqlite", false, vec!["a".to_string()]);
+        config.set_file_layout("/home/walter/dumps/db.sqlite", 12, 5);
+        config.add_file("notes.txt", true, vec!["b".to_string()]);
+        config.add_file("/home/walter/old/notes.txt", false, vec!["c".to_string()]);
+        config
+    }
+
+    #[test]
+    fn test_find_file() {
+        let config = test_config();
+        let name = |name: &str| find_file(&config, name).map(|(file_path, _)| file_path.as_str());
+
+        assert_eq!(
+            name("home/walter/dumps/db.sqlite"),
+            Some("/home/walter/dumps/db.sqlite")
+        );
+        assert_eq!(name("db.sqlite"), Some("/home/walter/dumps/db.sqlite"));
+        assert_eq!(name("notes.txt"), Some("notes.txt"));
+        assert_eq!(name("old/notes.txt"), None);
+        assert_eq!(name("missing.txt"), None);
+
+        let files = gateway_files(&config);
+        assert_eq!(files[0].url, "/files/home/walter/dumps/db.sqlite");
+        assert_eq!(files[0].size, Some(12));
+        assert_eq!(files[2].name, "notes.txt");
+        assert_eq!(files[2].size, None);
+    }
+
+    #[test]
+    fn test_file_url() {
+        assert_eq!(
+            file_url("/home/walter/dumps/db.sqlite"),
+            "/files/home/walter/dumps/db.sqlite"
+        );
+        assert_eq!(
+            file_url("migrated/bafy/cat photo?.png"),
+            "/files/migrated/bafy/cat%20photo%3F.png"
+        );
+        assert_eq!(file_url("café.txt"), "/files/caf%C3%A9.txt");
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("bytes=0-4", 12), Some(Ok(0..5)));
+        assert_eq!(parse_range("bytes=7-", 12), Some(Ok(7..12)));
+        assert_eq!(parse_range("bytes=-3", 12), Some(Ok(9..12)));
+        assert_eq!(parse_range("bytes=-30", 12), Some(Ok(0..12)));
+        assert_eq!(parse_range("bytes=10-100", 12), Some(Ok(10..12)));
+        assert_eq!(parse_range("bytes=12-", 12), Some(Err(())));
+        assert_eq!(parse_range("bytes=-0", 12), Some(Err(())));
+        assert_eq!(parse_range("bytes=0-1,4-5", 12), None);
+        assert_eq!(parse_range("bytes=5-1", 12), None);
+        assert_eq!(parse_range("items=0-1", 12), None);
+    }
+
+    #[test]
+    fn test_content_type() {
+        assert_eq!(content_type("site/index.HTML"), "text/html; charset=utf-8");
+        assert_eq!(content_type("db.sqlite"), "application/octet-stream");
+        assert_eq!(content_type("README"), "application/octet-stream");
+    }
+
+    #[tokio::test]
+    async fn test_health() {
+        let Json(body) = health().await;
+        assert_eq!(body["status"], "ok");
+    }
+}
@@ -0,0 +1,437 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::ffi::{c_int, OsStr};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use fuser::consts::FOPEN_KEEP_CACHE;
+use fuser::{
+    FileAttr, FileType, Filesystem, MountOption, ReplyAttr, ReplyData, ReplyDirectory, ReplyEntry,
+    ReplyOpen, Request, Session,
+};
+use tokio::runtime::Handle;
+use walter_core::blob_cache::BlobCache;
+use walter_core::config::{FileInfo, WalterConfig};
+use walter_core::reader::{file_size, legacy_file_size, read_range, read_whole};
+
+const ROOT_INODE: u64 = 1;
+
+enum Node {
+    Dir {
+        parent: u64,
+        children: Vec<(String, u64)>,
+    },
+    File {
+        parent: u64,
+        file_path: String,
+    },
+}
+
+/// The registered files laid out as directories by their paths. Inode `n`
+/// is `nodes[n - 1]`, the root directory is inode 1.
+pub struct MountTree {
+    nodes: Vec<Node>,
+}
+
+impl MountTree {
+    pub fn new(config: &WalterConfig) -> Self {
+        let mut tree = MountTree {
+            nodes: vec![Node::Dir {
+                parent: ROOT_INODE,
+                children: Vec::new(),
+            }],
+        };
+
+        let mut file_paths: Vec<&String> = config.get_files().keys().collect();
+        file_paths.sort();
+        for file_path in file_paths {
+            if !tree.insert(file_path) {
+                eprintln!("Skipping {}, its path clashes with another file", file_path);
+            }
+        }
+
+        tree
+    }
+
+    // Adds a file and the directories leading to it, false if a file is
+    // already where it or one of its directories would go
+    fn insert(&mut self, file_path: &str) -> bool {
+        let components: Vec<&str> = file_path
+            .split('/')
+            .filter(|component| !matches!(*component, "" | "." | ".."))
+            .collect();
+        let Some((name, dirs)) = components.split_last() else {
+            return false;
+        };
+
+        let mut inode = ROOT_INODE;
+        for dir in dirs {
+            inode = match self.lookup(inode, dir) {
+                Some(child) if self.children(child).is_some() => child,
+                Some(_) => return false,
+                None => self.add_child(
+                    inode,
+                    dir,
+                    Node::Dir {
+                        parent: inode,
+                        children: Vec::new(),
+                    },
+                ),
+            };
+        }
+
+        if self.lookup(inode, name).is_some() {
+            return false;
+        }
+        self.add_child(
+            inode,
+            name,
+            Node::File {
+                parent: inode,
+                file_path: file_path.to_string(),
+            },
+        );
+        true
+    }
+
+    fn add_child(&mut self, parent: u64, name: &str, node: Node) -> u64 {
+        self.nodes.push(node);
+        let inode = self.nodes.len() as u64;
+        if let Some(Node::Dir { children, .. }) = self.node_mut(parent) {
+            children.push((name.to_string(), inode));
+        }
+        inode
+    }
+
+    fn node(&self, inode: u64) -> Option<&Node> {
+        self.nodes.get((inode as usize).checked_sub(1)?)
+    }
+
+    fn node_mut(&mut self, inode: u64) -> Option<&mut Node> {
+        self.nodes.get_mut((inode as usize).checked_sub(1)?)
+    }
+
+    pub fn lookup(&self, parent: u64, name: &str) -> Option<u64> {
+        self.children(parent)?
+            .iter()
+            .find(|(child, _)| child == name)
+            .map(|&(_, inode)| inode)
+    }
+
+    /// The entries of a directory, `None` for files.
+    pub fn children(&self, inode: u64) -> Option<&[(String, u64)]> {
+        match self.node(inode)? {
+            Node::Dir { children, .. } => Some(children),
+            Node::File { .. } => None,
+        }
+    }
+
+    /// The registered path of a file, `None` for directories.
+    pub fn file_path(&self, inode: u64) -> Option<&str> {
+        match self.node(inode)? {
+            Node::File { file_path, .. } => Some(file_path),
+            Node::Dir { .. } => None,
+        }
+    }
+
+    pub fn parent(&self, inode: u64) -> Option<u64> {
+        match self.node(inode)? {
+            Node::Dir { parent, .. } | Node::File { parent, .. } => Some(*parent),
+        }
+    }
+}
+
+// How long the kernel may keep looked up names and attributes
+const TTL: Duration = Duration::from_secs(60);
+
+// Serves the tree, blocking on walter-core for file sizes and contents
+struct WalterFs {
+    tree: MountTree,
+    files: HashMap<String, FileInfo>,
+    password: Option<String>,
+    cache: BlobCache,
+    runtime: Handle,
+    // Everything in the mount belongs to the owner of the mountpoint
+    owner: (u32, u32),
+    mounted_at: SystemTime,
+    sizes: HashMap<u64, u64>,
+    // Files encrypted as a whole are decrypted in one go, the last one is kept
+    // so reading it block by block doesn't decrypt it again for every block
+    decrypted: Option<(u64, Vec<u8>)>,
+}
+
+impl WalterFs {
+    fn file_info(&self, inode: u64) -> Result<&FileInfo, c_int> {
+        let file_path = self.tree.file_path(inode).ok_or(libc::EISDIR)?;
+        self.files.get(file_path).ok_or(libc::ENOENT)
+    }
+
+    fn decrypted(&mut self, inode: u64) -> Result<&[u8], c_int> {
+        if !matches!(&self.decrypted, Some((cached, _)) if *cached == inode) {
+            let file_info = self.file_info(inode)?;
+            let data = self
+                .runtime
+                .block_on(read_whole(&self.cache, file_info, self.password.as_deref()))
+                .map_err(|e| io_error(self.tree.file_path(inode), e))?;
+            self.decrypted = Some((inode, data));
+        }
+
+        Ok(self
+            .decrypted
+            .as_ref()
+            .map(|(_, data)| data.as_slice())
+            .unwrap_or(&[]))
+    }
+
+    fn size(&mut self, inode: u64) -> Result<u64, c_int> {
+        if let Some(&size) = self.sizes.get(&inode) {
+            return Ok(size);
+        }
+
+        let file_info = self.file_info(inode)?;
+        let size = match self.runtime.block_on(file_size(file_info)) {
+            Ok(Some(size)) => Ok(size),
+            // Files encrypted as a whole need the password even for their size
+            Ok(None) => match self.password.as_deref() {
+                Some(password) => {
+                    self.runtime
+                        .block_on(legacy_file_size(&self.cache, file_info, password))
+                }
+                None => return Ok(0),
+            },
+            Err(e) => Err(e),
+        }
+        .map_err(|e| io_error(self.tree.file_path(inode), e))?;
+
+        self.sizes.insert(inode, size);
+        Ok(size)
+    }
+
+    fn attr(&mut self, inode: u64) -> Result<FileAttr, c_int> {
+        let (kind, size, perm) = if self.tree.children(inode).is_some() {
+            (FileType::Directory, 0, 0o555)
+        } else {
+            // Encrypted files are only readable when the mount has a password
+            let readable = self.password.is_some() || !self.file_info(inode)?.is_encrypted;
+            let perm = if readable { 0o444 } else { 0 };
+            (FileType::RegularFile, self.size(inode)?, perm)
+        };
+
+        Ok(FileAttr {
+            ino: inode,
+            size,
+            blocks: size.div_ceil(512),
+            atime: self.mounted_at,
+            mtime: self.mounted_at,
+            ctime: self.mounted_at,
+            crtime: self.mounted_at,
+            kind,
+            perm,
+            nlink: if kind == FileType::Directory { 2 } else { 1 },
+            uid: self.owner.0,
+            gid: self.owner.1,
+            rdev: 0,
+            blksize: 4096,
+            flags: 0,
+        })
+    }
+
+    fn read_at(&mut self, inode: u64, offset: u64, size: u32) -> Result<Vec<u8>, c_int> {
+        let range = offset..offset.saturating_add(size as u64);
+        let file_info = self.file_info(inode)?;
+
+        if file_info.is_encrypted && file_info.encryption.is_none() {
+            let data = self.decrypted(inode)?;
+            let end = (range.end as usize).min(data.len());
+            return Ok(data[(range.start as usize).min(end)..end].to_vec());
+        }
+
+        self.runtime
+            .block_on(read_range(
+                &self.cache,
+                file_info,
+                range,
+                self.password.as_deref(),
+            ))
+            .map_err(|e| io_error(self.tree.file_path(inode), e))
+    }
+}
+
+fn io_error(file_path: Option<&str>, e: Box<dyn Error>) -> c_int {
+    eprintln!("Failed to read {}: {}", file_path.unwrap_or("a file"), e);
+    libc::EIO
+}
+
+impl Filesystem for WalterFs {
+    fn lookup(&mut self, _req: &Request<'_>, parent: u64, name: &OsStr, reply: ReplyEntry) {
+        let inode = name
+            .to_str()
+            .and_then(|name| self.tree.lookup(parent, name))
+            .ok_or(libc::ENOENT);
+        match inode.and_then(|inode| self.attr(inode)) {
+            Ok(attr) => reply.entry(&TTL, &attr, 0),
+            Err(errno) => reply.error(errno),
+        }
+    }
+
+    fn getattr(&mut self, _req: &Request<'_>, inode: u64, _fh: Option<u64>, reply: ReplyAttr) {
+        match self.attr(inode) {
+            Ok(attr) => reply.attr(&TTL, &attr),
+            Err(errno) => reply.error(errno),
+        }
+    }
+
+    fn open(&mut self, _req: &Request<'_>, inode: u64, flags: i32, reply: ReplyOpen) {
+        if flags & libc::O_ACCMODE != libc::O_RDONLY {
+            return reply.error(libc::EROFS);
+        }
+        match self.file_info(inode) {
+            Ok(file_info) if file_info.is_encrypted && self.password.is_none() => {
+                reply.error(libc::EACCES)
+            }
+            // Registered files never change, the kernel can keep what it read
+            Ok(_) => reply.opened(0, FOPEN_KEEP_CACHE),
+            Err(errno) => reply.error(errno),
+        }
+    }
+
+    fn read(
+        &mut self,
+        _req: &Request<'_>,
+        inode: u64,
+        _fh: u64,
+        offset: i64,
+        size: u32,
+        _flags: i32,
+        _lock_owner: Option<u64>,
+        reply: ReplyData,
+    ) {
+        match self.read_at(inode, offset.max(0) as u64, size) {
+            Ok(data) => reply.data(&data),
+            Err(errno) => reply.error(errno),
+        }
+    }
+
+    fn readdir(
+        &mut self,
+        _req: &Request<'_>,
+        inode: u64,
+        _fh: u64,
+        offset: i64,
+        mut reply: ReplyDirectory,
+    ) {
+        let Some(children) = self.tree.children(inode) else {
+            return reply.error(libc::ENOTDIR);
+        };
+        let parent = self.tree.parent(inode).unwrap_or(ROOT_INODE);
+
+        let entries = [(".", inode), ("..", parent)]
+            .into_iter()
+            .chain(children.iter().map(|(name, child)| (name.as_str(), *child)));
+        for (index, (name, child)) in entries.enumerate().skip(offset.max(0) as usize) {
+            let kind = if self.tree.children(child).is_some() {
+                FileType::Directory
+            } else {
+                FileType::RegularFile
+            };
+            // The offset is where the next readdir picks up, true once the buffer is full
+            if reply.add(child, index as i64 + 1, kind, name) {
+                break;
+            }
+        }
+        reply.ok();
+    }
+}
+
+/// Mounts the registered files read-only on `mountpoint` until it is
+/// unmounted or the process gets Ctrl-C. Shards are fetched on first read
+/// through the blob cache, encrypted files are decrypted with `password`.
+pub async fn mount(mountpoint: &Path, password: Option<String>) -> Result<(), Box<dyn Error>> {
+    let config = WalterConfig::load_config_file();
+    let mountpoint = PathBuf::from(shellexpand::tilde(&mountpoint.to_string_lossy()).to_string());
+    let metadata = std::fs::metadata(&mountpoint)?;
+
+    let fs = WalterFs {
+        tree: MountTree::new(&config),
+        files: config.get_files().clone(),
+        password,
+        cache: BlobCache::from_config(&config),
+        runtime: Handle::current(),
+        owner: (metadata.uid(), metadata.gid()),
+        mounted_at: SystemTime::now(),
+        sizes: HashMap::new(),
+        decrypted: None,
+    };
+    let file_count = fs.files.len();
+
+    // Root mounts directly, everyone else goes through fusermount
+    let options = [
+        MountOption::RO,
+        MountOption::NoSuid,
+        MountOption::NoDev,
+        MountOption::DefaultPermissions,
+        MountOption::FSName("walter".to_string()),
+        MountOption::Subtype("walter".to_string()),
+    ];
+    let mut session = Session::new(fs, &mountpoint, &options)?;
+    let mut unmounter = session.unmount_callable();
+    println!(
+        "Mounted {} files on {}, press Ctrl-C to unmount",
+        file_count,
+        mountpoint.display()
+    );
+
+    // The session blocks on every request, so it gets a thread of its own
+    let mut running = tokio::task::spawn_blocking(move || session.run());
+    tokio::select! {
+        result = &mut running => return Ok(result??),
+        _ = tokio::signal::ctrl_c() => unmounter.unmount()?,
+    }
+
+    running.await??;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use walter_core::test_util::test_config;
+
+    #[test]
+    fn test_mount_tree() {
+        let mut config = test_config();
+        config.add_file("/home/walter/dumps/db.sqlite", false, vec!["a".to_string()]);
+        config.add_file("/home/walter/notes.txt", true, vec!["b".to_string()]);
+        config.add_file("notes.txt", false, vec!["c".to_string()]);
+        // A file can't also be a directory
+        config.add_file("notes.txt/old", false, vec!["d".to_string()]);
+
+        let tree = MountTree::new(&config);
+        let names = |inode| -> Vec<&str> {
+            tree.children(inode)
+                .unwrap()
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect()
+        };
+        assert_eq!(names(ROOT_INODE), vec!["home", "notes.txt"]);
+
+        let home = tree.lookup(ROOT_INODE, "home").unwrap();
+        let walter = tree.lookup(home, "walter").unwrap();
+        assert_eq!(names(walter), vec!["dumps", "notes.txt"]);
+        assert_eq!(tree.parent(walter), Some(home));
+
+        let db = tree
+            .lookup(tree.lookup(walter, "dumps").unwrap(), "db.sqlite")
+            .unwrap();
+        assert_eq!(tree.file_path(db), Some("/home/walter/dumps/db.sqlite"));
+        assert!(tree.children(db).is_none());
+        assert!(tree.file_path(walter).is_none());
+
+        let notes = tree.lookup(ROOT_INODE, "notes.txt").unwrap();
+        assert_eq!(tree.file_path(notes), Some("notes.txt"));
+        assert!(tree.lookup(notes, "old").is_none());
+        assert!(tree.lookup(ROOT_INODE, "missing").is_none());
+    }
+}
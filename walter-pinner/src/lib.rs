@@ -0,0 +1,281 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::error::Error;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+use walter_core::client::{list_blobs, walrus_info};
+use walter_core::config::WalterConfig;
+use walter_core::renewal::{kept_alive_blobs, RenewalRecord, RenewalScheduler};
+use walter_core::types::{BlobInfo, WalrusInfo};
+
+pub const DEFAULT_ADDR: &str = "127.0.0.1:1337";
+
+/// A blob the pinner keeps alive, with how long it has left.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct PinnedBlob {
+    pub blob_id: String,
+    pub file_path: Option<String>,
+    pub pinned: bool,
+    pub expiration_epoch: Option<u64>,
+    pub epochs_left: Option<u64>,
+    pub days_left: Option<f64>,
+}
+
+/// Every kept alive blob in `config` matched against the listed `blobs`.
+/// Blobs that aren't listed any more have no expiry.
+pub fn pinned_blobs(
+    config: &WalterConfig,
+    blobs: &[BlobInfo],
+    info: &WalrusInfo,
+) -> Vec<PinnedBlob> {
+    kept_alive_blobs(config)
+        .into_iter()
+        .map(|(blob_id, file_path)| {
+            let expiration_epoch = blobs
+                .iter()
+                .filter(|blob| blob.blob_id == blob_id)
+                .map(|blob| blob.expiration_epoch)
+                .max();
+            let epochs_left = expiration_epoch.map(|epoch| info.epochs_until(epoch));
+
+            PinnedBlob {
+                pinned: config.get_pinned_blobs().contains(&blob_id),
+                blob_id,
+                file_path,
+                expiration_epoch,
+                epochs_left,
+                days_left: epochs_left.map(|epochs| info.epochs_to_days(epochs)),
+            }
+        })
+        .collect()
+}
+
+#[derive(Clone)]
+struct PinnerState {
+    dry_run: bool,
+    // The TUI writes the same config file, so the service never interleaves its own writes
+    config_lock: Arc<Mutex<()>>,
+}
+
+type ApiError = (StatusCode, Json<serde_json::Value>);
+
+fn api_error(status: StatusCode, message: impl ToString) -> ApiError {
+    (status, Json(json!({ "error": message.to_string() })))
+}
+
+fn load_config() -> Result<WalterConfig, ApiError> {
+    WalterConfig::try_load().map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, e))
+}
+
+#[derive(Deserialize)]
+struct RenewParams {
+    dry_run: Option<bool>,
+}
+
+impl PinnerState {
+    // A request can ask for a dry run but never turn one off, the endpoint is unauthenticated
+    fn renew_dry_run(&self, params: &RenewParams) -> bool {
+        self.dry_run || params.dry_run.unwrap_or(false)
+    }
+}
+
+async fn health() -> Json<serde_json::Value> {
+    Json(json!({
+        "status": "ok",
+        "version": env!("CARGO_PKG_VERSION"),
+    }))
+}
+
+async fn list_pins() -> Result<Json<Vec<PinnedBlob>>, ApiError> {
+    let config = load_config()?;
+    let info = walrus_info()
+        .await
+        .map_err(|e| api_error(StatusCode::BAD_GATEWAY, e))?;
+    let blobs = list_blobs(false)
+        .await
+        .map_err(|e| api_error(StatusCode::BAD_GATEWAY, e))?;
+
+    Ok(Json(pinned_blobs(&config, &blobs, &info)))
+}
+
+async fn pin(
+    State(state): State<PinnerState>,
+    Path(blob_id): Path<String>,
+) -> Result<(StatusCode, Json<serde_json::Value>), ApiError> {
+    let _guard = state.config_lock.lock().await;
+    let mut config = load_config()?;
+
+    // Pinning twice is fine, only a new pin is written back
+    let status = if config.pin_blob(&blob_id) {
+        config.save_config_file();
+        StatusCode::CREATED
+    } else {
+        StatusCode::OK
+    };
+
+    Ok((status, Json(json!({ "blob_id": blob_id, "pinned": true }))))
+}
+
+async fn unpin(
+    State(state): State<PinnerState>,
+    Path(blob_id): Path<String>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let _guard = state.config_lock.lock().await;
+    let mut config = load_config()?;
+
+    if !config.unpin_blob(&blob_id) {
+        if let Some(location) = config.get_blob_locations().get(&blob_id) {
+            return Err(api_error(
+                StatusCode::CONFLICT,
+                format!(
+                    "{} is a shard of {}, remove the file to stop renewing it",
+                    blob_id, location.file_path
+                ),
+            ));
+        }
+        return Err(api_error(
+            StatusCode::NOT_FOUND,
+            format!("{} is not pinned", blob_id),
+        ));
+    }
+
+    config.save_config_file();
+    Ok(Json(json!({ "blob_id": blob_id, "pinned": false })))
+}
+
+async fn renew(
+    State(state): State<PinnerState>,
+    Query(params): Query<RenewParams>,
+) -> Result<Json<Vec<RenewalRecord>>, ApiError> {
+    let scheduler = RenewalScheduler::new(state.renew_dry_run(&params));
+    let config = load_config()?;
+
+    scheduler
+        .run_once(&config)
+        .await
+        .map(Json)
+        .map_err(|e| api_error(StatusCode::BAD_GATEWAY, e))
+}
+
+fn router(state: PinnerState) -> Router {
+    Router::new()
+        .route("/health", get(health))
+        .route("/pins", get(list_pins))
+        .route("/pins/{blob_id}", post(pin).delete(unpin))
+        .route("/renew", post(renew))
+        .with_state(state)
+}
+
+/// Serves the pinning API on `addr` and renews kept alive blobs every
+/// `interval` until the process is stopped.
+pub async fn serve(addr: &str, interval: Duration, dry_run: bool) -> Result<(), Box<dyn Error>> {
+    let state = PinnerState {
+        dry_run,
+        config_lock: Arc::new(Mutex::new(())),
+    };
+
+    tokio::spawn(async move {
+        // A panicking pass would end the loop while /health still answers, so
+        // the loop runs in a task of its own and is started again after one
+        loop {
+            let renewals = tokio::spawn(async move {
+                RenewalScheduler::new(dry_run)
+                    .run(interval, |result| match result {
+                        Ok(records) => {
+                            for record in records {
+                                println!("{}", record.describe());
+                            }
+                        }
+                        Err(e) => eprintln!("Renewal failed: {}", e),
+                    })
+                    .await
+            });
+            if let Err(e) = renewals.await {
+                eprintln!("Renewal loop stopped, restarting it: {}", e);
+            }
+            tokio::time::sleep(interval).await;
+        }
+    });
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    println!("Pinner listening on http://{}", listener.local_addr()?);
+    axum::serve(listener, router(state)).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use walter_core::test_util::{blob, test_config, walrus_info};
+
+    #[test]
+    fn test_pinned_blobs() {
+        let mut config = test_config();
+        config.add_file("db.sqlite", false, vec!["shard".to_string()]);
+        config.pin_blob("pinned");
+        config.pin_blob("missing");
+
+        let info = walrus_info();
+        let blobs = vec![blob("shard", 15), blob("pinned", 12), blob("pinned", 11)];
+
+        assert_eq!(
+            pinned_blobs(&config, &blobs, &info),
+            vec![
+                PinnedBlob {
+                    blob_id: "missing".to_string(),
+                    file_path: None,
+                    pinned: true,
+                    expiration_epoch: None,
+                    epochs_left: None,
+                    days_left: None,
+                },
+                PinnedBlob {
+                    blob_id: "pinned".to_string(),
+                    file_path: None,
+                    pinned: true,
+                    expiration_epoch: Some(12),
+                    epochs_left: Some(2),
+                    days_left: Some(2.0),
+                },
+                PinnedBlob {
+                    blob_id: "shard".to_string(),
+                    file_path: Some("db.sqlite".to_string()),
+                    pinned: false,
+                    expiration_epoch: Some(15),
+                    epochs_left: Some(5),
+                    days_left: Some(5.0),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_renew_dry_run() {
+        let state = |dry_run| PinnerState {
+            dry_run,
+            config_lock: Arc::new(Mutex::new(())),
+        };
+        let params = |dry_run| RenewParams { dry_run };
+
+        assert!(state(true).renew_dry_run(&params(Some(false))));
+        assert!(state(true).renew_dry_run(&params(None)));
+        assert!(state(false).renew_dry_run(&params(Some(true))));
+        assert!(!state(false).renew_dry_run(&params(Some(false))));
+        assert!(!state(false).renew_dry_run(&params(None)));
+    }
+
+    #[tokio::test]
+    async fn test_health() {
+        let Json(body) = health().await;
+        assert_eq!(body["status"], "ok");
+    }
+}
@@ -1,9 +1,32 @@
-use ratatui::widgets::{ScrollbarState, TableState};
+use ratatui::widgets::{ListState, ScrollbarState, TableState};
+use std::collections::{BTreeSet, HashMap};
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use walter_core::client::{download_blob, WalrusClient};
-use walter_core::config::WalterConfig;
+use walter_core::client::{blob_status, download_blob, list_blobs, upload_blob, WalrusClient};
+use walter_core::config::watcher::ConfigWatcher;
+use walter_core::config::{BlobLocation, WalterConfig};
+use walter_core::cost::{
+    estimate_blob_cost, estimate_extension_cost, estimate_file_cost, format_cost, total_cost,
+    CostEstimate,
+};
 use walter_core::epoch_extender::extend_epoch;
-use walter_core::types::BlobInfo;
+use walter_core::folder_watcher::{upload_watched_file, FolderWatcher};
+use walter_core::migrator::{
+    estimate_migration_cost, migrate_files, plan_files, MigrationOptions, MigrationPlan,
+    PlannedFile,
+};
+use walter_core::types::{BlobInfo, DownloadReport, ShardDownload, WalrusInfo};
+
+use crate::bulk::{run_blob_action, run_bulk, ActionTarget, BulkOptions};
+use crate::dashboard::{
+    format_size, summarize_files, BlobAction, BlobFilter, DashboardView, FileSummary,
+};
+use crate::downloader::{destination_path, verify_download, DownloadFocus, VerificationResult};
+use crate::input::{PasswordPrompt, TextInput};
+use crate::jobs::{JobKind, JobOutput, JobRunner, JobStatus};
+use crate::migrator::{next_shard_size, MigrationFilter};
+use crate::picker::FilePicker;
+use crate::utils;
 
 pub enum CurrentScreen {
     Splash,
@@ -11,6 +34,42 @@ pub enum CurrentScreen {
     Uploader,
     Migrator,
     SharderAndEpochExtender,
+    Jobs,
+    Downloader,
+}
+
+/// Uploads and extensions held back until their estimated cost is confirmed.
+pub enum SpendAction {
+    Upload,
+    Shard(Option<String>),
+    Extend,
+    Batch(Vec<String>),
+    Migrate(Option<String>),
+}
+
+/// Estimated cost of a `SpendAction`. `unknown` counts the files or blobs
+/// whose cost couldn't be estimated, which are left out of `cost`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SpendEstimate {
+    pub cost: u64,
+    pub unknown: usize,
+}
+
+impl SpendEstimate {
+    fn from_parts(parts: impl IntoIterator<Item = Option<u64>>) -> Self {
+        parts
+            .into_iter()
+            .fold(SpendEstimate::default(), |estimate, part| match part {
+                Some(cost) => SpendEstimate {
+                    cost: estimate.cost + cost,
+                    ..estimate
+                },
+                None => SpendEstimate {
+                    unknown: estimate.unknown + 1,
+                    ..estimate
+                },
+            })
+    }
 }
 
 pub struct App {
@@ -22,15 +81,31 @@ pub struct App {
     pub table_state: TableState,
     pub scrollbar_state: ScrollbarState,
     pub user_blobs: Vec<BlobInfo>,
-    pub walrus_system_info: String,
+    pub visible_blobs: Vec<usize>,
+    pub blob_filter: BlobFilter,
+    pub blob_locations: HashMap<String, BlobLocation>,
+    pub dashboard_view: DashboardView,
+    pub file_summaries: Vec<FileSummary>,
+    pub visible_files: Vec<usize>,
+    pub walrus_info: Option<WalrusInfo>,
     pub is_editing: bool,
+    pub pending_action: Option<(BlobAction, ActionTarget)>,
+    pub bulk_awaiting_password: Option<(BlobAction, ActionTarget)>,
+    pub marked_blobs: BTreeSet<String>,
+    pub marked_files: BTreeSet<String>,
+    pub action_output: Option<(String, String)>,
+    pub dashboard_status: String,
+    pub pending_spend: Option<SpendAction>,
 
-    pub filename: String,
+    pub filename: TextInput,
+    pub file_size: Option<u64>,
+    pub file_picker: Option<FilePicker>,
     pub pinata_api_key: String,
-    pub shard_encrypting: bool,
+    pub password_prompt: Option<PasswordPrompt>,
     pub shard_pass: String,
     pub extender_blob_id: String,
     pub walrus_client: WalrusClient,
+    pub config_watcher: Option<ConfigWatcher>,
 
     pub file_upload_status: String,
     pub sharder_status: String,
@@ -38,38 +113,109 @@ pub struct App {
     pub migration_status: String,
 
     pub epochs: u16,
+
+    pub jobs: JobRunner,
+    pub jobs_table_state: TableState,
+    pub folder_watcher: Option<FolderWatcher>,
+    pub watch_password: Option<String>,
+    pub watch_status: String,
+
+    pub download_focus: DownloadFocus,
+    pub download_list_state: ListState,
+    pub download_blob_id: TextInput,
+    pub download_dest: TextInput,
+    pub download_job: Option<u64>,
+    pub download_status: String,
+    pub download_results: Vec<VerificationResult>,
+
+    pub migration_plan: MigrationPlan,
+    pub visible_migration_files: Vec<usize>,
+    pub migration_filter: MigrationFilter,
+    pub checked_migration_files: BTreeSet<String>,
+    pub migration_table_state: TableState,
+    pub migration_epochs: u16,
+    pub migration_shard_size: usize,
+    pub migration_encrypt: bool,
 }
 
 impl App {
     pub fn new() -> Self {
+        let walrus_client = WalrusClient::new(WalterConfig::load_config_file());
+
+        let mut download_dest = TextInput::new();
+        download_dest.set_value(&format!(
+            "{}/",
+            walrus_client.config.get_download_dir_path().display()
+        ));
+
+        let migration_epochs = walrus_client.config.get_default_epochs();
+        let migration_shard_size = walrus_client.config.get_default_shard_size();
+
         App {
             current_screen: CurrentScreen::Splash,
             should_quit: false,
             table_state: TableState::default().with_selected(0),
             user_blobs: Vec::new(),
+            visible_blobs: Vec::new(),
+            blob_filter: BlobFilter::new(),
+            blob_locations: HashMap::new(),
+            dashboard_view: DashboardView::Blobs,
+            file_summaries: Vec::new(),
+            visible_files: Vec::new(),
             scrollbar_state: ScrollbarState::new(0),
             sui_active_address: String::new(),
             sui_active_env: String::new(),
-            walrus_system_info: String::new(),
+            walrus_info: None,
             is_editing: false,
-            filename: String::new(),
+            pending_action: None,
+            bulk_awaiting_password: None,
+            marked_blobs: BTreeSet::new(),
+            marked_files: BTreeSet::new(),
+            action_output: None,
+            dashboard_status: String::new(),
+            pending_spend: None,
+            filename: TextInput::new(),
+            file_size: None,
+            file_picker: None,
             pinata_api_key: String::new(),
             shard_pass: String::new(),
             extender_blob_id: String::new(),
-            walrus_client: WalrusClient::new(WalterConfig::load_config_file()),
+            walrus_client,
+            // Without a watcher the config is only reloaded after the TUI's own jobs
+            config_watcher: ConfigWatcher::new().ok(),
             file_upload_status: String::new(),
             sharder_status: String::new(),
             extender_status: String::new(),
             migration_status: String::new(),
             epochs: 1,
-            shard_encrypting: false,
+            password_prompt: None,
+            jobs: JobRunner::new(),
+            jobs_table_state: TableState::default().with_selected(0),
+            folder_watcher: None,
+            watch_password: None,
+            watch_status: String::new(),
+            download_focus: DownloadFocus::Files,
+            download_list_state: ListState::default().with_selected(Some(0)),
+            download_blob_id: TextInput::new(),
+            download_dest,
+            download_job: None,
+            download_status: String::new(),
+            download_results: Vec::new(),
+            migration_plan: MigrationPlan::default(),
+            visible_migration_files: Vec::new(),
+            migration_filter: MigrationFilter::new(),
+            checked_migration_files: BTreeSet::new(),
+            migration_table_state: TableState::default().with_selected(0),
+            migration_epochs,
+            migration_shard_size,
+            migration_encrypt: false,
         }
     }
     pub fn next_row(&mut self) {
-        if !self.user_blobs.is_empty() {
+        if self.visible_len() > 0 {
             let i = match self.table_state.selected() {
                 Some(i) => {
-                    if i >= self.user_blobs.len() - 1 {
+                    if i >= self.visible_len() - 1 {
                         0
                     } else {
                         i + 1
@@ -82,11 +228,11 @@ impl App {
         }
     }
     pub fn prev_row(&mut self) {
-        if !self.user_blobs.is_empty() {
+        if self.visible_len() > 0 {
             let i = match self.table_state.selected() {
                 Some(i) => {
-                    if i <= 0 {
-                        self.user_blobs.len() - 1
+                    if i == 0 {
+                        self.visible_len() - 1
                     } else {
                         i - 1
                     }
@@ -98,55 +244,1306 @@ impl App {
         }
     }
 
-    pub async fn upload_shard(&mut self) -> String {
-        let password = match self.shard_pass.len() {
-            0 => None,
-            _ => Some(self.shard_pass.clone()),
+    /// Number of rows in the Dashboard table for the current view.
+    pub fn visible_len(&self) -> usize {
+        match self.dashboard_view {
+            DashboardView::Blobs => self.visible_blobs.len(),
+            DashboardView::Files => self.visible_files.len(),
+        }
+    }
+
+    pub fn toggle_dashboard_view(&mut self) {
+        self.dashboard_view = match self.dashboard_view {
+            DashboardView::Blobs => DashboardView::Files,
+            DashboardView::Files => DashboardView::Blobs,
         };
+        self.table_state.select(Some(0));
+        self.update_visible_blobs();
+    }
+
+    /// Recomputes which blobs the Dashboard shows after the list, sort or filters change.
+    pub fn update_visible_blobs(&mut self) {
+        let current_epoch = self.walrus_info.as_ref().map(|info| info.current_epoch);
+        self.visible_blobs = self.blob_filter.apply(&self.user_blobs, current_epoch);
+
+        let config = &self.walrus_client.config;
+        self.blob_locations = config.get_blob_locations();
+        self.file_summaries = summarize_files(config.get_files(), &self.user_blobs);
+
+        let search = self.blob_filter.search.to_lowercase();
+        self.visible_files = self
+            .file_summaries
+            .iter()
+            .enumerate()
+            .filter(|(_, file)| file.file_path.to_lowercase().contains(&search))
+            .map(|(i, _)| i)
+            .collect();
+
+        self.scrollbar_state = ScrollbarState::new(self.visible_len().saturating_sub(1));
+        if self.table_state.selected().unwrap_or(0) >= self.visible_len() {
+            self.table_state.select(Some(0));
+        }
+    }
+
+    pub fn selected_blob(&self) -> Option<&BlobInfo> {
+        if self.dashboard_view != DashboardView::Blobs {
+            return None;
+        }
+
+        self.visible_blobs
+            .get(self.table_state.selected().unwrap_or(0))
+            .map(|&i| &self.user_blobs[i])
+    }
+
+    pub fn selected_file(&self) -> Option<&FileSummary> {
+        if self.dashboard_view != DashboardView::Files {
+            return None;
+        }
+
+        self.visible_files
+            .get(self.table_state.selected().unwrap_or(0))
+            .map(|&i| &self.file_summaries[i])
+    }
 
-        let result = self
-            .walrus_client
-            .upload_file(&self.filename, password)
-            .await;
+    pub fn next_job(&mut self) {
+        if !self.jobs.jobs.is_empty() {
+            let i = match self.jobs_table_state.selected() {
+                Some(i) if i + 1 < self.jobs.jobs.len() => i + 1,
+                _ => 0,
+            };
+            self.jobs_table_state.select(Some(i));
+        }
+    }
+
+    pub fn prev_job(&mut self) {
+        if !self.jobs.jobs.is_empty() {
+            let i = match self.jobs_table_state.selected() {
+                Some(i) if i > 0 && i < self.jobs.jobs.len() => i - 1,
+                _ => self.jobs.jobs.len() - 1,
+            };
+            self.jobs_table_state.select(Some(i));
+        }
+    }
+
+    pub fn cancel_selected_job(&mut self) {
+        let selected = self.jobs_table_state.selected().unwrap_or(0);
+        if let Some((id, kind)) = self.jobs.jobs.get(selected).map(|job| (job.id, job.kind)) {
+            // A cancelled job never finishes, its view hears about it here instead
+            if self.jobs.cancel(id) {
+                self.finish_job(kind, Err("cancelled".to_string()));
+            }
+        }
+    }
+
+    /// Starts or stops watching the configured folders, asking for the
+    /// password first when one of them is encrypted.
+    pub fn toggle_watching(&mut self) {
+        if self.folder_watcher.take().is_some() {
+            self.watch_password = None;
+            self.watch_status = "Stopped watching".into();
+            return;
+        }
 
-        match result {
-            Ok(b) => "success".to_string(),
-            Err(e) => "failure".to_string(),
+        let folders = self.walrus_client.config.get_watch_folders();
+        if folders.iter().any(|folder| folder.encrypt) {
+            self.password_prompt = Some(PasswordPrompt::new("Watch Folder Password", true));
+            return;
         }
+        self.start_watching(None);
     }
 
-    pub async fn download_file(&mut self) -> String {
-        let result = download_blob(&self.extender_blob_id, &self.filename).await;
+    pub fn start_watching(&mut self, password: Option<String>) {
+        let folders = self.walrus_client.config.get_watch_folders().clone();
+        match FolderWatcher::new(folders) {
+            Ok(watcher) => {
+                self.watch_status = format!("Watching {} folder(s)", watcher.folders().len());
+                self.folder_watcher = Some(watcher);
+                self.watch_password = password;
+            }
+            Err(e) => self.watch_status = e.to_string(),
+        }
+    }
 
-        match result {
-            Ok(b) => "success".to_string(),
-            Err(e) => "failure".to_string(),
+    /// Queues an upload job for every watched file that has stopped changing.
+    pub fn process_watch_folders(&mut self) {
+        let Some(watcher) = self.folder_watcher.as_mut() else {
+            return;
+        };
+
+        for (path, folder) in watcher.poll() {
+            let password = self.watch_password.clone();
+            self.jobs.spawn(
+                JobKind::WatchUpload,
+                format!("Watch upload {}", path.display()),
+                async move {
+                    let upload = upload_watched_file(&path, &folder, password).await;
+                    match upload.error {
+                        Some(_) => Err(upload.describe()),
+                        None => Ok(JobOutput::Message(upload.describe())),
+                    }
+                },
+            );
         }
     }
 
-    pub async fn download_sharded_file(&mut self) -> String {
+    /// Re-checks the current path, only when it changes rather than on every frame.
+    pub fn update_file_size(&mut self) {
+        self.file_size = std::fs::metadata(self.filename.value())
+            .ok()
+            .filter(|metadata| metadata.is_file())
+            .map(|metadata| metadata.len());
+    }
+
+    pub fn open_file_picker(&mut self) {
+        let start = if self.filename.is_empty() {
+            std::env::current_dir().unwrap_or_default()
+        } else {
+            std::path::PathBuf::from(self.filename.value())
+        };
+
+        self.is_editing = false;
+        self.file_picker = Some(FilePicker::open(&start, true));
+    }
+
+    /// Uses the files chosen in the picker: a single file becomes the current
+    /// path, several are queued as one job each.
+    pub fn pick_files(&mut self, files: Vec<std::path::PathBuf>) {
+        let files: Vec<String> = files
+            .iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect();
+
+        if let [file] = files.as_slice() {
+            self.filename.set_value(file);
+            self.update_file_size();
+            return;
+        }
+
+        self.request_spend(SpendAction::Batch(files));
+    }
+
+    fn start_batch(&mut self, files: Vec<String>) {
+        for file in files {
+            self.filename.set_value(&file);
+            match self.current_screen {
+                CurrentScreen::Uploader => self.start_upload(),
+                CurrentScreen::SharderAndEpochExtender => self.start_shard_upload(),
+                _ => {}
+            }
+        }
+
+        self.filename.clear();
+        self.update_file_size();
+    }
+
+    /// Estimated cost of uploading `file_size` bytes from the current screen,
+    /// one blob per shard on the Sharder.
+    fn upload_estimate(&self, file_size: u64, encrypted: bool) -> Option<Vec<CostEstimate>> {
+        let info = self.walrus_info.as_ref()?;
+        let config = &self.walrus_client.config;
+
+        match self.current_screen {
+            CurrentScreen::SharderAndEpochExtender => Some(estimate_file_cost(
+                info,
+                file_size,
+                config.get_default_shard_size() as u64,
+                encrypted,
+                config.get_default_epochs(),
+            )),
+            _ => Some(vec![estimate_blob_cost(info, file_size, self.epochs)]),
+        }
+    }
+
+    /// Estimate for the file currently in the path field.
+    pub fn current_upload_estimate(&self) -> Option<Vec<CostEstimate>> {
+        self.upload_estimate(self.file_size?, false)
+    }
+
+    /// Cost of extending `blobs` by the selected number of epochs.
+    pub fn extension_estimate(&self, blobs: &[BlobInfo]) -> Option<u64> {
+        let info = self.walrus_info.as_ref()?;
+        Some(
+            blobs
+                .iter()
+                .map(|blob| estimate_extension_cost(info, blob.unencoded_size, self.epochs).total())
+                .sum(),
+        )
+    }
+
+    pub fn extender_blob(&self) -> Option<&BlobInfo> {
+        let blob_id = self.extender_blob_id.trim();
+        self.user_blobs.iter().find(|blob| blob.blob_id == blob_id)
+    }
+
+    fn spend_estimate(&self, action: &SpendAction) -> SpendEstimate {
+        match action {
+            SpendAction::Upload => SpendEstimate::from_parts([self
+                .current_upload_estimate()
+                .map(|shards| total_cost(&shards))]),
+            SpendAction::Shard(password) => SpendEstimate::from_parts([self
+                .file_size
+                .and_then(|size| self.upload_estimate(size, password.is_some()))
+                .map(|shards| total_cost(&shards))]),
+            SpendAction::Extend => SpendEstimate::from_parts([self
+                .extender_blob()
+                .and_then(|blob| self.extension_estimate(std::slice::from_ref(blob)))]),
+            SpendAction::Batch(files) => SpendEstimate::from_parts(files.iter().map(|file| {
+                let size = std::fs::metadata(file).ok()?.len();
+                self.upload_estimate(size, false)
+                    .map(|shards| total_cost(&shards))
+            })),
+            // Before the file list is loaded the whole source would be migrated
+            SpendAction::Migrate(_) if self.migration_plan.files.is_empty() => {
+                SpendEstimate::from_parts([None])
+            }
+            SpendAction::Migrate(_) => SpendEstimate::from_parts(
+                self.migration_batch()
+                    .iter()
+                    .map(|planned| planned.estimated_cost),
+            ),
+        }
+    }
+
+    /// Starts `action` right away unless its estimated cost is above the
+    /// configured threshold or not fully known, in which case it waits for
+    /// confirmation.
+    pub fn request_spend(&mut self, action: SpendAction) {
+        let threshold = self.walrus_client.config.get_cost_confirmation_threshold();
+        let estimate = self.spend_estimate(&action);
+
+        if estimate.cost > threshold || estimate.unknown > 0 {
+            self.is_editing = false;
+            self.pending_spend = Some(action);
+        } else {
+            self.run_spend(action);
+        }
+    }
+
+    pub fn run_spend(&mut self, action: SpendAction) {
+        match action {
+            SpendAction::Upload => self.start_upload(),
+            SpendAction::Shard(Some(password)) => self.start_encrypted_shard_upload(password),
+            SpendAction::Shard(None) => self.start_shard_upload(),
+            SpendAction::Extend => self.start_epoch_extension(),
+            SpendAction::Batch(files) => self.start_batch(files),
+            SpendAction::Migrate(password) => self.start_migration(password),
+        }
+    }
+
+    pub fn run_pending_spend(&mut self) {
+        if let Some(action) = self.pending_spend.take() {
+            self.run_spend(action);
+        }
+    }
+
+    /// Question shown in the spending confirmation popup.
+    pub fn pending_spend_message(&self) -> String {
+        let Some(action) = &self.pending_spend else {
+            return String::new();
+        };
+
+        let question = match action {
+            SpendAction::Upload => format!(
+                "Upload {} for {} epochs?",
+                self.filename.value(),
+                self.epochs
+            ),
+            SpendAction::Shard(password) => format!(
+                "Shard and upload {}{} for {} epochs?",
+                self.filename.value(),
+                if password.is_some() { " encrypted" } else { "" },
+                self.walrus_client.config.get_default_epochs()
+            ),
+            SpendAction::Extend => format!(
+                "Extend {} by {} epochs?",
+                self.extender_blob_id.trim(),
+                self.epochs
+            ),
+            SpendAction::Batch(files) => format!("Upload {} files?", files.len()),
+            SpendAction::Migrate(password) => format!(
+                "Migrate {} files{} for {} epochs in {} shards?",
+                self.migration_batch().len(),
+                if password.is_some() { " encrypted" } else { "" },
+                self.migration_epochs,
+                format_size(self.migration_shard_size as u64)
+            ),
+        };
+
+        let threshold = self.walrus_client.config.get_cost_confirmation_threshold();
+        let estimate = self.spend_estimate(action);
+        match estimate {
+            SpendEstimate { unknown: 0, cost } => format!(
+                "{}\nEstimated cost: {}\nThis is above your confirmation threshold of {}.",
+                question,
+                format_cost(cost),
+                format_cost(threshold)
+            ),
+            SpendEstimate { cost: 0, .. } => format!(
+                "{}\nEstimated cost: unknown\nThe cost can't be estimated, confirm to spend anyway.",
+                question
+            ),
+            SpendEstimate { cost, unknown } => format!(
+                "{}\nEstimated cost: {} plus {} more of unknown cost\nThe cost can't be fully estimated, confirm to spend anyway.",
+                question,
+                format_cost(cost),
+                unknown
+            ),
+        }
+    }
+
+    /// Routes bracketed paste text to whichever field is taking input.
+    pub fn paste(&mut self, text: &str) {
+        if let Some(prompt) = self.password_prompt.as_mut() {
+            prompt.focused_input().insert_str(text);
+            return;
+        }
+
+        if self.file_picker.is_some() {
+            return;
+        }
+
+        match self.current_screen {
+            CurrentScreen::Uploader | CurrentScreen::SharderAndEpochExtender if self.is_editing => {
+                self.filename.insert_str(text);
+                self.update_file_size();
+            }
+            CurrentScreen::Dashboard if self.is_editing => {
+                self.blob_filter.search.push_str(text.trim());
+                self.update_visible_blobs();
+            }
+            CurrentScreen::Migrator if self.is_editing => {
+                self.migration_filter.search.push_str(text.trim());
+                self.update_visible_migration_files();
+            }
+            CurrentScreen::Migrator => {
+                self.set_pinata_api_key(text.trim());
+            }
+            CurrentScreen::Downloader => {
+                if self.download_focus == DownloadFocus::Files {
+                    self.download_focus = DownloadFocus::BlobId;
+                }
+                self.focused_download_input().insert_str(text.trim());
+            }
+            _ => {}
+        }
+    }
+
+    pub fn start_upload(&mut self) {
+        self.file_upload_status = "Uploading file...".into();
+        self.filename.commit_history();
+
+        let filename = self.filename.value().to_string();
+        let epochs = self.epochs;
+        self.jobs.spawn(
+            JobKind::Upload,
+            format!("Upload {}", filename),
+            async move {
+                upload_blob(&filename, epochs)
+                    .await
+                    .map(JobOutput::Message)
+                    .map_err(|e| e.to_string())
+            },
+        );
+    }
+
+    pub fn start_shard_upload(&mut self) {
+        self.sharder_status = "Sharding Started...".into();
+
         let password = match self.shard_pass.len() {
             0 => None,
             _ => Some(self.shard_pass.clone()),
         };
 
-        let result = self
-            .walrus_client
-            .download_file(&self.filename, password)
-            .await;
+        self.filename.commit_history();
+        let filename = self.filename.value().to_string();
+        let mut client = WalrusClient::new(self.walrus_client.config.clone());
+        self.jobs.spawn(
+            JobKind::Shard,
+            format!("Shard & upload {}", filename),
+            async move {
+                client
+                    .upload_file(&filename, password)
+                    .await
+                    .map(|_| JobOutput::Message(format!("{} uploaded", filename)))
+                    .map_err(|e| e.to_string())
+            },
+        );
+    }
+
+    /// Asks for the encryption password, the upload starts once it is confirmed.
+    pub fn open_password_prompt(&mut self) {
+        self.is_editing = false;
+        self.password_prompt = Some(PasswordPrompt::new("Encryption Password", true));
+    }
+
+    pub fn start_encrypted_shard_upload(&mut self, password: String) {
+        self.shard_pass = password;
+        self.start_shard_upload();
+        // Keep the password only for this upload, the next [K] stays unencrypted
+        self.shard_pass.clear();
+    }
+
+    pub fn next_download_file(&mut self) {
+        let len = self.file_summaries.len();
+        if len > 0 {
+            let i = match self.download_list_state.selected() {
+                Some(i) if i + 1 < len => i + 1,
+                _ => 0,
+            };
+            self.download_list_state.select(Some(i));
+        }
+        self.download_focus = DownloadFocus::Files;
+    }
+
+    pub fn prev_download_file(&mut self) {
+        let len = self.file_summaries.len();
+        if len > 0 {
+            let i = match self.download_list_state.selected() {
+                Some(i) if i > 0 && i < len => i - 1,
+                _ => len - 1,
+            };
+            self.download_list_state.select(Some(i));
+        }
+        self.download_focus = DownloadFocus::Files;
+    }
+
+    /// The text field typed into on the Download screen, the blob ID unless
+    /// the destination has focus.
+    pub fn focused_download_input(&mut self) -> &mut TextInput {
+        match self.download_focus {
+            DownloadFocus::Destination => &mut self.download_dest,
+            _ => &mut self.download_blob_id,
+        }
+    }
+
+    /// Starts downloading the pasted blob ID, or else the highlighted file,
+    /// asking for the password first when the file is encrypted.
+    pub fn request_download(&mut self) {
+        if !self.download_blob_id.is_empty() {
+            self.start_download(None);
+            return;
+        }
+
+        let selected = self.download_list_state.selected().unwrap_or(0);
+        match self.file_summaries.get(selected) {
+            Some(file) if file.is_encrypted => {
+                self.is_editing = false;
+                self.password_prompt = Some(PasswordPrompt::new("Decryption Password", false));
+            }
+            Some(_) => self.start_download(None),
+            None => self.download_status = "Select a file or paste a blob ID".into(),
+        }
+    }
+
+    pub fn start_download(&mut self, password: Option<String>) {
+        self.download_results.clear();
+
+        if !self.download_blob_id.is_empty() {
+            self.download_blob_id.commit_history();
+            let blob_id = self.download_blob_id.value().trim().to_string();
+            self.start_blob_download(blob_id);
+            return;
+        }
+
+        let selected = self.download_list_state.selected().unwrap_or(0);
+        let Some(file_path) = self
+            .file_summaries
+            .get(selected)
+            .map(|file| file.file_path.clone())
+        else {
+            return;
+        };
+
+        let out_path = destination_path(self.download_dest.value(), &file_path);
+        self.download_status = format!("Downloading {}...", file_path);
+
+        let client = WalrusClient::new(self.walrus_client.config.clone());
+        let id = self.jobs.spawn_with_progress(
+            JobKind::Download,
+            format!("Download {}", file_path),
+            |progress| async move {
+                if let Some(parent) = out_path.parent() {
+                    std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+                }
+
+                client
+                    .download_file_with_progress(
+                        &file_path,
+                        &out_path.to_string_lossy(),
+                        password,
+                        |done, total| progress.report(format!("Shard {}/{} read", done, total)),
+                    )
+                    .await
+                    .map(JobOutput::Download)
+                    .map_err(|e| e.to_string())
+            },
+        );
+        self.download_job = Some(id);
+    }
+
+    /// Downloads a single blob into the Download screen's destination.
+    pub fn start_blob_download(&mut self, blob_id: String) {
+        let out_path = destination_path(self.download_dest.value(), &blob_id);
+        let cache = self.walrus_client.cache.clone();
+        self.download_results.clear();
+        self.download_status = format!("Downloading {}...", blob_id);
+
+        let id = self.jobs.spawn_with_progress(
+            JobKind::Download,
+            format!("Download blob {}", blob_id),
+            |progress| async move {
+                if let Some(parent) = out_path.parent() {
+                    std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+                }
+
+                progress.report("Reading blob");
+                let out_path = out_path.to_string_lossy().to_string();
+                let success = download_blob(&cache, &blob_id, &out_path)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                if !success {
+                    return Err("Failed to download blob".to_string());
+                }
+
+                let size = std::fs::metadata(&out_path)
+                    .map_err(|e| e.to_string())?
+                    .len();
+                Ok(JobOutput::Download(DownloadReport {
+                    out_path,
+                    shards: vec![ShardDownload { blob_id, size }],
+                    decrypted: false,
+                    size,
+                }))
+            },
+        );
+        self.download_job = Some(id);
+    }
+
+    /// Text for the status line of the Download screen, live while the job runs.
+    pub fn download_progress(&self) -> String {
+        let job = self.download_job.and_then(|id| self.jobs.get(id));
+
+        match job.map(|job| (&job.status, &job.progress)) {
+            Some((JobStatus::Queued, _)) => "Queued".to_string(),
+            Some((JobStatus::Running, Some(progress))) => progress.clone(),
+            Some((JobStatus::Running, None)) => self.download_status.clone(),
+            Some((JobStatus::Cancelled, _)) => "Download cancelled".to_string(),
+            _ => self.download_status.clone(),
+        }
+    }
+
+    pub fn mark_count(&self) -> usize {
+        match self.dashboard_view {
+            DashboardView::Blobs => self.marked_blobs.len(),
+            DashboardView::Files => self.marked_files.len(),
+        }
+    }
+
+    /// Adds or removes the highlighted Dashboard row from the selection.
+    pub fn toggle_mark(&mut self) {
+        let key = match self.dashboard_view {
+            DashboardView::Blobs => self.selected_blob().map(|blob| blob.blob_id.clone()),
+            DashboardView::Files => self.selected_file().map(|file| file.file_path.clone()),
+        };
+        let Some(key) = key else {
+            return;
+        };
+
+        let marked = match self.dashboard_view {
+            DashboardView::Blobs => &mut self.marked_blobs,
+            DashboardView::Files => &mut self.marked_files,
+        };
+        if !marked.remove(&key) {
+            marked.insert(key);
+        }
+        self.next_row();
+    }
+
+    /// Selects every row, or clears the selection when everything is already selected.
+    pub fn toggle_mark_all(&mut self) {
+        match self.dashboard_view {
+            DashboardView::Blobs => {
+                if self.marked_blobs.len() == self.user_blobs.len() {
+                    self.marked_blobs.clear();
+                } else {
+                    self.marked_blobs = self.user_blobs.iter().map(|b| b.blob_id.clone()).collect();
+                }
+            }
+            DashboardView::Files => {
+                if self.marked_files.len() == self.file_summaries.len() {
+                    self.marked_files.clear();
+                } else {
+                    self.marked_files = self
+                        .file_summaries
+                        .iter()
+                        .map(|f| f.file_path.clone())
+                        .collect();
+                }
+            }
+        }
+    }
+
+    /// Adds every row matching the current search and filters to the selection.
+    pub fn mark_visible(&mut self) {
+        match self.dashboard_view {
+            DashboardView::Blobs => {
+                for &i in &self.visible_blobs {
+                    self.marked_blobs.insert(self.user_blobs[i].blob_id.clone());
+                }
+            }
+            DashboardView::Files => {
+                for &i in &self.visible_files {
+                    self.marked_files.insert(self.file_summaries[i].file_path.clone());
+                }
+            }
+        }
+    }
+
+    pub fn clear_marks(&mut self) {
+        self.marked_blobs.clear();
+        self.marked_files.clear();
+    }
+
+    // The selected rows in the current view, or the highlighted row when
+    // nothing is selected. Files expand to their shards except for downloads,
+    // which fetch the whole file.
+    fn action_target(&self, action: BlobAction) -> Option<ActionTarget> {
+        match self.dashboard_view {
+            DashboardView::Blobs if self.marked_blobs.is_empty() => self
+                .selected_blob()
+                .map(|blob| ActionTarget::Blobs(vec![blob.clone()])),
+            DashboardView::Blobs => Some(ActionTarget::Blobs(
+                self.user_blobs
+                    .iter()
+                    .filter(|blob| self.marked_blobs.contains(&blob.blob_id))
+                    .cloned()
+                    .collect(),
+            )),
+            DashboardView::Files => {
+                let files: Vec<FileSummary> = if self.marked_files.is_empty() {
+                    vec![self.selected_file()?.clone()]
+                } else {
+                    self.file_summaries
+                        .iter()
+                        .filter(|file| self.marked_files.contains(&file.file_path))
+                        .cloned()
+                        .collect()
+                };
+
+                if action == BlobAction::Download {
+                    return Some(ActionTarget::Files(files));
+                }
+
+                let blob_ids: Vec<&String> = files
+                    .iter()
+                    .filter_map(|file| self.walrus_client.config.get_file_blobs(&file.file_path))
+                    .flatten()
+                    .collect();
+                Some(ActionTarget::Blobs(
+                    self.user_blobs
+                        .iter()
+                        .filter(|blob| blob_ids.contains(&&blob.blob_id))
+                        .cloned()
+                        .collect(),
+                ))
+            }
+        }
+    }
+
+    /// Asks for confirmation before running `action` on the selected rows, or
+    /// on the highlighted row when nothing is selected.
+    pub fn request_blob_action(&mut self, action: BlobAction) {
+        let Some(mut target) = self.action_target(action) else {
+            return;
+        };
+
+        if let ActionTarget::Blobs(blobs) = &mut target {
+            if blobs.len() > 1 && matches!(action, BlobAction::Status | BlobAction::Open) {
+                self.dashboard_status = format!("{} only works on a single blob", action.title());
+                return;
+            }
+
+            if action == BlobAction::Delete {
+                blobs.retain(|blob| blob.is_deletable);
+            }
+            if blobs.is_empty() {
+                self.dashboard_status = match action {
+                    BlobAction::Delete => "No deletable blobs selected".into(),
+                    _ => "No blobs on chain for the selection".into(),
+                };
+                return;
+            }
+        }
+
+        self.pending_action = Some((action, target));
+    }
+
+    /// Question shown in the confirmation popup for the pending blob action.
+    pub fn pending_action_message(&self) -> String {
+        let Some((action, target)) = &self.pending_action else {
+            return String::new();
+        };
+
+        let blob = match target {
+            ActionTarget::Blobs(blobs) if blobs.len() == 1 => &blobs[0],
+            ActionTarget::Blobs(blobs) => {
+                return match action {
+                    BlobAction::Extend => format!(
+                        "Extend {} blobs by {} epochs?{}\n[+/-] Change epochs",
+                        blobs.len(),
+                        self.epochs,
+                        self.extension_cost_line(blobs)
+                    ),
+                    BlobAction::Download => format!(
+                        "Download {} blobs to {}?",
+                        blobs.len(),
+                        self.download_dest.value()
+                    ),
+                    BlobAction::Delete => {
+                        format!("Delete {} deletable blobs?\nThis cannot be undone.", blobs.len())
+                    }
+                    _ => format!("Read {} blobs back to check they are available?", blobs.len()),
+                };
+            }
+            ActionTarget::Files(files) if files.len() == 1 => {
+                return format!(
+                    "Download {} into {}?",
+                    files[0].file_path,
+                    self.download_dest.value()
+                );
+            }
+            ActionTarget::Files(files) => {
+                return format!(
+                    "Download {} files to {}?",
+                    files.len(),
+                    self.download_dest.value()
+                );
+            }
+        };
 
-        match result {
-            Ok(b) => "success".to_string(),
-            Err(e) => "failure".to_string(),
+        match action {
+            BlobAction::Extend => format!(
+                "Extend {} by {} epochs?{}\n[+/-] Change epochs",
+                blob.blob_id,
+                self.epochs,
+                self.extension_cost_line(std::slice::from_ref(blob))
+            ),
+            BlobAction::Download => format!(
+                "Download {} to {}?",
+                blob.blob_id,
+                destination_path(self.download_dest.value(), &blob.blob_id).display()
+            ),
+            BlobAction::Delete => format!("Delete {}?\nThis cannot be undone.", blob.blob_id),
+            BlobAction::Verify => format!("Read {} back to check it is available?", blob.blob_id),
+            BlobAction::Status => format!("Fetch the walrus blob-status of {}?", blob.blob_id),
+            BlobAction::Open => format!(
+                "Open {} in the browser?",
+                self.walrus_client.config.get_blob_url(&blob.blob_id)
+            ),
         }
     }
 
-    pub async fn extend_blob_epoch(&self) -> String {
-        let result = extend_epoch(&self.extender_blob_id, self.epochs).await;
+    // Estimated cost line for the extend popup, flagged when it is above the threshold
+    fn extension_cost_line(&self, blobs: &[BlobInfo]) -> String {
+        let Some(cost) = self.extension_estimate(blobs) else {
+            return String::new();
+        };
+
+        let threshold = self.walrus_client.config.get_cost_confirmation_threshold();
+        let warning = if cost > threshold {
+            format!(", above your threshold of {}", format_cost(threshold))
+        } else {
+            String::new()
+        };
+        format!("\nEstimated cost: {}{}", format_cost(cost), warning)
+    }
+
+    fn bulk_options(&self, password: Option<String>) -> BulkOptions {
+        BulkOptions {
+            epochs: self.epochs,
+            destination: self.download_dest.value().to_string(),
+            password,
+            config: self.walrus_client.config.clone(),
+            cache: self.walrus_client.cache.clone(),
+        }
+    }
+
+    pub fn run_pending_action(&mut self) {
+        let Some((action, target)) = self.pending_action.take() else {
+            return;
+        };
+
+        let blob = match target {
+            ActionTarget::Blobs(mut blobs) if blobs.len() == 1 => blobs.remove(0),
+            ActionTarget::Files(files) if files.iter().any(|file| file.is_encrypted) => {
+                // Encrypted files in the batch all share the one password asked for here
+                self.bulk_awaiting_password = Some((action, ActionTarget::Files(files)));
+                self.password_prompt = Some(PasswordPrompt::new("Decryption Password", false));
+                return;
+            }
+            target => {
+                self.start_bulk_action(action, target, None);
+                return;
+            }
+        };
+        let blob_id = blob.blob_id.clone();
+
+        match action {
+            BlobAction::Extend | BlobAction::Delete | BlobAction::Verify => {
+                let options = self.bulk_options(None);
+                // Verifying only reads the blob, the list doesn't need a refresh after it
+                let kind = if action == BlobAction::Verify {
+                    JobKind::BlobVerify
+                } else {
+                    JobKind::BlobAction
+                };
+                self.dashboard_status = format!("{} {}...", action.title(), blob_id);
+                self.jobs.spawn(
+                    kind,
+                    format!("{} {}", action.title(), blob_id),
+                    async move {
+                        run_blob_action(action, &blob, &options)
+                            .await
+                            .map(|message| JobOutput::Message(format!("{}: {}", blob_id, message)))
+                    },
+                );
+            }
+            BlobAction::Download => {
+                self.dashboard_status = format!("Downloading {}, see [6] Download", blob_id);
+                self.start_blob_download(blob_id);
+            }
+            BlobAction::Status => {
+                self.dashboard_status = format!("Fetching the status of {}...", blob_id);
+                self.jobs.spawn(
+                    JobKind::BlobStatus,
+                    format!("Status of {}", blob_id),
+                    async move {
+                        blob_status(&blob_id)
+                            .await
+                            .map(JobOutput::Message)
+                            .map_err(|e| e.to_string())
+                    },
+                );
+            }
+            BlobAction::Open => {
+                let url = self.walrus_client.config.get_blob_url(&blob_id);
+                self.dashboard_status = match utils::open_url(&url) {
+                    Ok(_) => format!("Opened {}", url),
+                    Err(e) => e.to_string(),
+                };
+            }
+        }
+    }
+
+    /// Runs `action` on every target as one job and clears the selection.
+    pub fn start_bulk_action(
+        &mut self,
+        action: BlobAction,
+        target: ActionTarget,
+        password: Option<String>,
+    ) {
+        let options = self.bulk_options(password);
+        let name = format!("{} x{}", action.title(), target.len());
+
+        self.dashboard_status = format!("{} started, see [5] Jobs", name);
+        self.jobs.spawn_with_progress(JobKind::BulkAction, name, |progress| {
+            run_bulk(action, target, options, progress)
+        });
+        self.clear_marks();
+    }
+
+    /// Uses a new Pinata key, the files listed for the old one are dropped.
+    pub fn set_pinata_api_key(&mut self, api_key: &str) {
+        self.pinata_api_key = api_key.to_string();
+        self.migration_plan = MigrationPlan::default();
+        self.checked_migration_files.clear();
+        self.update_visible_migration_files();
+    }
+
+    fn migration_options(&self, password: Option<String>) -> MigrationOptions {
+        MigrationOptions {
+            epochs: self.migration_epochs,
+            shard_size: self.migration_shard_size,
+            password,
+            only: Vec::new(),
+        }
+    }
+
+    /// Lists the files on Pinata with their estimated cost, nothing is migrated.
+    pub fn start_migration_plan(&mut self) {
+        self.migration_status = "Listing files...".into();
+
+        let api_key = self.pinata_api_key.clone();
+        let options = self.migration_options(None);
+        self.jobs
+            .spawn(JobKind::MigrationPlan, "List files on Pinata", async move {
+                plan_files(&api_key, &options)
+                    .await
+                    .map(JobOutput::Plan)
+                    .map_err(|e| e.to_string())
+            });
+    }
+
+    /// Recomputes the estimated costs after the epochs, shard size or
+    /// encryption of the batch change.
+    pub fn update_migration_estimates(&mut self) {
+        // Only whether there is a password matters to the estimate
+        let options = self.migration_options(self.migration_encrypt.then(String::new));
+        for planned in &mut self.migration_plan.files {
+            planned.estimated_cost =
+                estimate_migration_cost(&planned.file, &options, self.walrus_info.as_ref());
+        }
+    }
+
+    pub fn next_migration_shard_size(&mut self) {
+        self.migration_shard_size = next_shard_size(
+            self.migration_shard_size,
+            self.walrus_client.config.get_default_shard_size(),
+        );
+        self.update_migration_estimates();
+    }
+
+    /// Recomputes which listed files the Migrator shows after the list or filters change.
+    pub fn update_visible_migration_files(&mut self) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+        self.visible_migration_files = self.migration_filter.apply(&self.migration_plan.files, now);
+
+        if self.migration_table_state.selected().unwrap_or(0) >= self.visible_migration_files.len()
+        {
+            self.migration_table_state.select(Some(0));
+        }
+    }
+
+    pub fn selected_migration_file(&self) -> Option<&PlannedFile> {
+        self.visible_migration_files
+            .get(self.migration_table_state.selected().unwrap_or(0))
+            .map(|&i| &self.migration_plan.files[i])
+    }
+
+    pub fn next_migration_file(&mut self) {
+        let len = self.visible_migration_files.len();
+        if len > 0 {
+            let i = match self.migration_table_state.selected() {
+                Some(i) if i + 1 < len => i + 1,
+                _ => 0,
+            };
+            self.migration_table_state.select(Some(i));
+        }
+    }
+
+    pub fn prev_migration_file(&mut self) {
+        let len = self.visible_migration_files.len();
+        if len > 0 {
+            let i = match self.migration_table_state.selected() {
+                Some(i) if i > 0 && i < len => i - 1,
+                _ => len - 1,
+            };
+            self.migration_table_state.select(Some(i));
+        }
+    }
+
+    /// Checks or unchecks the highlighted file for the next migration.
+    pub fn toggle_migration_check(&mut self) {
+        let Some(id) = self
+            .selected_migration_file()
+            .map(|planned| planned.file.id.clone())
+        else {
+            return;
+        };
+
+        if !self.checked_migration_files.remove(&id) {
+            self.checked_migration_files.insert(id);
+        }
+        self.next_migration_file();
+    }
+
+    /// Checks every shown file that still needs migrating, or unchecks them
+    /// when they are all checked already.
+    pub fn toggle_all_migration_checks(&mut self) {
+        let ids: Vec<String> = self
+            .visible_migration_files
+            .iter()
+            .map(|&i| &self.migration_plan.files[i])
+            .filter(|planned| !planned.migrated)
+            .map(|planned| planned.file.id.clone())
+            .collect();
+
+        if ids
+            .iter()
+            .all(|id| self.checked_migration_files.contains(id))
+        {
+            for id in &ids {
+                self.checked_migration_files.remove(id);
+            }
+        } else {
+            self.checked_migration_files.extend(ids);
+        }
+    }
+
+    /// The checked files, or every shown file when none are checked. Files
+    /// migrated by an earlier run are left out.
+    pub fn migration_batch(&self) -> Vec<&PlannedFile> {
+        let files = &self.migration_plan.files;
+        let batch: Vec<&PlannedFile> = if self.checked_migration_files.is_empty() {
+            self.visible_migration_files
+                .iter()
+                .map(|&i| &files[i])
+                .collect()
+        } else {
+            files
+                .iter()
+                .filter(|planned| self.checked_migration_files.contains(&planned.file.id))
+                .collect()
+        };
+
+        batch
+            .into_iter()
+            .filter(|planned| !planned.migrated)
+            .collect()
+    }
+
+    /// Migrates the batch, asking for the password first when it is encrypted.
+    /// Before the files are listed, every file on Pinata is migrated.
+    pub fn request_migration(&mut self) {
+        if !self.migration_plan.files.is_empty() && self.migration_batch().is_empty() {
+            self.migration_status = "Nothing to migrate in the selection".into();
+            return;
+        }
+
+        if self.migration_encrypt {
+            self.is_editing = false;
+            self.password_prompt = Some(PasswordPrompt::new("Encryption Password", true));
+            return;
+        }
+        self.request_spend(SpendAction::Migrate(None));
+    }
+
+    pub fn start_migration(&mut self, password: Option<String>) {
+        let mut options = self.migration_options(password);
+        options.only = self
+            .migration_batch()
+            .iter()
+            .map(|planned| planned.file.id.clone())
+            .collect();
+        self.migration_status = match options.only.len() {
+            0 => "Migrating files...".to_string(),
+            count => format!("Migrating {} files...", count),
+        };
+
+        let api_key = self.pinata_api_key.clone();
+        self.jobs
+            .spawn(JobKind::Migrate, "Migrate from Pinata", async move {
+                migrate_files(&api_key, &options)
+                    .await
+                    .map(JobOutput::Migration)
+                    .map_err(|e| e.to_string())
+            });
+    }
+
+    pub fn start_epoch_extension(&mut self) {
+        self.extender_status = "Extending Epoch...".into();
+
+        let blob_id = self.extender_blob_id.clone();
+        let epochs = self.epochs;
+        let cache = self.walrus_client.cache.clone();
+        self.jobs.spawn(
+            JobKind::ExtendEpoch,
+            format!("Extend {} by {} epochs", blob_id, epochs),
+            async move {
+                extend_epoch(&cache, &blob_id, epochs)
+                    .await
+                    .map(|end_epoch| {
+                        JobOutput::Message(format!("Extended, now ends at epoch {}", end_epoch))
+                    })
+                    .map_err(|e| e.to_string())
+            },
+        );
+    }
+
+    pub fn refresh_blobs(&mut self) {
+        self.jobs
+            .spawn(JobKind::RefreshBlobs, "Refresh blob list", async move {
+                list_blobs(false)
+                    .await
+                    .map(JobOutput::Blobs)
+                    .map_err(|e| e.to_string())
+            });
+    }
+
+    /// Picks up config changes written by other processes, like the pinner or
+    /// another walter-ui, and refreshes everything derived from the registry.
+    pub fn process_config_changes(&mut self) {
+        let Some(config) = self.config_watcher.as_ref().and_then(ConfigWatcher::poll) else {
+            return;
+        };
+        if config.same_as(&self.walrus_client.config) {
+            return;
+        }
+
+        self.walrus_client.cache.max_size = config.get_cache_max_size();
+        self.walrus_client.config = config;
+        self.update_visible_blobs();
+        self.dashboard_status = "Config changed on disk, reloaded".into();
+    }
+
+    /// Rereads the config after a job registered files from inside it. A config
+    /// that can't be read keeps the current one, the watcher brings the change later.
+    fn reload_config(&mut self) {
+        match WalterConfig::try_load() {
+            Ok(config) => self.walrus_client.config = config,
+            Err(e) => self.dashboard_status = format!("Unable to read config file: {}", e),
+        }
+        self.update_visible_blobs();
+    }
+
+    /// Feeds the results of finished jobs back into the screens that started them.
+    pub fn process_jobs(&mut self) {
+        for (kind, result) in self.jobs.poll() {
+            self.finish_job(kind, result);
+        }
+    }
+
+    // Shows the result of a job in its view and refreshes the blob list
+    // after anything that may have changed it
+    fn finish_job(&mut self, kind: JobKind, result: Result<JobOutput, String>) {
+        let succeeded = result.is_ok();
+
+        match (kind, result) {
+            (JobKind::Upload, Ok(JobOutput::Message(blob_id))) => {
+                self.file_upload_status =
+                    format!("File uploaded successfully! Blob ID {}", blob_id);
+            }
+            (JobKind::Upload, Err(e)) => {
+                self.file_upload_status = format!("File upload Failed: {e}");
+            }
+            (JobKind::Shard, result) => {
+                self.sharder_status = match result {
+                    Ok(JobOutput::Message(message)) => message,
+                    Ok(_) => "Sharding succeeded".to_string(),
+                    Err(e) => format!("Sharding failed: {}", e),
+                };
+                // The client saved the new file entry to disk from inside the job
+                self.reload_config();
+            }
+            (JobKind::MigrationPlan, Ok(JobOutput::Plan(plan))) => {
+                let migrated = plan.files.iter().filter(|planned| planned.migrated).count();
+                self.migration_status = format!(
+                    "Listed {} files, {} already migrated",
+                    plan.files.len(),
+                    migrated
+                );
+                self.migration_plan = plan;
+                self.checked_migration_files.clear();
+                self.update_migration_estimates();
+                self.update_visible_migration_files();
+            }
+            (JobKind::MigrationPlan, Err(e)) => {
+                self.migration_status = format!("Listing failed: {}", e);
+            }
+            (JobKind::Migrate, Ok(output)) => {
+                self.migration_status = match output {
+                    JobOutput::Migration(report) => {
+                        for record in &report.migrated {
+                            self.checked_migration_files.remove(&record.id);
+                            for planned in &mut self.migration_plan.files {
+                                if planned.file.id == record.id {
+                                    planned.migrated = true;
+                                }
+                            }
+                        }
+                        report.summary()
+                    }
+                    _ => "Migration successful".to_string(),
+                };
+                self.reload_config();
+            }
+            (JobKind::Migrate, Err(e)) => {
+                self.migration_status = format!("Migration failed: {}", e);
+            }
+            (JobKind::ExtendEpoch, result) => {
+                self.extender_status = match result {
+                    Ok(JobOutput::Message(message)) => message,
+                    Ok(_) => "Extension succeeded".to_string(),
+                    Err(e) => format!("Extension failed: {}", e),
+                };
+            }
+            (JobKind::Download, Ok(JobOutput::Download(report))) => {
+                self.download_results = verify_download(&report, &self.user_blobs);
+                self.download_status = if self.download_results.iter().all(|r| r.passed) {
+                    "Download complete and verified".to_string()
+                } else {
+                    "Download complete, verification failed".to_string()
+                };
+            }
+            (JobKind::Download, Err(e)) => {
+                self.download_status = format!("Download failed: {}", e);
+            }
+            (JobKind::BlobAction | JobKind::BlobVerify, Ok(JobOutput::Message(message))) => {
+                self.dashboard_status = message;
+            }
+            (JobKind::BlobAction | JobKind::BlobVerify, Err(e)) => {
+                self.dashboard_status = format!("Failed: {}", e);
+            }
+            (JobKind::BlobStatus, Ok(JobOutput::Message(output))) => {
+                self.dashboard_status.clear();
+                self.action_output = Some((BlobAction::Status.title().to_string(), output));
+            }
+            (JobKind::BulkAction, Ok(JobOutput::Summary(items))) => {
+                let failed = items.iter().filter(|item| item.result.is_err()).count();
+                self.dashboard_status =
+                    format!("{} of {} succeeded", items.len() - failed, items.len());
+
+                let lines = items
+                    .iter()
+                    .map(|item| match &item.result {
+                        Ok(message) => format!("✓ {}: {}", item.name, message),
+                        Err(e) => format!("✗ {}: {}", item.name, e),
+                    })
+                    .collect::<Vec<String>>();
+                self.action_output = Some((self.dashboard_status.clone(), lines.join("\n")));
+            }
+            (JobKind::BulkAction, Err(e)) => {
+                self.dashboard_status = format!("Bulk action stopped: {}", e);
+                // The items done before it stopped may have changed the blobs
+                self.refresh_blobs();
+            }
+            (JobKind::BlobStatus, Err(e)) => {
+                self.dashboard_status = format!("Failed: {}", e);
+            }
+            (JobKind::WatchUpload, result) => {
+                self.watch_status = match result {
+                    Ok(JobOutput::Message(message)) => message,
+                    Ok(_) => "success".to_string(),
+                    Err(e) => e,
+                };
+                // The upload registered the file on disk from inside the job
+                self.reload_config();
+            }
+            (JobKind::RefreshBlobs, Ok(JobOutput::Blobs(user_blobs))) => {
+                self.user_blobs = user_blobs;
+                self.update_visible_blobs();
+            }
+            (JobKind::RefreshBlobs, Err(e)) => {
+                self.dashboard_status = format!("Refreshing the blob list failed: {}", e);
+            }
+            _ => {}
+        }
 
-        match result {
-            Ok(b) => "success".to_string(),
-            Err(e) => "failure".to_string(),
+        // Reads don't change what is stored, so there is nothing to refresh
+        if succeeded
+            && !matches!(
+                kind,
+                JobKind::RefreshBlobs
+                    | JobKind::Download
+                    | JobKind::BlobStatus
+                    | JobKind::BlobVerify
+                    | JobKind::MigrationPlan
+            )
+        {
+            self.refresh_blobs();
         }
     }
 }
@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use walter_core::blob_cache::BlobCache;
+use walter_core::client::{delete_blob, download_blob, verify_blob, WalrusClient};
+use walter_core::config::WalterConfig;
+use walter_core::epoch_extender::extend_epoch;
+use walter_core::types::BlobInfo;
+
+use crate::dashboard::{format_size, BlobAction, FileSummary};
+use crate::downloader::bulk_destination_path;
+use crate::jobs::{JobOutput, JobProgress};
+
+/// The rows a confirmed Dashboard action runs on.
+pub enum ActionTarget {
+    Blobs(Vec<BlobInfo>),
+    Files(Vec<FileSummary>),
+}
+
+impl ActionTarget {
+    pub fn len(&self) -> usize {
+        match self {
+            ActionTarget::Blobs(blobs) => blobs.len(),
+            ActionTarget::Files(files) => files.len(),
+        }
+    }
+}
+
+/// Outcome of a bulk operation for one blob or file.
+pub struct ItemResult {
+    pub name: String,
+    pub result: Result<String, String>,
+}
+
+/// Settings captured when a bulk operation is confirmed.
+pub struct BulkOptions {
+    pub epochs: u16,
+    pub destination: String,
+    pub password: Option<String>,
+    pub config: WalterConfig,
+    pub cache: BlobCache,
+}
+
+/// Runs `action` on a single blob and describes what happened. Downloads go
+/// into the destination directory under the blob ID.
+pub async fn run_blob_action(
+    action: BlobAction,
+    blob: &BlobInfo,
+    options: &BulkOptions,
+) -> Result<String, String> {
+    let blob_id = &blob.blob_id;
+
+    match action {
+        BlobAction::Extend => extend_epoch(&options.cache, blob_id, options.epochs)
+            .await
+            .map(|end_epoch| {
+                format!(
+                    "extended by {} epochs, now ends at epoch {}",
+                    options.epochs, end_epoch
+                )
+            })
+            .map_err(|e| e.to_string()),
+        BlobAction::Download => {
+            let out_path = bulk_destination_path(&options.destination, blob_id);
+            if let Some(parent) = out_path.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+
+            let out_path = out_path.to_string_lossy().to_string();
+            match download_blob(&options.cache, blob_id, &out_path).await {
+                Ok(true) => Ok(format!("saved to {}", out_path)),
+                Ok(false) => Err("failed to download blob".to_string()),
+                Err(e) => Err(e.to_string()),
+            }
+        }
+        BlobAction::Delete => delete_blob(&options.cache, blob_id)
+            .await
+            .map(|_| "deleted".to_string())
+            .map_err(|e| e.to_string()),
+        BlobAction::Verify => {
+            let size = verify_blob(blob_id).await.map_err(|e| e.to_string())?;
+            if size != blob.unencoded_size {
+                return Err(format!(
+                    "read back {} but {} is listed",
+                    format_size(size),
+                    format_size(blob.unencoded_size)
+                ));
+            }
+            Ok(format!("available ({})", format_size(size)))
+        }
+        BlobAction::Status | BlobAction::Open => {
+            Err(format!("{} only works on a single blob", action.title()))
+        }
+    }
+}
+
+async fn download_registered_file(
+    file: &FileSummary,
+    out_path: &Path,
+    options: &BulkOptions,
+) -> Result<String, String> {
+    let password = match (file.is_encrypted, &options.password) {
+        (false, _) => None,
+        (true, Some(password)) => Some(password.clone()),
+        (true, None) => return Err("encrypted, no password given".to_string()),
+    };
+
+    if let Some(parent) = out_path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    let out_path = out_path.to_string_lossy().to_string();
+    let client = WalrusClient::new(options.config.clone());
+    client
+        .download_file_to(&file.file_path, &out_path, password)
+        .await
+        .map(|_| format!("saved to {}", out_path))
+        .map_err(|e| e.to_string())
+}
+
+/// Runs `action` on every target one after another, reporting progress per
+/// item, and collects the outcome of each instead of stopping at the first error.
+pub async fn run_bulk(
+    action: BlobAction,
+    target: ActionTarget,
+    options: BulkOptions,
+    progress: JobProgress,
+) -> Result<JobOutput, String> {
+    let total = target.len();
+    let mut results = Vec::new();
+
+    match target {
+        ActionTarget::Blobs(blobs) => {
+            for (i, blob) in blobs.iter().enumerate() {
+                progress.report(format!("{}/{} {}", i + 1, total, blob.blob_id));
+                results.push(ItemResult {
+                    name: blob.blob_id.clone(),
+                    result: run_blob_action(action, blob, &options).await,
+                });
+            }
+        }
+        ActionTarget::Files(files) => {
+            // Registered paths that only differ in `..` or a leading `/` end up
+            // at the same place, the second one fails instead of overwriting
+            let mut saved: HashMap<_, &String> = HashMap::new();
+            for (i, file) in files.iter().enumerate() {
+                progress.report(format!("{}/{} {}", i + 1, total, file.file_path));
+                let out_path = bulk_destination_path(&options.destination, &file.file_path);
+                let result = match saved.get(&out_path) {
+                    Some(other) => Err(format!(
+                        "{} was already saved from {}",
+                        out_path.display(),
+                        other
+                    )),
+                    None => download_registered_file(file, &out_path, &options).await,
+                };
+                if result.is_ok() {
+                    saved.insert(out_path, &file.file_path);
+                }
+                results.push(ItemResult {
+                    name: file.file_path.clone(),
+                    result,
+                });
+            }
+        }
+    }
+
+    Ok(JobOutput::Summary(results))
+}
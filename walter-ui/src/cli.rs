@@ -0,0 +1,950 @@
+use clap::{ArgGroup, Args, Parser, Subcommand};
+use serde_json::json;
+use std::path::Path;
+use std::time::Duration;
+
+use walter_core::blob_cache::BlobCache;
+use walter_core::client::{
+    delete_blob, download_blob, list_blobs, verify_blob, walrus_info,
+    WalrusClient,
+};
+use walter_core::config::WalterConfig;
+use walter_core::epoch_extender::extend_epoch;
+use walter_core::folder_watcher::FolderWatcher;
+use walter_core::migrator::{
+    export_cid_mappings, migrate_from, plan_migration, ExportFormat, GatewaySource, KuboSource,
+    MigrationOptions, MigrationSource, PinataSource, S3Source, IPFS_GATEWAY_URL, KUBO_API_URL,
+    S3_ENDPOINT, S3_REGION,
+};
+use walter_core::renewal::{default_log_path, read_log, RenewalRecord, RenewalScheduler};
+use walter_core::types::BlobPage;
+use walter_core::updater;
+
+use crate::dashboard::system_summary;
+
+pub const EXIT_SUCCESS: i32 = 0;
+pub const EXIT_FAILURE: i32 = 1;
+pub const EXIT_NOT_FOUND: i32 = 3;
+pub const EXIT_VERIFY_FAILED: i32 = 4;
+
+#[derive(Parser)]
+#[command(
+    name = "walter-ui",
+    version,
+    about = "WalTerminalUI - A TUI Devtool keychain for Walrus"
+)]
+pub struct Cli {
+    /// Print machine-readable JSON instead of human-readable text
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Launch the terminal UI (default when no command is given)
+    #[command(long_flag = "tui", short_flag = 'c', long_flag_alias = "cli")]
+    Tui,
+    /// Setup Walter along with Walrus CLI, Walrus Site Builder and Sui Client
+    #[command(long_flag = "setup", short_flag = 's')]
+    Setup,
+    /// Update Walter and its dependencies
+    #[command(long_flag = "update", short_flag = 'u')]
+    Update,
+    /// Run Walrus SQLite shell with rollbacks
+    #[command(long_flag = "sqlite")]
+    Sqlite { blob_id: String },
+    /// Build Walrus site with the given app path
+    #[command(long_flag = "build-site")]
+    BuildSite { app_path: String },
+    /// Run the Walrus pinning HTTP service until stopped
+    #[command(long_flag = "run-pinner")]
+    RunPinner {
+        /// Address to listen on
+        #[arg(long, default_value = walter_pinner::DEFAULT_ADDR)]
+        addr: String,
+        /// Seconds between renewal checks
+        #[arg(long, default_value_t = 3600)]
+        interval: u64,
+        /// Log what would be renewed without extending anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Serve registered files over HTTP until stopped
+    Gateway {
+        /// Address to listen on
+        #[arg(long, default_value = walter_gateway::DEFAULT_ADDR)]
+        addr: String,
+        /// Password to decrypt encrypted files with, they are refused without one
+        #[arg(long, env = "WALTER_PASSWORD", hide_env_values = true)]
+        password: Option<String>,
+    },
+    /// Mount the registered files read-only until unmounted or stopped
+    #[cfg(target_os = "linux")]
+    Mount {
+        mountpoint: String,
+        /// Password to decrypt encrypted files with, they can't be opened without one
+        #[arg(long, env = "WALTER_PASSWORD", hide_env_values = true)]
+        password: Option<String>,
+    },
+    /// Shard, optionally encrypt, and upload a file
+    Upload {
+        path: String,
+        /// Number of epochs to store the file for
+        #[arg(short, long)]
+        epochs: Option<u16>,
+        /// Encrypt the file with this password
+        #[arg(long, env = "WALTER_PASSWORD", hide_env_values = true)]
+        password: Option<String>,
+    },
+    /// Download a registered file or a single blob
+    Download {
+        /// Registered file path or blob ID
+        target: String,
+        /// Where to write the file, defaults to the download directory
+        #[arg(short, long)]
+        out: Option<String>,
+        /// Decrypt the file with this password
+        #[arg(long, env = "WALTER_PASSWORD", hide_env_values = true)]
+        password: Option<String>,
+    },
+    /// List registered files, or the blobs owned by the active address
+    Ls {
+        #[arg(long)]
+        blobs: bool,
+        /// Include expired blobs
+        #[arg(long, requires = "blobs")]
+        expired: bool,
+        /// Page of blobs to show, starting at 0. Every blob is still listed
+        /// from Walrus, only the output is paged
+        #[arg(long, requires = "blobs")]
+        page: Option<usize>,
+        #[arg(long, default_value_t = 50, requires = "page")]
+        page_size: usize,
+    },
+    /// Show Walrus system information
+    Info,
+    /// Extend the storage of a blob
+    Extend {
+        blob_id: String,
+        #[arg(short, long)]
+        epochs: u16,
+    },
+    /// Check that every blob of a registered file, or a single blob, can be read back
+    Verify {
+        /// Registered file path or blob ID
+        target: String,
+    },
+    /// Migrate files from Pinata, an IPFS gateway or node, or an S3 bucket to Walrus
+    Migrate {
+        #[command(flatten)]
+        source: Box<SourceArgs>,
+        /// List the files with their estimated cost without migrating anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Only migrate these files, by CID or S3 key, can be repeated
+        #[arg(long)]
+        only: Vec<String>,
+        /// Number of epochs to store the files for
+        #[arg(short, long)]
+        epochs: Option<u16>,
+        /// Size of the shards the files are split into, in bytes
+        #[arg(long)]
+        shard_size: Option<usize>,
+        /// Encrypt the files with this password
+        #[arg(long, env = "WALTER_PASSWORD", hide_env_values = true)]
+        password: Option<String>,
+    },
+    /// Keep registered blobs alive, extending those within renew_epoch_threshold epochs of expiry
+    Daemon {
+        /// Log what would be renewed without extending anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Seconds between checks
+        #[arg(long, default_value_t = 3600)]
+        interval: u64,
+        /// Check once and exit
+        #[arg(long)]
+        once: bool,
+    },
+    /// Upload new files in the configured watch_folders once they stop changing
+    Watch {
+        /// Password for folders with encryption turned on
+        #[arg(long, env = "WALTER_PASSWORD", hide_env_values = true)]
+        password: Option<String>,
+    },
+    /// Show the most recent entries of the renewal log
+    Renewals {
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Remove a file from the registry
+    Rm {
+        path: String,
+        /// Also delete the file's blobs from Walrus (only works for deletable blobs)
+        #[arg(long)]
+        delete_blobs: bool,
+    },
+    /// Show or empty the local blob cache
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+    /// Export or look up the IPFS CIDs of migrated files and their Walrus blobs
+    Cids {
+        #[command(subcommand)]
+        action: CidAction,
+    },
+}
+
+/// Where `migrate` reads files from. Only one source can be given, --cid and
+/// --cid-list together count as one.
+#[derive(Args)]
+#[command(group(
+    ArgGroup::new("source")
+        .args(["jwt", "kubo", "s3_bucket"])
+        .multiple(false)
+))]
+#[command(group(
+    ArgGroup::new("cids")
+        .args(["cid", "cid_list"])
+        .multiple(true)
+        .conflicts_with("source")
+))]
+pub struct SourceArgs {
+    /// Pinata JWT, migrates every file pinned on Pinata. Read from PINATA_JWT
+    /// when no other source is given
+    #[arg(long)]
+    jwt: Option<String>,
+    /// CID to read through the IPFS gateway, can be repeated
+    #[arg(long)]
+    cid: Vec<String>,
+    /// File of CIDs to read through the IPFS gateway, one per line with an optional name
+    #[arg(long)]
+    cid_list: Option<String>,
+    /// IPFS gateway for --cid and --cid-list
+    #[arg(long, default_value = IPFS_GATEWAY_URL)]
+    gateway: String,
+    /// Kubo RPC API URL, migrates every file pinned on the node
+    #[arg(long, num_args = 0..=1, default_missing_value = KUBO_API_URL)]
+    kubo: Option<String>,
+    /// S3 bucket to migrate
+    #[arg(long)]
+    s3_bucket: Option<String>,
+    #[arg(long, default_value = S3_ENDPOINT)]
+    s3_endpoint: String,
+    #[arg(long, default_value = S3_REGION)]
+    s3_region: String,
+    /// Only migrate objects whose keys start with this prefix
+    #[arg(long, default_value = "")]
+    s3_prefix: String,
+    #[arg(long, env = "AWS_ACCESS_KEY_ID", hide_env_values = true)]
+    s3_access_key: Option<String>,
+    #[arg(long, env = "AWS_SECRET_ACCESS_KEY", hide_env_values = true)]
+    s3_secret_key: Option<String>,
+}
+
+#[derive(Subcommand)]
+pub enum CidAction {
+    /// Print or write every CID with its blobs, for rewriting links elsewhere
+    Export {
+        #[arg(long, default_value = "csv", value_parser = ["csv", "json"])]
+        format: String,
+        /// File to write to instead of printing
+        #[arg(short, long)]
+        out: Option<String>,
+    },
+    /// Show the blobs a CID was migrated to
+    Lookup { cid: String },
+}
+
+#[derive(Subcommand)]
+pub enum CacheAction {
+    /// Show how many blobs are cached, their size and the hit rate
+    Stats,
+    /// Remove every cached blob
+    Clear,
+}
+
+pub struct CliError {
+    pub code: i32,
+    pub message: String,
+    pub details: Option<serde_json::Value>,
+}
+
+impl CliError {
+    fn failure(message: impl ToString) -> Self {
+        CliError {
+            code: EXIT_FAILURE,
+            message: message.to_string(),
+            details: None,
+        }
+    }
+
+    fn not_found(message: impl ToString) -> Self {
+        CliError {
+            code: EXIT_NOT_FOUND,
+            message: message.to_string(),
+            details: None,
+        }
+    }
+}
+
+// Human-readable text and the equivalent JSON value for a command's result
+type CliOutput = (String, serde_json::Value);
+
+/// Runs a headless command and returns the process exit code.
+pub async fn run(command: Command, json: bool) -> i32 {
+    let result = match command {
+        Command::Tui => unreachable!("the TUI is started by main"),
+        Command::Setup => setup(),
+        Command::Update => {
+            updater::run();
+            Ok((String::new(), json!({})))
+        }
+        Command::Sqlite { blob_id } => walter_db::run(&blob_id)
+            .map(|_| (String::new(), json!({})))
+            .map_err(CliError::failure),
+        Command::BuildSite { app_path } => build_site(&app_path),
+        Command::RunPinner {
+            addr,
+            interval,
+            dry_run,
+        } => walter_pinner::serve(&addr, Duration::from_secs(interval), dry_run)
+            .await
+            .map(|_| (String::new(), json!({})))
+            .map_err(CliError::failure),
+        Command::Gateway { addr, password } => walter_gateway::serve(&addr, password)
+            .await
+            .map(|_| (String::new(), json!({})))
+            .map_err(CliError::failure),
+        #[cfg(target_os = "linux")]
+        Command::Mount {
+            mountpoint,
+            password,
+        } => walter_mount::mount(Path::new(&mountpoint), password)
+            .await
+            .map(|_| (String::new(), json!({})))
+            .map_err(CliError::failure),
+        Command::Upload {
+            path,
+            epochs,
+            password,
+        } => upload(&path, epochs, password).await,
+        Command::Download {
+            target,
+            out,
+            password,
+        } => download(&target, out, password).await,
+        Command::Ls {
+            blobs,
+            expired,
+            page,
+            page_size,
+        } => {
+            if blobs {
+                list_user_blobs(expired, page, page_size).await
+            } else {
+                list_files()
+            }
+        }
+        Command::Info => info().await,
+        Command::Extend { blob_id, epochs } => extend(&blob_id, epochs).await,
+        Command::Verify { target } => verify(&target).await,
+        Command::Migrate {
+            source,
+            dry_run,
+            only,
+            epochs,
+            shard_size,
+            password,
+        } => {
+            let mut options = MigrationOptions::from_config(&WalterConfig::load_config_file());
+            options.epochs = epochs.unwrap_or(options.epochs);
+            options.shard_size = shard_size.unwrap_or(options.shard_size);
+            options.password = password;
+            options.only = only;
+            migrate(*source, options, dry_run).await
+        }
+        Command::Rm { path, delete_blobs } => remove(&path, delete_blobs).await,
+        Command::Daemon {
+            dry_run,
+            interval,
+            once,
+        } => daemon(dry_run, interval, once, json).await,
+        Command::Renewals { limit } => renewals(limit),
+        Command::Watch { password } => watch(password, json).await,
+        Command::Cache { action } => cache(action),
+        Command::Cids { action } => cids(action),
+    };
+
+    match result {
+        Ok((text, value)) => {
+            if json {
+                println!("{}", value);
+            } else if !text.is_empty() {
+                println!("{}", text);
+            }
+            EXIT_SUCCESS
+        }
+        Err(e) => {
+            if json {
+                println!(
+                    "{}",
+                    json!({ "error": e.message, "code": e.code, "details": e.details })
+                );
+            } else {
+                eprintln!("\x1b[1;31mError:\x1b[0m {}", e.message);
+            }
+            e.code
+        }
+    }
+}
+
+fn setup() -> Result<CliOutput, CliError> {
+    std::env::set_current_dir("..").map_err(CliError::failure)?;
+    println!("Running setup...");
+    let output = std::process::Command::new("make")
+        .arg("all")
+        .output()
+        .map_err(CliError::failure)?;
+
+    if !output.status.success() {
+        return Err(CliError::failure(format!(
+            "Setup failed with error: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+
+    Ok((
+        "Setup completed successfully.".to_string(),
+        json!({ "setup": true }),
+    ))
+}
+
+fn build_site(app_path: &str) -> Result<CliOutput, CliError> {
+    let output = std::process::Command::new("site-builder")
+        .arg("publish")
+        .arg(app_path)
+        .output()
+        .map_err(CliError::failure)?;
+
+    if !output.status.success() {
+        return Err(CliError::failure(format!(
+            "Site build failed with error: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+
+    Ok((
+        "Site built successfully.".to_string(),
+        json!({ "built": true }),
+    ))
+}
+
+async fn upload(
+    path: &str,
+    epochs: Option<u16>,
+    password: Option<String>,
+) -> Result<CliOutput, CliError> {
+    if !Path::new(path).is_file() {
+        return Err(CliError::not_found(format!("{} does not exist", path)));
+    }
+
+    let mut client = WalrusClient::new(WalterConfig::load_config_file());
+    let epochs = epochs.unwrap_or(client.config.get_default_epochs());
+    let is_encrypted = password.is_some();
+
+    client
+        .upload_file_with_epochs(path, password, epochs)
+        .await
+        .map_err(CliError::failure)?;
+
+    let blobs = client.config.get_file_blobs(path).cloned().unwrap_or_default();
+    Ok((
+        format!("Uploaded {} as {} blob(s):\n{}", path, blobs.len(), blobs.join("\n")),
+        json!({
+            "file": path,
+            "epochs": epochs,
+            "encrypted": is_encrypted,
+            "blobs": blobs,
+        }),
+    ))
+}
+
+async fn download(
+    target: &str,
+    out: Option<String>,
+    password: Option<String>,
+) -> Result<CliOutput, CliError> {
+    let client = WalrusClient::new(WalterConfig::load_config_file());
+    let is_registered = client.config.get_file_blobs(target).is_some();
+
+    let out = match out {
+        Some(out) => out,
+        None => {
+            let download_dir = client.config.get_download_dir_path();
+            std::fs::create_dir_all(&download_dir).map_err(CliError::failure)?;
+
+            let file_name = Path::new(target)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or(target.to_string());
+            download_dir
+                .join(file_name)
+                .to_string_lossy()
+                .to_string()
+        }
+    };
+
+    if is_registered {
+        client
+            .download_file_to(target, &out, password)
+            .await
+            .map_err(CliError::failure)?;
+    } else {
+        download_blob(&client.cache, target, &out)
+            .await
+            .map_err(CliError::failure)?;
+    }
+
+    Ok((
+        format!("Downloaded {} to {}", target, out),
+        json!({ "target": target, "out": out, "registered": is_registered }),
+    ))
+}
+
+fn list_files() -> Result<CliOutput, CliError> {
+    let config = WalterConfig::load_config_file();
+    let mut files: Vec<_> = config.get_files().iter().collect();
+    files.sort_by(|a, b| a.0.cmp(b.0));
+
+    let text = files
+        .iter()
+        .map(|(path, info)| {
+            format!(
+                "{}\t{} shard(s){}",
+                path,
+                info.blobs.len(),
+                if info.is_encrypted { "\tencrypted" } else { "" }
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    let value = files
+        .iter()
+        .map(|(path, info)| {
+            json!({
+                "file": path,
+                "encrypted": info.is_encrypted,
+                "blobs": info.blobs,
+            })
+        })
+        .collect::<Vec<serde_json::Value>>();
+
+    Ok((text, json!(value)))
+}
+
+async fn list_user_blobs(
+    include_expired: bool,
+    page: Option<usize>,
+    page_size: usize,
+) -> Result<CliOutput, CliError> {
+    let blobs = list_blobs(include_expired)
+        .await
+        .map_err(CliError::failure)?;
+    let (blobs, value) = match page {
+        Some(page) => {
+            let blob_page = BlobPage::from_blobs(blobs, page, page_size);
+            (blob_page.blobs.clone(), json!(blob_page))
+        }
+        None => {
+            let value = json!(blobs);
+            (blobs, value)
+        }
+    };
+
+    let text = blobs
+        .iter()
+        .map(|blob| {
+            format!(
+                "{}\t{} bytes\texpires at epoch {}{}",
+                blob.blob_id,
+                blob.unencoded_size,
+                blob.expiration_epoch,
+                if blob.is_deletable { "\tdeletable" } else { "" }
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    Ok((text, value))
+}
+
+async fn info() -> Result<CliOutput, CliError> {
+    let info = walrus_info().await.map_err(CliError::failure)?;
+
+    Ok((system_summary(&info), json!(info)))
+}
+
+async fn extend(blob_id: &str, epochs: u16) -> Result<CliOutput, CliError> {
+    let end_epoch = extend_epoch(&BlobCache::open(), blob_id, epochs)
+        .await
+        .map_err(CliError::failure)?;
+
+    Ok((
+        format!(
+            "Extended {} by {} epochs, now ends at epoch {}",
+            blob_id, epochs, end_epoch
+        ),
+        json!({ "blob_id": blob_id, "epochs": epochs, "end_epoch": end_epoch }),
+    ))
+}
+
+async fn verify(target: &str) -> Result<CliOutput, CliError> {
+    let config = WalterConfig::load_config_file();
+    let blobs = match config.get_file_blobs(target) {
+        Some(blobs) => blobs.clone(),
+        None => vec![target.to_string()],
+    };
+
+    let mut results = Vec::new();
+    for blob_id in &blobs {
+        let result = verify_blob(blob_id).await.map_err(|e| e.to_string());
+        results.push((blob_id, result));
+    }
+
+    let failed = results.iter().filter(|(_, result)| result.is_err()).count();
+
+    let text = results
+        .iter()
+        .map(|(blob_id, result)| match result {
+            Ok(_) => format!("{}\tok", blob_id),
+            Err(e) => format!("{}\tfailed: {}", blob_id, e),
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    let value = json!({
+        "target": target,
+        "verified": failed == 0,
+        "blobs": results
+            .iter()
+            .map(|(blob_id, result)| json!({
+                "blob_id": blob_id,
+                "ok": result.is_ok(),
+                "error": result.as_ref().err(),
+            }))
+            .collect::<Vec<serde_json::Value>>(),
+    });
+
+    if failed > 0 {
+        let message = if results.len() == 1 {
+            text
+        } else {
+            format!("{} of {} blobs failed:\n{}", failed, results.len(), text)
+        };
+        return Err(CliError {
+            code: EXIT_VERIFY_FAILED,
+            message,
+            details: Some(value),
+        });
+    }
+
+    Ok((text, value))
+}
+
+fn describe_renewals(records: &[RenewalRecord]) -> String {
+    records
+        .iter()
+        .map(RenewalRecord::describe)
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+async fn daemon(
+    dry_run: bool,
+    interval: u64,
+    once: bool,
+    json: bool,
+) -> Result<CliOutput, CliError> {
+    let scheduler = RenewalScheduler::new(dry_run);
+
+    if once {
+        let config = WalterConfig::load_config_file();
+        let records = scheduler.run_once(&config).await.map_err(CliError::failure)?;
+        let text = match records.len() {
+            0 => "Nothing to renew".to_string(),
+            _ => describe_renewals(&records),
+        };
+        return Ok((text, json!(records)));
+    }
+
+    if !json {
+        println!(
+            "Checking registered blobs every {} seconds{}, logging to {}",
+            interval,
+            if dry_run { " (dry run)" } else { "" },
+            scheduler.log_path.display()
+        );
+    }
+
+    // Runs until the process is stopped, a failed check is retried on the next tick
+    scheduler
+        .run(Duration::from_secs(interval), |result| match result {
+            Ok(records) if json => {
+                for record in records {
+                    println!("{}", json!(record));
+                }
+            }
+            Ok(records) if !records.is_empty() => println!("{}", describe_renewals(&records)),
+            Ok(_) => {}
+            Err(e) if json => println!("{}", json!({ "error": e })),
+            Err(e) => eprintln!("\x1b[1;31mError:\x1b[0m {}", e),
+        })
+        .await;
+
+    Ok((String::new(), json!({})))
+}
+
+fn renewals(limit: usize) -> Result<CliOutput, CliError> {
+    let records = read_log(&default_log_path()).map_err(CliError::failure)?;
+    let records = &records[records.len().saturating_sub(limit)..];
+
+    Ok((describe_renewals(records), json!(records)))
+}
+
+fn cache(action: CacheAction) -> Result<CliOutput, CliError> {
+    let cache = BlobCache::open();
+
+    match action {
+        CacheAction::Stats => {
+            let stats = cache.stats();
+            Ok((stats.describe(), json!(stats)))
+        }
+        CacheAction::Clear => {
+            let stats = cache.clear().map_err(CliError::failure)?;
+            Ok((
+                format!(
+                    "Removed {} blobs ({:.1} MiB) from {}",
+                    stats.blobs,
+                    stats.size as f64 / (1024.0 * 1024.0),
+                    stats.dir
+                ),
+                json!(stats),
+            ))
+        }
+    }
+}
+
+fn cids(action: CidAction) -> Result<CliOutput, CliError> {
+    let config = WalterConfig::load_config_file();
+
+    match action {
+        CidAction::Export { format, out } => {
+            let mappings = config.get_cid_mappings();
+            let format = ExportFormat::from_name(&format)
+                .ok_or(CliError::failure(format!("Unknown export format {}", format)))?;
+            let document = export_cid_mappings(&mappings, format).map_err(CliError::failure)?;
+
+            let text = match out {
+                Some(out) => {
+                    std::fs::write(&out, document).map_err(CliError::failure)?;
+                    format!("Wrote {} CIDs to {}", mappings.len(), out)
+                }
+                None => document.trim_end().to_string(),
+            };
+            Ok((text, json!(mappings)))
+        }
+        CidAction::Lookup { cid } => {
+            let mapping = config
+                .blob_for_cid(&cid)
+                .ok_or(CliError::not_found(format!("{} was not migrated", cid)))?;
+            let mut text = format!(
+                "{} -> {} ({})",
+                mapping.cid,
+                mapping.blobs.join(", "),
+                mapping.file_path
+            );
+            if let Some(url) = &mapping.url {
+                text.push_str(&format!("\n{}", url));
+            }
+            Ok((text, json!(mapping)))
+        }
+    }
+}
+
+async fn migrate_with(
+    source: impl MigrationSource + Sync,
+    options: MigrationOptions,
+    dry_run: bool,
+) -> Result<CliOutput, CliError> {
+    if dry_run {
+        let plan = plan_migration(&source, &options)
+            .await
+            .map_err(CliError::failure)?;
+        let (total_cost, unknown) = plan.total_cost();
+        return Ok((
+            plan.describe(),
+            json!({
+                "source": plan.source,
+                "files": plan.files,
+                "total_cost": total_cost,
+                "unknown_cost": unknown,
+            }),
+        ));
+    }
+
+    let report = migrate_from(&source, &options)
+        .await
+        .map_err(CliError::failure)?;
+    let text = report.describe();
+    let value = json!({
+        "migrated": report.migrated,
+        "skipped": report.skipped,
+        "failed": report.failed,
+        "blobs_by_cid": report.blobs_by_cid(),
+    });
+
+    if !report.failed.is_empty() {
+        return Err(CliError {
+            code: EXIT_FAILURE,
+            message: text,
+            details: Some(value),
+        });
+    }
+    Ok((text, value))
+}
+
+async fn migrate(
+    args: SourceArgs,
+    options: MigrationOptions,
+    dry_run: bool,
+) -> Result<CliOutput, CliError> {
+    if let Some(bucket) = &args.s3_bucket {
+        let mut source =
+            S3Source::new(&args.s3_endpoint, bucket, &args.s3_region).with_prefix(&args.s3_prefix);
+        if let (Some(access_key), Some(secret_key)) = (&args.s3_access_key, &args.s3_secret_key) {
+            source = source.with_credentials(access_key, secret_key);
+        }
+        return migrate_with(source, options, dry_run).await;
+    }
+
+    if let Some(api_url) = &args.kubo {
+        return migrate_with(KuboSource::new(api_url), options, dry_run).await;
+    }
+
+    if args.cid_list.is_some() || !args.cid.is_empty() {
+        let mut cid_list = match &args.cid_list {
+            Some(path) => std::fs::read_to_string(path)
+                .map_err(|e| CliError::not_found(format!("{}: {}", path, e)))?,
+            None => String::new(),
+        };
+        for cid in &args.cid {
+            cid_list.push('\n');
+            cid_list.push_str(cid);
+        }
+        let source = GatewaySource::from_cid_list(&args.gateway, &cid_list);
+        return migrate_with(source, options, dry_run).await;
+    }
+
+    // Not a clap `env`, a JWT kept in the environment would conflict with every other source
+    let jwt = args.jwt.or_else(|| std::env::var("PINATA_JWT").ok());
+    match &jwt {
+        Some(jwt) => migrate_with(PinataSource::new(jwt), options, dry_run).await,
+        None => Err(CliError::failure(
+            "Nothing to migrate, give one of --jwt, --cid, --cid-list, --kubo or --s3-bucket",
+        )),
+    }
+}
+
+async fn watch(password: Option<String>, json: bool) -> Result<CliOutput, CliError> {
+    let config = WalterConfig::load_config_file();
+    let folders = config.get_watch_folders().clone();
+
+    if password.is_none() {
+        if let Some(folder) = folders.iter().find(|folder| folder.encrypt) {
+            return Err(CliError::failure(format!(
+                "{} is encrypted, pass --password or set WALTER_PASSWORD",
+                folder.path
+            )));
+        }
+    }
+
+    let mut watcher = FolderWatcher::new(folders).map_err(CliError::failure)?;
+
+    if !json {
+        for folder in watcher.folders() {
+            println!(
+                "Watching {} ({} epochs{}, settles after {}s)",
+                folder.path,
+                folder.get_epochs(&config),
+                if folder.encrypt { ", encrypted" } else { "" },
+                folder.settle_secs
+            );
+        }
+    }
+
+    // Runs until the process is stopped, a failed upload is reported and not retried
+    watcher
+        .run(password, |upload| match &upload.error {
+            _ if json => println!("{}", json!(upload)),
+            None => println!("{}", upload.describe()),
+            Some(_) => eprintln!("\x1b[1;31mError:\x1b[0m {}", upload.describe()),
+        })
+        .await;
+
+    Ok((String::new(), json!({})))
+}
+
+async fn remove(path: &str, delete_blobs: bool) -> Result<CliOutput, CliError> {
+    let mut config = WalterConfig::load_config_file();
+    let file_info = config
+        .remove_file(path)
+        .ok_or(CliError::not_found(format!("{} is not a registered file", path)))?;
+
+    // The entry goes first, so a failed delete never leaves it pointing at deleted blobs
+    config.save_config_file();
+
+    // Blobs that couldn't be deleted, with the error, left for the user to clean up
+    let mut leftover_blobs: Vec<(&String, String)> = Vec::new();
+    if delete_blobs {
+        let cache = BlobCache::open();
+        for blob_id in &file_info.blobs {
+            if let Err(e) = delete_blob(&cache, blob_id).await {
+                leftover_blobs.push((blob_id, e.to_string()));
+            }
+        }
+    }
+
+    let value = json!({
+        "file": path,
+        "blobs": file_info.blobs,
+        "deleted_blobs": delete_blobs,
+        "leftover_blobs": leftover_blobs
+            .iter()
+            .map(|(blob_id, e)| json!({ "blob_id": blob_id, "error": e }))
+            .collect::<Vec<_>>(),
+    });
+    if !leftover_blobs.is_empty() {
+        let lines: Vec<String> = leftover_blobs
+            .iter()
+            .map(|(blob_id, e)| format!("  {}: {}", blob_id, e))
+            .collect();
+        return Err(CliError {
+            code: EXIT_FAILURE,
+            message: format!(
+                "Removed {}, but {} of its blobs were not deleted:\n{}",
+                path,
+                leftover_blobs.len(),
+                lines.join("\n")
+            ),
+            details: Some(value),
+        });
+    }
+
+    Ok((format!("Removed {}", path), value))
+}
@@ -0,0 +1,434 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use walter_core::config::FileInfo;
+use walter_core::types::{BlobInfo, WalrusInfo};
+
+// Presets cycled through by the "expiring within N epochs" filter
+const EXPIRY_FILTER_STEPS: [u64; 4] = [1, 2, 5, 10];
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum DashboardView {
+    Blobs,
+    Files,
+}
+
+/// One row of the grouped Files view: a registered file with its shards collapsed.
+#[derive(Clone)]
+pub struct FileSummary {
+    pub file_path: String,
+    pub shard_count: usize,
+    pub live_shards: usize,
+    pub is_encrypted: bool,
+    pub total_size: u64,
+    pub earliest_expiry: Option<u64>,
+}
+
+/// Joins the file registry with the listed blobs, one summary per registered file.
+pub fn summarize_files(files: &HashMap<String, FileInfo>, blobs: &[BlobInfo]) -> Vec<FileSummary> {
+    // A blob can be stored by several objects, the one expiring last is what counts
+    let mut blobs_by_id: HashMap<&str, &BlobInfo> = HashMap::new();
+    for blob in blobs {
+        let entry = blobs_by_id.entry(blob.blob_id.as_str()).or_insert(blob);
+        if blob.expiration_epoch > entry.expiration_epoch {
+            *entry = blob;
+        }
+    }
+
+    let mut summaries: Vec<FileSummary> = files
+        .iter()
+        .map(|(file_path, file_info)| {
+            let live: Vec<&BlobInfo> = file_info
+                .blobs
+                .iter()
+                .filter_map(|blob_id| blobs_by_id.get(blob_id.as_str()).copied())
+                .collect();
+
+            FileSummary {
+                file_path: file_path.clone(),
+                shard_count: file_info.blobs.len(),
+                live_shards: live.len(),
+                is_encrypted: file_info.is_encrypted,
+                total_size: live.iter().map(|blob| blob.unencoded_size).sum(),
+                earliest_expiry: live.iter().map(|blob| blob.expiration_epoch).min(),
+            }
+        })
+        .collect();
+
+    summaries.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+    summaries
+}
+
+/// Operations that can be run on the selected Dashboard blob, each confirmed first.
+#[derive(Clone, Copy, PartialEq)]
+pub enum BlobAction {
+    Extend,
+    Download,
+    Delete,
+    Verify,
+    Status,
+    Open,
+}
+
+impl BlobAction {
+    pub fn title(&self) -> &'static str {
+        match self {
+            BlobAction::Extend => "Extend Blob",
+            BlobAction::Download => "Download Blob",
+            BlobAction::Delete => "Delete Blob",
+            BlobAction::Verify => "Verify Blob",
+            BlobAction::Status => "Blob Status",
+            BlobAction::Open => "Open in Aggregator",
+        }
+    }
+}
+
+/// How close a blob is to expiring, relative to the renewal threshold.
+#[derive(Clone, Copy, PartialEq)]
+pub enum ExpiryLevel {
+    Expired,
+    Critical,
+    Warning,
+    Healthy,
+}
+
+/// Critical within `threshold` epochs of expiry, a warning within twice that.
+pub fn expiry_level(expiration_epoch: u64, info: &WalrusInfo, threshold: u64) -> ExpiryLevel {
+    match info.epochs_until(expiration_epoch) {
+        0 => ExpiryLevel::Expired,
+        epochs if epochs <= threshold => ExpiryLevel::Critical,
+        epochs if epochs <= threshold * 2 => ExpiryLevel::Warning,
+        _ => ExpiryLevel::Healthy,
+    }
+}
+
+/// "expires in N epochs (~X days)", or just the epoch when the system info is unknown.
+pub fn describe_expiry(expiration_epoch: u64, info: Option<&WalrusInfo>) -> String {
+    let Some(info) = info else {
+        return format!("epoch {}", expiration_epoch);
+    };
+
+    match info.epochs_until(expiration_epoch) {
+        0 => format!("expired at epoch {}", expiration_epoch),
+        epochs => format!(
+            "expires in {} epochs (~{})",
+            epochs,
+            format_days(info.epochs_to_days(epochs))
+        ),
+    }
+}
+
+fn format_days(days: f64) -> String {
+    if days < 1.0 {
+        format!("{:.0} hours", days * 24.0)
+    } else if days < 10.0 {
+        format!("{:.1} days", days)
+    } else {
+        format!("{:.0} days", days)
+    }
+}
+
+/// Multi-line overview of the system parameters for the System Info panel.
+pub fn system_summary(info: &WalrusInfo) -> String {
+    [
+        format!("Current epoch: {}", info.current_epoch),
+        format!(
+            "Epoch duration: {}",
+            format_days(info.epoch_duration_secs as f64 / 86400.0)
+        ),
+        format!("Max epochs ahead: {}", info.max_epochs_ahead),
+        format!("Shards: {}", info.n_shards),
+        format!("Max blob size: {}", format_size(info.max_blob_size)),
+        format!(
+            "Storage price: {} FROST per {} per epoch",
+            info.storage_price_per_unit_size,
+            format_size(info.storage_unit_size)
+        ),
+        format!(
+            "Write price: {} FROST per {}",
+            info.write_price_per_unit_size,
+            format_size(info.storage_unit_size)
+        ),
+    ]
+    .join("\n")
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SortColumn {
+    Size,
+    ExpiryEpoch,
+    Certified,
+    Deletable,
+}
+
+impl SortColumn {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SortColumn::Size => "Size",
+            SortColumn::ExpiryEpoch => "Expiry epoch",
+            SortColumn::Certified => "Certified",
+            SortColumn::Deletable => "Deletable",
+        }
+    }
+}
+
+/// Sorting, search and filter settings for the Dashboard blob table.
+pub struct BlobFilter {
+    pub sort_column: Option<SortColumn>,
+    pub sort_ascending: bool,
+    pub search: String,
+    pub expiring_within: Option<u64>,
+}
+
+impl BlobFilter {
+    pub fn new() -> Self {
+        BlobFilter {
+            sort_column: None,
+            sort_ascending: true,
+            search: String::new(),
+            expiring_within: None,
+        }
+    }
+
+    pub fn next_sort_column(&mut self) {
+        self.sort_column = match self.sort_column {
+            None => Some(SortColumn::Size),
+            Some(SortColumn::Size) => Some(SortColumn::ExpiryEpoch),
+            Some(SortColumn::ExpiryEpoch) => Some(SortColumn::Certified),
+            Some(SortColumn::Certified) => Some(SortColumn::Deletable),
+            Some(SortColumn::Deletable) => None,
+        };
+    }
+
+    pub fn next_expiry_filter(&mut self) {
+        self.expiring_within = match self.expiring_within {
+            None => Some(EXPIRY_FILTER_STEPS[0]),
+            Some(epochs) => EXPIRY_FILTER_STEPS
+                .iter()
+                .find(|&&step| step > epochs)
+                .copied(),
+        };
+    }
+
+    pub fn clear(&mut self) {
+        *self = BlobFilter::new();
+    }
+
+    /// Returns the indices of the blobs to show, in display order.
+    pub fn apply(&self, blobs: &[BlobInfo], current_epoch: Option<u64>) -> Vec<usize> {
+        let search = self.search.to_lowercase();
+
+        let mut visible: Vec<usize> = blobs
+            .iter()
+            .enumerate()
+            .filter(|(_, blob)| {
+                search.is_empty()
+                    || blob.blob_id.to_lowercase().contains(&search)
+                    || blob.object_id.to_lowercase().contains(&search)
+            })
+            .filter(|(_, blob)| match (self.expiring_within, current_epoch) {
+                (Some(epochs), Some(current_epoch)) => {
+                    blob.expiration_epoch <= current_epoch + epochs
+                }
+                _ => true,
+            })
+            .map(|(i, _)| i)
+            .collect();
+
+        if let Some(column) = self.sort_column {
+            visible.sort_by(|&a, &b| {
+                let ordering = compare_blobs(&blobs[a], &blobs[b], column);
+                if self.sort_ascending {
+                    ordering
+                } else {
+                    ordering.reverse()
+                }
+            });
+        }
+
+        visible
+    }
+
+    /// Short description of the active settings, shown under the blob table.
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+
+        if let Some(column) = self.sort_column {
+            parts.push(format!(
+                "Sort: {} {}",
+                column.label(),
+                if self.sort_ascending { "↑" } else { "↓" }
+            ));
+        }
+        if !self.search.is_empty() {
+            parts.push(format!("Search: {}", self.search));
+        }
+        if let Some(epochs) = self.expiring_within {
+            parts.push(format!("Expiring within {} epochs", epochs));
+        }
+
+        parts.join(" | ")
+    }
+}
+
+fn compare_blobs(a: &BlobInfo, b: &BlobInfo, column: SortColumn) -> Ordering {
+    match column {
+        SortColumn::Size => a.unencoded_size.cmp(&b.unencoded_size),
+        SortColumn::ExpiryEpoch => a.expiration_epoch.cmp(&b.expiration_epoch),
+        SortColumn::Certified => a.is_certified.cmp(&b.is_certified),
+        SortColumn::Deletable => a.is_deletable.cmp(&b.is_deletable),
+    }
+}
+
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.2} {}", size, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use walter_core::test_util::{blob, test_config, walrus_info};
+
+    fn blobs() -> Vec<BlobInfo> {
+        vec![
+            BlobInfo {
+                unencoded_size: 30,
+                ..blob("Alpha", 12)
+            },
+            BlobInfo {
+                unencoded_size: 10,
+                is_certified: false,
+                ..blob("beta", 20)
+            },
+            BlobInfo {
+                unencoded_size: 20,
+                is_deletable: true,
+                ..blob("gamma", 11)
+            },
+        ]
+    }
+
+    #[test]
+    fn test_blob_filter_sort() {
+        let blobs = blobs();
+        let mut filter = BlobFilter::new();
+        assert_eq!(filter.apply(&blobs, None), vec![0, 1, 2]);
+
+        filter.next_sort_column();
+        assert_eq!(filter.apply(&blobs, None), vec![1, 2, 0]);
+        filter.sort_ascending = false;
+        assert_eq!(filter.apply(&blobs, None), vec![0, 2, 1]);
+        filter.sort_ascending = true;
+
+        filter.next_sort_column();
+        assert_eq!(filter.apply(&blobs, None), vec![2, 0, 1]);
+
+        // Ties keep the listing order
+        filter.next_sort_column();
+        assert_eq!(filter.apply(&blobs, None), vec![1, 0, 2]);
+        filter.next_sort_column();
+        assert_eq!(filter.apply(&blobs, None), vec![0, 1, 2]);
+
+        filter.next_sort_column();
+        assert!(filter.sort_column.is_none());
+    }
+
+    #[test]
+    fn test_blob_filter_search_and_expiry() {
+        let blobs = blobs();
+        let mut filter = BlobFilter::new();
+
+        // Blob and object IDs are searched regardless of case
+        filter.search = "ALPHA".to_string();
+        assert_eq!(filter.apply(&blobs, None), vec![0]);
+        filter.search = "0xbeta".to_string();
+        assert_eq!(filter.apply(&blobs, None), vec![1]);
+        filter.search = "nothing".to_string();
+        assert!(filter.apply(&blobs, None).is_empty());
+        filter.clear();
+
+        let steps: Vec<Option<u64>> = (0..5)
+            .map(|_| {
+                filter.next_expiry_filter();
+                filter.expiring_within
+            })
+            .collect();
+        assert_eq!(steps, vec![Some(1), Some(2), Some(5), Some(10), None]);
+
+        filter.expiring_within = Some(2);
+        assert_eq!(filter.apply(&blobs, Some(10)), vec![0, 2]);
+        // Without the current epoch nothing is filtered out
+        assert_eq!(filter.apply(&blobs, None), vec![0, 1, 2]);
+
+        filter.search = "a".to_string();
+        filter.sort_column = Some(SortColumn::Size);
+        filter.sort_ascending = false;
+        assert_eq!(
+            filter.summary(),
+            "Sort: Size ↓ | Search: a | Expiring within 2 epochs"
+        );
+        filter.clear();
+        assert_eq!(filter.summary(), "");
+    }
+
+    #[test]
+    fn test_summarize_files() {
+        let mut config = test_config();
+        config.add_file(
+            "b.txt",
+            true,
+            vec!["s1".to_string(), "s2".to_string(), "gone".to_string()],
+        );
+        config.add_file("a.txt", false, vec!["gone".to_string()]);
+        let blobs = vec![blob("s1", 15), blob("s1", 18), blob("s2", 12)];
+
+        let summaries = summarize_files(config.get_files(), &blobs);
+        assert_eq!(summaries.len(), 2);
+
+        let a = &summaries[0];
+        assert_eq!(a.file_path, "a.txt");
+        assert_eq!((a.shard_count, a.live_shards, a.total_size), (1, 0, 0));
+        assert_eq!(a.earliest_expiry, None);
+
+        // s1 is counted once, with the object expiring last
+        let b = &summaries[1];
+        assert_eq!(b.file_path, "b.txt");
+        assert!(b.is_encrypted);
+        assert_eq!((b.shard_count, b.live_shards, b.total_size), (3, 2, 20));
+        assert_eq!(b.earliest_expiry, Some(12));
+    }
+
+    #[test]
+    fn test_expiry() {
+        let info = walrus_info();
+
+        assert!(expiry_level(10, &info, 2) == ExpiryLevel::Expired);
+        assert!(expiry_level(12, &info, 2) == ExpiryLevel::Critical);
+        assert!(expiry_level(14, &info, 2) == ExpiryLevel::Warning);
+        assert!(expiry_level(15, &info, 2) == ExpiryLevel::Healthy);
+
+        assert_eq!(describe_expiry(9, Some(&info)), "expired at epoch 9");
+        assert_eq!(
+            describe_expiry(11, Some(&info)),
+            "expires in 1 epochs (~1.0 days)"
+        );
+        assert_eq!(
+            describe_expiry(30, Some(&info)),
+            "expires in 20 epochs (~20 days)"
+        );
+        assert_eq!(describe_expiry(30, None), "epoch 30");
+    }
+}
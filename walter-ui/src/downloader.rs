@@ -0,0 +1,134 @@
+use std::path::{Component, Path, PathBuf};
+
+use walter_core::types::{BlobInfo, DownloadReport};
+
+use crate::dashboard::format_size;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum DownloadFocus {
+    Files,
+    BlobId,
+    Destination,
+}
+
+impl DownloadFocus {
+    pub fn next(self) -> Self {
+        match self {
+            DownloadFocus::Files => DownloadFocus::BlobId,
+            DownloadFocus::BlobId => DownloadFocus::Destination,
+            DownloadFocus::Destination => DownloadFocus::Files,
+        }
+    }
+}
+
+/// Where downloading `source` writes to: inside `destination` when it names a
+/// directory, otherwise `destination` itself.
+pub fn destination_path(destination: &str, source: &str) -> PathBuf {
+    let path = PathBuf::from(destination);
+
+    if destination.is_empty() || destination.ends_with('/') || path.is_dir() {
+        let file_name = Path::new(source)
+            .file_name()
+            .map(|name| name.to_os_string())
+            .unwrap_or(source.into());
+        path.join(file_name)
+    } else {
+        path
+    }
+}
+
+/// Where a bulk download writes `source`: always inside `destination`, under
+/// the registered path made relative, so files with the same name in
+/// different directories don't overwrite each other.
+pub fn bulk_destination_path(destination: &str, source: &str) -> PathBuf {
+    // Only plain names are kept, a `..` in a registered path can't leave `destination`
+    let relative: PathBuf = Path::new(source)
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name),
+            _ => None,
+        })
+        .collect();
+    PathBuf::from(destination).join(relative)
+}
+
+pub struct VerificationResult {
+    pub passed: bool,
+    pub message: String,
+}
+
+/// Checks each downloaded shard against the size Walrus lists for its blob.
+pub fn verify_download(report: &DownloadReport, blobs: &[BlobInfo]) -> Vec<VerificationResult> {
+    let shard_count = report.shards.len();
+    let mut results: Vec<VerificationResult> = report
+        .shards
+        .iter()
+        .enumerate()
+        .map(|(i, shard)| {
+            let prefix = format!("Shard {}/{}", i + 1, shard_count);
+
+            match blobs.iter().find(|blob| blob.blob_id == shard.blob_id) {
+                Some(blob) if blob.unencoded_size == shard.size => VerificationResult {
+                    passed: true,
+                    message: format!("{}: {} matches the listed size", prefix, format_size(shard.size)),
+                },
+                Some(blob) => VerificationResult {
+                    passed: false,
+                    message: format!(
+                        "{}: read {} but {} is listed",
+                        prefix,
+                        format_size(shard.size),
+                        format_size(blob.unencoded_size)
+                    ),
+                },
+                None => VerificationResult {
+                    passed: true,
+                    message: format!(
+                        "{}: read {}, not owned by this address so the size is unchecked",
+                        prefix,
+                        format_size(shard.size)
+                    ),
+                },
+            }
+        })
+        .collect();
+
+    if report.decrypted {
+        results.push(VerificationResult {
+            passed: true,
+            message: "Decrypted with the given password".to_string(),
+        });
+    }
+
+    results.push(VerificationResult {
+        passed: results.iter().all(|result| result.passed),
+        message: format!("Saved {} to {}", format_size(report.size), report.out_path),
+    });
+
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bulk_destination_path() {
+        assert_eq!(
+            bulk_destination_path("out", "/a/db.sqlite"),
+            PathBuf::from("out/a/db.sqlite")
+        );
+        assert_eq!(
+            bulk_destination_path("out", "/b/db.sqlite"),
+            PathBuf::from("out/b/db.sqlite")
+        );
+        assert_eq!(
+            bulk_destination_path("out/", "notes.txt"),
+            PathBuf::from("out/notes.txt")
+        );
+        assert_eq!(
+            bulk_destination_path("out", "../../etc/passwd"),
+            PathBuf::from("out/etc/passwd")
+        );
+    }
+}
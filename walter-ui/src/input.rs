@@ -0,0 +1,395 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+// Oldest entries are dropped once the history grows past this
+const MAX_HISTORY: usize = 50;
+
+/// Single line text field with a cursor, word editing, paste and history.
+pub struct TextInput {
+    value: String,
+    // Cursor position counted in chars, not bytes
+    cursor: usize,
+    history: Vec<String>,
+    history_index: Option<usize>,
+    pub masked: bool,
+}
+
+impl TextInput {
+    pub fn new() -> Self {
+        TextInput {
+            value: String::new(),
+            cursor: 0,
+            history: Vec::new(),
+            history_index: None,
+            masked: false,
+        }
+    }
+
+    pub fn masked() -> Self {
+        TextInput {
+            masked: true,
+            ..TextInput::new()
+        }
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.value.is_empty()
+    }
+
+    pub fn set_value(&mut self, value: &str) {
+        self.value = value.to_string();
+        self.cursor = self.value.chars().count();
+        self.history_index = None;
+    }
+
+    pub fn clear(&mut self) {
+        self.set_value("");
+    }
+
+    /// Text to show on screen, passwords are replaced by bullets.
+    pub fn display_value(&self) -> String {
+        if self.masked {
+            "•".repeat(self.value.chars().count())
+        } else {
+            self.value.clone()
+        }
+    }
+
+    /// Remembers the current value so it can be recalled with Up/Down.
+    pub fn commit_history(&mut self) {
+        if self.masked || self.value.is_empty() {
+            return;
+        }
+
+        self.history.retain(|entry| entry != &self.value);
+        self.history.push(self.value.clone());
+        if self.history.len() > MAX_HISTORY {
+            self.history.remove(0);
+        }
+        self.history_index = None;
+    }
+
+    pub fn insert_str(&mut self, text: &str) {
+        // Pasted text can contain newlines, which a single line field can't hold
+        let text: String = text.chars().filter(|c| !c.is_control()).collect();
+
+        let index = self.byte_index(self.cursor);
+        self.value.insert_str(index, &text);
+        self.cursor += text.chars().count();
+    }
+
+    fn insert_char(&mut self, value: char) {
+        let index = self.byte_index(self.cursor);
+        self.value.insert(index, value);
+        self.cursor += 1;
+    }
+
+    fn byte_index(&self, char_index: usize) -> usize {
+        self.value
+            .char_indices()
+            .nth(char_index)
+            .map(|(i, _)| i)
+            .unwrap_or(self.value.len())
+    }
+
+    fn delete_range(&mut self, start: usize, end: usize) {
+        let (start_byte, end_byte) = (self.byte_index(start), self.byte_index(end));
+        self.value.replace_range(start_byte..end_byte, "");
+        self.cursor = start;
+    }
+
+    // Start of the word before the cursor, skipping any separators right before it
+    fn prev_word_start(&self) -> usize {
+        let chars: Vec<char> = self.value.chars().collect();
+        let mut i = self.cursor;
+
+        while i > 0 && is_word_separator(chars[i - 1]) {
+            i -= 1;
+        }
+        while i > 0 && !is_word_separator(chars[i - 1]) {
+            i -= 1;
+        }
+        i
+    }
+
+    fn next_word_end(&self) -> usize {
+        let chars: Vec<char> = self.value.chars().collect();
+        let mut i = self.cursor;
+
+        while i < chars.len() && is_word_separator(chars[i]) {
+            i += 1;
+        }
+        while i < chars.len() && !is_word_separator(chars[i]) {
+            i += 1;
+        }
+        i
+    }
+
+    fn recall_history(&mut self, older: bool) {
+        if self.history.is_empty() {
+            return;
+        }
+
+        let index = match (self.history_index, older) {
+            (None, true) => Some(self.history.len() - 1),
+            (None, false) => None,
+            (Some(0), true) => Some(0),
+            (Some(i), true) => Some(i - 1),
+            (Some(i), false) if i + 1 < self.history.len() => Some(i + 1),
+            (Some(_), false) => None,
+        };
+
+        let value = match index {
+            Some(i) => self.history[i].clone(),
+            None => String::new(),
+        };
+        self.set_value(&value);
+        self.history_index = index;
+    }
+
+    /// Applies an editing key and returns whether the value changed.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        let len = self.value.chars().count();
+        let before = self.value.clone();
+
+        match key.code {
+            KeyCode::Char('w') if ctrl => self.delete_range(self.prev_word_start(), self.cursor),
+            KeyCode::Char('u') if ctrl => self.delete_range(0, self.cursor),
+            KeyCode::Char('k') if ctrl => self.delete_range(self.cursor, len),
+            KeyCode::Char('a') if ctrl => self.cursor = 0,
+            KeyCode::Char('e') if ctrl => self.cursor = len,
+            KeyCode::Char(value) if !ctrl && !alt => self.insert_char(value),
+            KeyCode::Backspace if ctrl || alt => {
+                self.delete_range(self.prev_word_start(), self.cursor)
+            }
+            KeyCode::Backspace if self.cursor > 0 => self.delete_range(self.cursor - 1, self.cursor),
+            KeyCode::Delete if self.cursor < len => self.delete_range(self.cursor, self.cursor + 1),
+            KeyCode::Left if ctrl || alt => self.cursor = self.prev_word_start(),
+            KeyCode::Right if ctrl || alt => self.cursor = self.next_word_end(),
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(len),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = len,
+            KeyCode::Up if !self.masked => self.recall_history(true),
+            KeyCode::Down if !self.masked => self.recall_history(false),
+            _ => {}
+        }
+
+        self.value != before
+    }
+}
+
+fn is_word_separator(value: char) -> bool {
+    value.is_whitespace() || value == '/' || value == '.' || value == '-' || value == '_'
+}
+
+pub enum PromptResult {
+    Pending,
+    Cancelled,
+    Submitted(String),
+}
+
+/// Masked password entry with a confirmation field.
+pub struct PasswordPrompt {
+    pub title: &'static str,
+    pub password: TextInput,
+    pub confirmation: TextInput,
+    pub confirming: bool,
+    pub require_confirmation: bool,
+    pub error: Option<String>,
+}
+
+impl PasswordPrompt {
+    pub fn new(title: &'static str, require_confirmation: bool) -> Self {
+        PasswordPrompt {
+            title,
+            password: TextInput::masked(),
+            confirmation: TextInput::masked(),
+            confirming: false,
+            require_confirmation,
+            error: None,
+        }
+    }
+
+    pub fn focused_input(&mut self) -> &mut TextInput {
+        if self.confirming {
+            &mut self.confirmation
+        } else {
+            &mut self.password
+        }
+    }
+
+    fn submit(&mut self) -> PromptResult {
+        if self.password.is_empty() {
+            self.error = Some("Password can't be empty".to_string());
+            self.confirming = false;
+            return PromptResult::Pending;
+        }
+
+        if !self.require_confirmation {
+            return PromptResult::Submitted(self.password.value().to_string());
+        }
+
+        if !self.confirming {
+            self.confirming = true;
+            return PromptResult::Pending;
+        }
+
+        if self.password.value() != self.confirmation.value() {
+            self.error = Some("Passwords don't match".to_string());
+            self.confirmation.clear();
+            return PromptResult::Pending;
+        }
+
+        PromptResult::Submitted(self.password.value().to_string())
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> PromptResult {
+        match key.code {
+            KeyCode::Esc => return PromptResult::Cancelled,
+            KeyCode::Enter => return self.submit(),
+            KeyCode::Tab | KeyCode::BackTab | KeyCode::Up | KeyCode::Down
+                if self.require_confirmation =>
+            {
+                self.confirming = !self.confirming;
+            }
+            _ => {
+                if self.focused_input().handle_key(key) {
+                    self.error = None;
+                }
+            }
+        }
+
+        PromptResult::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(input: &mut TextInput, code: KeyCode, modifiers: KeyModifiers) -> bool {
+        input.handle_key(KeyEvent::new(code, modifiers))
+    }
+
+    fn committed(input: &mut TextInput, value: &str) {
+        input.set_value(value);
+        input.commit_history();
+    }
+
+    #[test]
+    fn test_word_deletion() {
+        let mut input = TextInput::new();
+        input.set_value("~/walter/my-file.txt");
+
+        assert!(press(&mut input, KeyCode::Char('w'), KeyModifiers::CONTROL));
+        assert_eq!(input.value(), "~/walter/my-file.");
+        // Separators right before the cursor go with the word before them
+        assert!(press(&mut input, KeyCode::Char('w'), KeyModifiers::CONTROL));
+        assert_eq!(input.value(), "~/walter/my-");
+        assert!(press(&mut input, KeyCode::Backspace, KeyModifiers::ALT));
+        assert_eq!(input.value(), "~/walter/");
+        assert_eq!(input.cursor(), 9);
+
+        input.set_value("one two three");
+        press(&mut input, KeyCode::Left, KeyModifiers::CONTROL);
+        assert_eq!(input.cursor(), 8);
+        assert!(press(&mut input, KeyCode::Char('k'), KeyModifiers::CONTROL));
+        assert_eq!(input.value(), "one two ");
+        press(&mut input, KeyCode::Left, KeyModifiers::NONE);
+        assert!(press(&mut input, KeyCode::Char('u'), KeyModifiers::CONTROL));
+        assert_eq!(input.value(), " ");
+        assert_eq!(input.cursor(), 0);
+        assert!(!press(
+            &mut input,
+            KeyCode::Char('w'),
+            KeyModifiers::CONTROL
+        ));
+    }
+
+    #[test]
+    fn test_char_indexed_cursor() {
+        let mut input = TextInput::new();
+        input.set_value("héllo wörld");
+        assert_eq!(input.cursor(), 11);
+
+        for _ in 0..5 {
+            assert!(!press(&mut input, KeyCode::Left, KeyModifiers::NONE));
+        }
+        assert_eq!(input.cursor(), 6);
+        press(&mut input, KeyCode::Char('X'), KeyModifiers::NONE);
+        assert_eq!(input.value(), "héllo Xwörld");
+        press(&mut input, KeyCode::Delete, KeyModifiers::NONE);
+        press(&mut input, KeyCode::Delete, KeyModifiers::NONE);
+        assert_eq!(input.value(), "héllo Xrld");
+        press(&mut input, KeyCode::Backspace, KeyModifiers::NONE);
+        assert_eq!(input.value(), "héllo rld");
+        assert_eq!(input.cursor(), 6);
+
+        press(&mut input, KeyCode::Left, KeyModifiers::CONTROL);
+        assert_eq!(input.cursor(), 0);
+        press(&mut input, KeyCode::Right, KeyModifiers::ALT);
+        assert_eq!(input.cursor(), 5);
+
+        // Pasted newlines are dropped
+        input.insert_str("ö\nö");
+        assert_eq!(input.value(), "hélloöö rld");
+        assert_eq!(input.cursor(), 7);
+
+        press(&mut input, KeyCode::End, KeyModifiers::NONE);
+        assert_eq!(input.cursor(), 11);
+        press(&mut input, KeyCode::Right, KeyModifiers::NONE);
+        assert_eq!(input.cursor(), 11);
+        press(&mut input, KeyCode::Home, KeyModifiers::NONE);
+        assert!(!press(&mut input, KeyCode::Backspace, KeyModifiers::NONE));
+    }
+
+    #[test]
+    fn test_history() {
+        let mut input = TextInput::new();
+        committed(&mut input, "one");
+        committed(&mut input, "two");
+        committed(&mut input, "");
+        // Committing an entry again moves it to the end
+        committed(&mut input, "one");
+        input.clear();
+
+        press(&mut input, KeyCode::Up, KeyModifiers::NONE);
+        assert_eq!(input.value(), "one");
+        press(&mut input, KeyCode::Up, KeyModifiers::NONE);
+        assert_eq!(input.value(), "two");
+        press(&mut input, KeyCode::Up, KeyModifiers::NONE);
+        assert_eq!(input.value(), "two");
+        press(&mut input, KeyCode::Down, KeyModifiers::NONE);
+        assert_eq!(input.value(), "one");
+        press(&mut input, KeyCode::Down, KeyModifiers::NONE);
+        assert_eq!(input.value(), "");
+
+        // The oldest entries are dropped past MAX_HISTORY
+        for i in 0..=MAX_HISTORY {
+            committed(&mut input, &i.to_string());
+        }
+        for _ in 0..MAX_HISTORY + 10 {
+            press(&mut input, KeyCode::Up, KeyModifiers::NONE);
+        }
+        assert_eq!(input.value(), "1");
+
+        // Passwords are never kept
+        let mut password = TextInput::masked();
+        committed(&mut password, "secret");
+        password.clear();
+        assert!(!press(&mut password, KeyCode::Up, KeyModifiers::NONE));
+        assert_eq!(password.display_value(), "");
+        password.set_value("pässword");
+        assert_eq!(password.display_value(), "••••••••");
+    }
+}
@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+
+use tokio::sync::{mpsc, Semaphore};
+use tokio::task::JoinHandle;
+use walter_core::migrator::{MigrationPlan, MigrationReport};
+use walter_core::types::{BlobInfo, DownloadReport};
+
+use crate::bulk::ItemResult;
+
+// Number of jobs allowed to run at the same time, the rest stay queued
+const MAX_RUNNING_JOBS: usize = 2;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum JobKind {
+    Upload,
+    Shard,
+    Migrate,
+    MigrationPlan,
+    ExtendEpoch,
+    RefreshBlobs,
+    Download,
+    BlobAction,
+    BlobVerify,
+    BlobStatus,
+    BulkAction,
+    WatchUpload,
+}
+
+pub enum JobStatus {
+    Queued,
+    Running,
+    Finished(String),
+    Failed(String),
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn is_active(&self) -> bool {
+        matches!(self, JobStatus::Queued | JobStatus::Running)
+    }
+}
+
+pub enum JobOutput {
+    Message(String),
+    Blobs(Vec<BlobInfo>),
+    Download(DownloadReport),
+    Summary(Vec<ItemResult>),
+    Plan(MigrationPlan),
+    Migration(MigrationReport),
+}
+
+pub struct Job {
+    pub id: u64,
+    pub kind: JobKind,
+    pub name: String,
+    pub status: JobStatus,
+    pub progress: Option<String>,
+}
+
+enum JobEvent {
+    Started(u64),
+    Progress(u64, String),
+    Finished(u64, Result<JobOutput, String>),
+}
+
+/// Handed to a running job so it can report how far along it is.
+#[derive(Clone)]
+pub struct JobProgress {
+    id: u64,
+    tx: mpsc::UnboundedSender<JobEvent>,
+}
+
+impl JobProgress {
+    pub fn report(&self, message: impl Into<String>) {
+        let _ = self.tx.send(JobEvent::Progress(self.id, message.into()));
+    }
+}
+
+/// Runs long operations as tokio tasks and reports back to the event loop
+/// through a channel, so the UI never awaits them directly.
+pub struct JobRunner {
+    pub jobs: Vec<Job>,
+    handles: HashMap<u64, JoinHandle<()>>,
+    next_id: u64,
+    permits: Arc<Semaphore>,
+    tx: mpsc::UnboundedSender<JobEvent>,
+    rx: mpsc::UnboundedReceiver<JobEvent>,
+}
+
+impl JobRunner {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        JobRunner {
+            jobs: Vec::new(),
+            handles: HashMap::new(),
+            next_id: 1,
+            permits: Arc::new(Semaphore::new(MAX_RUNNING_JOBS)),
+            tx,
+            rx,
+        }
+    }
+
+    pub fn spawn<F>(&mut self, kind: JobKind, name: impl Into<String>, future: F) -> u64
+    where
+        F: Future<Output = Result<JobOutput, String>> + Send + 'static,
+    {
+        self.spawn_with_progress(kind, name, |_| future)
+    }
+
+    /// Like `spawn`, but builds the future from a `JobProgress` it can report through.
+    pub fn spawn_with_progress<F, M>(&mut self, kind: JobKind, name: impl Into<String>, make: M) -> u64
+    where
+        F: Future<Output = Result<JobOutput, String>> + Send + 'static,
+        M: FnOnce(JobProgress) -> F,
+    {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let tx = self.tx.clone();
+        let permits = self.permits.clone();
+        let future = make(JobProgress {
+            id,
+            tx: self.tx.clone(),
+        });
+
+        let handle = tokio::spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            let _ = tx.send(JobEvent::Started(id));
+
+            let result = future.await;
+            let _ = tx.send(JobEvent::Finished(id, result));
+        });
+
+        self.jobs.push(Job {
+            id,
+            kind,
+            name: name.into(),
+            status: JobStatus::Queued,
+            progress: None,
+        });
+        self.handles.insert(id, handle);
+
+        id
+    }
+
+    pub fn cancel(&mut self, id: u64) -> bool {
+        let Some(job) = self.jobs.iter_mut().find(|job| job.id == id) else {
+            return false;
+        };
+
+        if !job.status.is_active() {
+            return false;
+        }
+
+        if let Some(handle) = self.handles.remove(&id) {
+            handle.abort();
+        }
+        job.status = JobStatus::Cancelled;
+        true
+    }
+
+    /// Removes every job that is no longer queued or running.
+    pub fn clear_finished(&mut self) {
+        self.jobs.retain(|job| job.status.is_active());
+    }
+
+    pub fn get(&self, id: u64) -> Option<&Job> {
+        self.jobs.iter().find(|job| job.id == id)
+    }
+
+    pub fn active_count(&self) -> usize {
+        self.jobs.iter().filter(|job| job.status.is_active()).count()
+    }
+
+    /// Applies pending job events and hands back the results of the jobs that
+    /// finished since the last call.
+    pub fn poll(&mut self) -> Vec<(JobKind, Result<JobOutput, String>)> {
+        let mut finished = Vec::new();
+
+        while let Ok(event) = self.rx.try_recv() {
+            match event {
+                JobEvent::Started(id) => {
+                    if let Some(job) = self.jobs.iter_mut().find(|job| job.id == id) {
+                        if let JobStatus::Queued = job.status {
+                            job.status = JobStatus::Running;
+                        }
+                    }
+                }
+                JobEvent::Progress(id, message) => {
+                    if let Some(job) = self.jobs.iter_mut().find(|job| job.id == id) {
+                        if job.status.is_active() {
+                            job.progress = Some(message);
+                        }
+                    }
+                }
+                JobEvent::Finished(id, result) => {
+                    self.handles.remove(&id);
+
+                    let Some(job) = self.jobs.iter_mut().find(|job| job.id == id) else {
+                        continue;
+                    };
+
+                    // The task may have completed right before it was cancelled
+                    if !job.status.is_active() {
+                        continue;
+                    }
+
+                    job.status = match &result {
+                        Ok(JobOutput::Message(message)) => JobStatus::Finished(message.clone()),
+                        Ok(JobOutput::Blobs(blobs)) => {
+                            JobStatus::Finished(format!("{} blobs", blobs.len()))
+                        }
+                        Ok(JobOutput::Download(report)) => {
+                            JobStatus::Finished(format!("saved to {}", report.out_path))
+                        }
+                        Ok(JobOutput::Summary(items)) => {
+                            let failed = items.iter().filter(|item| item.result.is_err()).count();
+                            JobStatus::Finished(format!(
+                                "{} succeeded, {} failed",
+                                items.len() - failed,
+                                failed
+                            ))
+                        }
+                        Ok(JobOutput::Plan(plan)) => {
+                            JobStatus::Finished(format!("{} files listed", plan.files.len()))
+                        }
+                        Ok(JobOutput::Migration(report)) => JobStatus::Finished(report.summary()),
+                        Err(e) => JobStatus::Failed(e.clone()),
+                    };
+
+                    finished.push((job.kind, result));
+                }
+            }
+        }
+
+        finished
+    }
+}
@@ -1,10 +1,26 @@
 mod app;
+mod bulk;
+mod cli;
+mod dashboard;
+mod downloader;
+mod input;
+mod jobs;
+mod migrator;
+mod picker;
 mod ui;
 mod utils;
 
-use app::{App, CurrentScreen};
+use app::{App, CurrentScreen, SpendAction};
+use clap::Parser;
+use cli::{Cli, Command};
+use dashboard::BlobAction;
+use input::PromptResult;
+use picker::PickerResult;
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    event::{
+        self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste,
+        EnableMouseCapture, Event, KeyCode,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -15,116 +31,32 @@ use ratatui::{
 use std::{
     error::Error,
     io::{self, Stdout},
+    time::Duration,
 };
 use ui::render_ui;
-use walter_core::client::upload_blob;
-use walter_core::migrator::migrate_files;
-use walter_core::updater;
-use walter_db;
+use walter_core::client::{list_blobs, walrus_info};
 
 use clipboard::{ClipboardContext, ClipboardProvider};
 
+// How long the event loop waits for input before checking on background jobs
+const TICK_RATE: Duration = Duration::from_millis(100);
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    let args: Vec<String> = std::env::args().collect();
-    if args.len() > 1 {
-        match args[1].as_str() {
-            "--help" | "-h" => {
-                println!("\x1b[1;32mUsage:\x1b[0m walter-ui --cli [OPTIONS]");
-                println!("\x1b[1;32mOptions:\x1b[0m");
-                println!("  \x1b[1;32m-h, --help\x1b[0m    Print this help message");
-                println!("  \x1b[1;32m-c, --cli\x1b[0m     CLI mode");
-                println!("  \x1b[1;32m-s, --setup\x1b[0m   Setup Walter along with Walrus CLI, Walrus Site Builder and Sui Client");
-                println!("  \x1b[1;32m-u, --update\x1b[0m  Update Walter and its dependencies");
-                println!("  \x1b[1;32m-sql, --sqlite\x1b[0m Run Walrus SQLite shell with rollbacks. Requires blobID as argument");
-                println!("  \x1b[1;32m-bs, --build-site\x1b[0m Build Walrus site with the given app path");
-                println!(
-                    "  \x1b[1;32m-rp, --run-pinner\x1b[0m Run Walrus Pinner in the background"
-                );
-                return Ok(());
-            }
-            "--tui" | "-c" => {
-                println!("\x1b[1;32mWalTerminalUI\x1b[0m - \x1b[1;34mA TUI Devtool keychain for Walrus\x1b[0m");
-                println!("\x1b[1;32mVersion:\x1b[0m 0.1.0");
-            }
-            "--setup" | "-s" => {
-                std::env::set_current_dir("..").expect("Failed to change directory.");
-                println!("Running setup...");
-                let output = std::process::Command::new("make")
-                    .arg("all")
-                    .output()
-                    .expect("Failed to execute make command");
-
-                if output.status.success() {
-                    println!("Setup completed successfully.");
-                } else {
-                    eprintln!(
-                        "Setup failed with error: {}",
-                        String::from_utf8_lossy(&output.stderr)
-                    );
-                }
-                return Ok(());
-            }
-            "--update" | "-u" => {
-                updater::run();
-            }
-            "--sqlite" | "-sql" => {
-                walter_db::main().unwrap();
-                return Ok(());
-            }
-            "--build-site" | "-bs" => {
-                let app_path = args[2].as_str();
-                let output = std::process::Command::new("site-builder")
-                    .arg("publish")
-                    .arg(app_path)
-                    .output()
-                    .expect("Failed to execute command.");
-
-                if output.status.success() {
-                    println!("Site built successfully.");
-                } else {
-                    eprintln!(
-                        "Site build failed with error: {}",
-                        String::from_utf8_lossy(&output.stderr)
-                    );
-                }
-                return Ok(());
-            }
-            "--run-pinner" | "-rp" => {
-                std::env::set_current_dir("../wal-pinner").expect("Failed to change directory.");
-
-                let output = std::process::Command::new("npm")
-                    .arg("install")
-                    .output()
-                    .expect("Failed to execute npm install command.");
-
-                if output.status.success() {
-                    println!("npm install completed successfully.");
-                } else {
-                    eprintln!(
-                        "npm install failed with error: {}",
-                        String::from_utf8_lossy(&output.stderr)
-                    );
-                }
-
-                println!("Starting pinner in the background...");
-
-                std::process::Command::new("npx")
-                    .arg("ts-node")
-                    .arg("../wal-pinner/src/index.ts")
-                    .spawn()
-                    .expect("Failed to start pinner process in the background.");
-                return Ok(());
-            }
-            _ => {
-                eprintln!("Unknown option: {}", args[1]);
-                return Ok(());
-            }
-        }
+    let cli = Cli::parse();
+    match cli.command {
+        None | Some(Command::Tui) => {}
+        Some(command) => std::process::exit(cli::run(command, cli.json).await),
     }
+
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(
+        stdout,
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste
+    )?;
 
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
@@ -139,13 +71,20 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let sui_active_address = sui_active_address.trim().to_string();
     app.sui_active_address = sui_active_address;
 
-    let user_blobs = utils::walrus_list_blobs().await?;
-    let user_blobs = serde_json::from_str(&user_blobs)?;
-    app.user_blobs = user_blobs;
-
-    let walrus_system_info = utils::walrus_info_system().await?;
-    let walrus_system_info = walrus_system_info.trim().to_string();
-    app.walrus_system_info = walrus_system_info;
+    // The terminal is already in raw mode, so failures go to the status line
+    // instead of ending the program with the terminal left unusable
+    let mut errors = Vec::new();
+    match list_blobs(false).await {
+        Ok(user_blobs) => app.user_blobs = user_blobs,
+        Err(e) => errors.push(format!("Listing blobs failed: {}", e)),
+    }
+    let info = walrus_info().await;
+    if let Err(e) = &info {
+        errors.push(format!("Reading Walrus info failed: {}", e));
+    }
+    app.walrus_info = info.ok();
+    app.dashboard_status = errors.join(", ");
+    app.update_visible_blobs();
 
     let _res = run_app(&mut terminal, &mut app).await;
 
@@ -153,7 +92,8 @@ async fn main() -> Result<(), Box<dyn Error>> {
     execute!(
         terminal.backend_mut(),
         LeaveAlternateScreen,
-        DisableMouseCapture
+        DisableMouseCapture,
+        DisableBracketedPaste
     )?;
     terminal.show_cursor()?;
     Ok(())
@@ -164,9 +104,115 @@ async fn run_app(
     app: &mut App,
 ) -> io::Result<bool> {
     loop {
+        app.process_jobs();
+        app.process_config_changes();
+        app.process_watch_folders();
         terminal.draw(|frame| render_ui(frame, app))?;
 
-        if let Event::Key(key) = event::read()? {
+        if !event::poll(TICK_RATE)? {
+            continue;
+        }
+
+        let event = event::read()?;
+        if let Event::Paste(text) = &event {
+            app.paste(text);
+            continue;
+        }
+
+        if let Event::Key(key) = event {
+            // The password prompt takes every key while it is open
+            if let Some(prompt) = app.password_prompt.as_mut() {
+                if key.kind == event::KeyEventKind::Press {
+                    match prompt.handle_key(key) {
+                        PromptResult::Pending => {}
+                        PromptResult::Cancelled => {
+                            app.password_prompt = None;
+                            app.bulk_awaiting_password = None;
+                        }
+                        PromptResult::Submitted(password) => {
+                            app.password_prompt = None;
+                            match app.current_screen {
+                                CurrentScreen::Downloader => app.start_download(Some(password)),
+                                CurrentScreen::Jobs => app.start_watching(Some(password)),
+                                CurrentScreen::Migrator => {
+                                    app.request_spend(SpendAction::Migrate(Some(password)))
+                                }
+                                CurrentScreen::Dashboard => {
+                                    if let Some((action, target)) =
+                                        app.bulk_awaiting_password.take()
+                                    {
+                                        app.start_bulk_action(action, target, Some(password));
+                                    }
+                                }
+                                _ => app.request_spend(SpendAction::Shard(Some(password))),
+                            }
+                        }
+                    }
+                }
+                continue;
+            }
+
+            // Blob action popups take every key while they are open
+            if app.action_output.is_some() {
+                if key.kind == event::KeyEventKind::Press
+                    && matches!(key.code, KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q'))
+                {
+                    app.action_output = None;
+                }
+                continue;
+            }
+
+            if app.pending_action.is_some() {
+                if key.kind == event::KeyEventKind::Press {
+                    match key.code {
+                        KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                            app.run_pending_action();
+                        }
+                        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                            app.pending_action = None;
+                        }
+                        KeyCode::Char('+') | KeyCode::Up if app.epochs < 200 => {
+                            app.epochs += 1;
+                        }
+                        KeyCode::Char('-') | KeyCode::Down if app.epochs > 1 => {
+                            app.epochs -= 1;
+                        }
+                        _ => {}
+                    }
+                }
+                continue;
+            }
+
+            if app.pending_spend.is_some() {
+                if key.kind == event::KeyEventKind::Press {
+                    match key.code {
+                        KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                            app.run_pending_spend();
+                        }
+                        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                            app.pending_spend = None;
+                        }
+                        _ => {}
+                    }
+                }
+                continue;
+            }
+
+            // The file picker popup takes every key while it is open
+            if let Some(picker) = app.file_picker.as_mut() {
+                if key.kind == event::KeyEventKind::Press {
+                    match picker.handle_key(key) {
+                        PickerResult::Pending => {}
+                        PickerResult::Cancelled => app.file_picker = None,
+                        PickerResult::Chosen(files) => {
+                            app.file_picker = None;
+                            app.pick_files(files);
+                        }
+                    }
+                }
+                continue;
+            }
+
             if key.kind == event::KeyEventKind::Press {
                 if key.code == KeyCode::Esc {
                     if app.is_editing {
@@ -195,43 +241,125 @@ async fn run_app(
                     }
                 }
 
-                if key.code == KeyCode::Char('1') {
+                if key.code == KeyCode::Char('1') && !app.is_editing {
                     app.current_screen = CurrentScreen::Dashboard;
                     app.is_editing = false;
                 }
 
-                if key.code == KeyCode::Char('2') {
+                if key.code == KeyCode::Char('2') && !app.is_editing {
                     app.current_screen = CurrentScreen::Uploader;
                     app.is_editing = false;
                 }
 
-                if key.code == KeyCode::Char('3') {
+                if key.code == KeyCode::Char('3') && !app.is_editing {
                     app.current_screen = CurrentScreen::Migrator;
                     app.is_editing = false;
                 }
 
-                if key.code == KeyCode::Char('4') {
+                if key.code == KeyCode::Char('4') && !app.is_editing {
                     app.current_screen = CurrentScreen::SharderAndEpochExtender;
                     app.is_editing = false;
                 }
+
+                if key.code == KeyCode::Char('5') && !app.is_editing {
+                    app.current_screen = CurrentScreen::Jobs;
+                    app.is_editing = false;
+                }
+
+                if key.code == KeyCode::Char('6') && !app.is_editing {
+                    app.current_screen = CurrentScreen::Downloader;
+                    app.is_editing = false;
+                }
             }
 
             match app.current_screen {
                 CurrentScreen::Splash => match key.code {
                     KeyCode::Enter => {
                         app.current_screen = CurrentScreen::Dashboard;
-                        if !&app.user_blobs.is_empty() {
-                            app.scrollbar_state = ScrollbarState::new(&app.user_blobs.len() - 1);
+                        if app.visible_len() > 0 {
+                            app.scrollbar_state = ScrollbarState::new(app.visible_len() - 1);
                         }
                     }
                     _ => {}
                 },
+                CurrentScreen::Dashboard if app.is_editing => match key.code {
+                    KeyCode::Char(value) => {
+                        app.blob_filter.search.push(value);
+                        app.update_visible_blobs();
+                    }
+                    KeyCode::Backspace => {
+                        app.blob_filter.search.pop();
+                        app.update_visible_blobs();
+                    }
+                    KeyCode::Enter => {
+                        app.is_editing = false;
+                    }
+                    KeyCode::Up => {
+                        app.prev_row();
+                    }
+                    KeyCode::Down => {
+                        app.next_row();
+                    }
+                    _ => {}
+                },
                 CurrentScreen::Dashboard => match key.code {
                     KeyCode::Char('c') | KeyCode::Char('C') => {
-                        let mut ctx: ClipboardContext = ClipboardProvider::new().unwrap();
-                        let selected_index = app.table_state.selected().unwrap_or(0);
-                        ctx.set_contents(app.user_blobs[selected_index].blob_id.clone())
-                            .unwrap();
+                        if let Some(blob) = app.selected_blob() {
+                            let mut ctx: ClipboardContext = ClipboardProvider::new().unwrap();
+                            ctx.set_contents(blob.blob_id.clone()).unwrap();
+                        }
+                    }
+                    KeyCode::Char('/') => {
+                        app.is_editing = true;
+                    }
+                    KeyCode::Char('s') | KeyCode::Char('S') => {
+                        app.blob_filter.next_sort_column();
+                        app.update_visible_blobs();
+                    }
+                    KeyCode::Char('r') | KeyCode::Char('R') => {
+                        app.blob_filter.sort_ascending = !app.blob_filter.sort_ascending;
+                        app.update_visible_blobs();
+                    }
+                    KeyCode::Char('f') | KeyCode::Char('F') => {
+                        app.blob_filter.next_expiry_filter();
+                        app.update_visible_blobs();
+                    }
+                    KeyCode::Char('x') | KeyCode::Char('X') => {
+                        app.blob_filter.clear();
+                        app.update_visible_blobs();
+                    }
+                    KeyCode::Char('g') | KeyCode::Char('G') => {
+                        app.toggle_dashboard_view();
+                    }
+                    KeyCode::Char(' ') => {
+                        app.toggle_mark();
+                    }
+                    KeyCode::Char('a') | KeyCode::Char('A') => {
+                        app.toggle_mark_all();
+                    }
+                    KeyCode::Char('m') | KeyCode::Char('M') => {
+                        app.mark_visible();
+                    }
+                    KeyCode::Char('u') | KeyCode::Char('U') => {
+                        app.clear_marks();
+                    }
+                    KeyCode::Char('t') | KeyCode::Char('T') => {
+                        app.request_blob_action(BlobAction::Extend);
+                    }
+                    KeyCode::Char('d') | KeyCode::Char('D') => {
+                        app.request_blob_action(BlobAction::Download);
+                    }
+                    KeyCode::Delete => {
+                        app.request_blob_action(BlobAction::Delete);
+                    }
+                    KeyCode::Char('v') | KeyCode::Char('V') => {
+                        app.request_blob_action(BlobAction::Verify);
+                    }
+                    KeyCode::Char('i') | KeyCode::Char('I') => {
+                        app.request_blob_action(BlobAction::Status);
+                    }
+                    KeyCode::Char('o') | KeyCode::Char('O') => {
+                        app.request_blob_action(BlobAction::Open);
                     }
                     KeyCode::Up => {
                         app.prev_row();
@@ -241,6 +369,34 @@ async fn run_app(
                     }
                     _ => {}
                 },
+                CurrentScreen::Uploader | CurrentScreen::SharderAndEpochExtender
+                    if app.is_editing =>
+                {
+                    match key.code {
+                        KeyCode::PageUp => {
+                            if app.epochs < 200 {
+                                app.epochs += 1;
+                            }
+                        }
+                        KeyCode::PageDown => {
+                            if app.epochs > 1 {
+                                app.epochs -= 1;
+                            }
+                        }
+                        KeyCode::Enter => {
+                            if let CurrentScreen::Uploader = app.current_screen {
+                                app.request_spend(SpendAction::Upload);
+                            }
+                        }
+                        _ => {
+                            if key.kind == event::KeyEventKind::Press
+                                && app.filename.handle_key(key)
+                            {
+                                app.update_file_size();
+                            }
+                        }
+                    }
+                }
                 CurrentScreen::Uploader => match key.code {
                     KeyCode::Up | KeyCode::Char('+') => {
                         if app.epochs < 200 {
@@ -252,36 +408,31 @@ async fn run_app(
                             app.epochs -= 1;
                         }
                     }
+                    KeyCode::Char('o') | KeyCode::Char('O') => {
+                        app.open_file_picker();
+                    }
+                    KeyCode::Enter => {
+                        app.request_spend(SpendAction::Upload);
+                    }
+                    _ => {}
+                },
+                CurrentScreen::Migrator if app.is_editing => match key.code {
                     KeyCode::Char(value) => {
-                        if app.is_editing {
-                            app.filename += &value.to_string();
-                        }
+                        app.migration_filter.search.push(value);
+                        app.update_visible_migration_files();
                     }
                     KeyCode::Backspace => {
-                        if app.is_editing {
-                            if app.filename.len() > 0 {
-                                app.filename.pop();
-                            }
-                        }
+                        app.migration_filter.search.pop();
+                        app.update_visible_migration_files();
                     }
                     KeyCode::Enter => {
-                        app.migration_status = "Uploading file...".into();
-                        terminal.draw(|frame| render_ui(frame, app))?;
-
-                        let res = upload_blob(&app.filename, app.epochs).await;
-                        match res {
-                            Ok(blob_id) => {
-                                app.file_upload_status =
-                                    format!("File uploaded successfully! Blob ID {}", blob_id);
-                                let user_blobs = utils::walrus_list_blobs().await.unwrap();
-                                let user_blobs = serde_json::from_str(&user_blobs).unwrap();
-                                app.user_blobs = user_blobs;
-                            }
-                            Err(e) => {
-                                app.file_upload_status =
-                                    format!("File upload Failed: {e}").to_string();
-                            }
-                        }
+                        app.is_editing = false;
+                    }
+                    KeyCode::Up => {
+                        app.prev_migration_file();
+                    }
+                    KeyCode::Down => {
+                        app.next_migration_file();
                     }
                     _ => {}
                 },
@@ -289,83 +440,146 @@ async fn run_app(
                     KeyCode::Char('P') | KeyCode::Char('p') => {
                         let mut ctx: clipboard::ClipboardContext =
                             ClipboardProvider::new().unwrap();
-                        app.pinata_api_key = ctx.get_contents().unwrap();
+                        app.set_pinata_api_key(ctx.get_contents().unwrap().trim());
                     }
                     KeyCode::Char('x') => {
-                        app.pinata_api_key = "".into();
+                        app.set_pinata_api_key("");
+                    }
+                    KeyCode::Char('L') | KeyCode::Char('l') => {
+                        app.start_migration_plan();
+                    }
+                    KeyCode::Char(' ') => {
+                        app.toggle_migration_check();
+                    }
+                    KeyCode::Char('a') | KeyCode::Char('A') => {
+                        app.toggle_all_migration_checks();
+                    }
+                    KeyCode::Char('/') => {
+                        app.is_editing = true;
+                    }
+                    KeyCode::Char('s') | KeyCode::Char('S') => {
+                        app.migration_filter.next_size_filter();
+                        app.update_visible_migration_files();
+                    }
+                    KeyCode::Char('d') | KeyCode::Char('D') => {
+                        app.migration_filter.next_date_filter();
+                        app.update_visible_migration_files();
+                    }
+                    KeyCode::Char('r') | KeyCode::Char('R') => {
+                        app.migration_filter.clear();
+                        app.update_visible_migration_files();
+                    }
+                    KeyCode::Char('+') if app.migration_epochs < 200 => {
+                        app.migration_epochs += 1;
+                        app.update_migration_estimates();
+                    }
+                    KeyCode::Char('-') if app.migration_epochs > 1 => {
+                        app.migration_epochs -= 1;
+                        app.update_migration_estimates();
+                    }
+                    KeyCode::Char('K') | KeyCode::Char('k') => {
+                        app.next_migration_shard_size();
+                    }
+                    KeyCode::Char('Y') | KeyCode::Char('y') => {
+                        app.migration_encrypt = !app.migration_encrypt;
+                        app.update_migration_estimates();
                     }
                     KeyCode::Char('M') | KeyCode::Char('m') => {
-                        app.migration_status = "Migrating files...".into();
-                        terminal.draw(|frame| render_ui(frame, app))?;
-
-                        let res = migrate_files(&app.pinata_api_key).await;
-                        match res {
-                            Ok(_) => {
-                                app.migration_status = "Migration successful".into();
-                                let user_blobs = utils::walrus_list_blobs().await.unwrap();
-                                let user_blobs = serde_json::from_str(&user_blobs).unwrap();
-                                app.user_blobs = user_blobs;
-                            }
-                            Err(e) => {
-                                app.migration_status = format!("Migration failed: {}", e);
-                            }
-                        }
+                        app.request_migration();
+                    }
+                    KeyCode::Up => {
+                        app.prev_migration_file();
+                    }
+                    KeyCode::Down => {
+                        app.next_migration_file();
                     }
                     _ => {}
                 },
                 CurrentScreen::SharderAndEpochExtender => match key.code {
                     KeyCode::Char('K') | KeyCode::Char('k') => {
-                        if app.is_editing {
-                            app.filename += &key.code.to_string();
-                        } else {
-                            app.sharder_status = "Sharding Started...".into();
-                            terminal.draw(|frame| render_ui(frame, app))?;
-
-                            let status = app.upload_shard().await;
-                            app.sharder_status = status;
-                        }
+                        app.request_spend(SpendAction::Shard(None));
                     }
                     KeyCode::Char('Y') | KeyCode::Char('y') => {
-                        if app.is_editing {
-                            app.filename += &key.code.to_string();
-                        } else {
-                            app.shard_pass = "password".into();
-                            let status = app.upload_shard().await;
-                            app.sharder_status = status;
-                        }
+                        app.open_password_prompt();
                     }
                     KeyCode::Char('T') | KeyCode::Char('t') => {
-                        if app.is_editing {
-                            app.filename += &key.code.to_string();
-                        } else {
-                            app.sharder_status = "Extending Epoch...".into();
-                            terminal.draw(|frame| render_ui(frame, app))?;
-
-                            let status = app.extend_blob_epoch().await;
-                            app.extender_status = status;
-                        }
+                        app.request_spend(SpendAction::Extend);
                     }
                     KeyCode::Char('P') | KeyCode::Char('p') => {
-                        if app.is_editing {
-                            app.filename += &key.code.to_string();
-                        } else {
-                            let mut ctx: clipboard::ClipboardContext =
-                                ClipboardProvider::new().unwrap();
-                            app.extender_blob_id = ctx.get_contents().unwrap();
-                        }
+                        let mut ctx: clipboard::ClipboardContext =
+                            ClipboardProvider::new().unwrap();
+                        app.extender_blob_id = ctx.get_contents().unwrap();
                     }
-                    KeyCode::Char(value) => {
-                        if app.is_editing {
-                            app.filename += &value.to_string();
+                    KeyCode::Char('O') | KeyCode::Char('o') => {
+                        app.open_file_picker();
+                    }
+                    _ => {}
+                },
+                CurrentScreen::Downloader if app.is_editing => match key.code {
+                    KeyCode::Tab => {
+                        app.download_focus = match app.download_focus {
+                            downloader::DownloadFocus::Destination => {
+                                downloader::DownloadFocus::BlobId
+                            }
+                            _ => downloader::DownloadFocus::Destination,
+                        };
+                    }
+                    KeyCode::Enter => {
+                        app.is_editing = false;
+                        app.request_download();
+                    }
+                    _ => {
+                        if key.kind == event::KeyEventKind::Press {
+                            app.focused_download_input().handle_key(key);
                         }
                     }
-                    KeyCode::Backspace => {
-                        if app.is_editing {
-                            if app.filename.len() > 0 {
-                                app.filename.pop();
+                },
+                CurrentScreen::Downloader => match key.code {
+                    KeyCode::Up => {
+                        app.prev_download_file();
+                    }
+                    KeyCode::Down => {
+                        app.next_download_file();
+                    }
+                    KeyCode::Tab => {
+                        app.download_focus = app.download_focus.next();
+                    }
+                    KeyCode::Char('P') | KeyCode::Char('p') => {
+                        match ClipboardContext::new().and_then(|mut ctx| ctx.get_contents()) {
+                            Ok(contents) => {
+                                app.download_blob_id.set_value(contents.trim());
+                                app.download_focus = downloader::DownloadFocus::BlobId;
+                            }
+                            Err(e) => {
+                                app.download_status = format!("Can't read the clipboard: {}", e);
                             }
                         }
                     }
+                    KeyCode::Char('X') | KeyCode::Char('x') => {
+                        app.download_blob_id.clear();
+                    }
+                    KeyCode::Char('D') | KeyCode::Char('d') | KeyCode::Enter => {
+                        app.request_download();
+                    }
+                    _ => {}
+                },
+                CurrentScreen::Jobs => match key.code {
+                    KeyCode::Up => {
+                        app.prev_job();
+                    }
+                    KeyCode::Down => {
+                        app.next_job();
+                    }
+                    KeyCode::Char('c') | KeyCode::Char('C') => {
+                        app.cancel_selected_job();
+                    }
+                    KeyCode::Char('x') | KeyCode::Char('X') => {
+                        app.jobs.clear_finished();
+                        app.jobs_table_state.select(Some(0));
+                    }
+                    KeyCode::Char('w') | KeyCode::Char('W') => {
+                        app.toggle_watching();
+                    }
                     _ => {}
                 },
             }
@@ -0,0 +1,209 @@
+use walter_core::migrator::PlannedFile;
+
+use crate::dashboard::format_size;
+
+// Presets cycled through by the "at least N bytes" filter
+const MIN_SIZE_FILTER_STEPS: [u64; 4] = [1 << 20, 10 << 20, 100 << 20, 1 << 30];
+
+// Presets cycled through by the "added within N days" filter
+const ADDED_FILTER_STEPS: [u64; 4] = [1, 7, 30, 365];
+
+// Shard sizes a migration batch can be split into, after the configured default
+const SHARD_SIZE_STEPS: [usize; 4] = [1 << 20, 10 << 20, 100 << 20, 500 << 20];
+
+/// The shard size after `current`, cycling from the configured `default`
+/// through `SHARD_SIZE_STEPS` and back.
+pub fn next_shard_size(current: usize, default: usize) -> usize {
+    let mut steps = SHARD_SIZE_STEPS
+        .iter()
+        .copied()
+        .filter(|&step| step != default);
+    if current == default {
+        return steps.next().unwrap_or(default);
+    }
+    steps.find(|&step| step > current).unwrap_or(default)
+}
+
+/// Search and filter settings for the Migrator file table.
+pub struct MigrationFilter {
+    pub search: String,
+    pub min_size: Option<u64>,
+    pub added_within_days: Option<u64>,
+}
+
+impl MigrationFilter {
+    pub fn new() -> Self {
+        MigrationFilter {
+            search: String::new(),
+            min_size: None,
+            added_within_days: None,
+        }
+    }
+
+    pub fn next_size_filter(&mut self) {
+        self.min_size = match self.min_size {
+            None => Some(MIN_SIZE_FILTER_STEPS[0]),
+            Some(size) => MIN_SIZE_FILTER_STEPS
+                .iter()
+                .find(|&&step| step > size)
+                .copied(),
+        };
+    }
+
+    pub fn next_date_filter(&mut self) {
+        self.added_within_days = match self.added_within_days {
+            None => Some(ADDED_FILTER_STEPS[0]),
+            Some(days) => ADDED_FILTER_STEPS
+                .iter()
+                .find(|&&step| step > days)
+                .copied(),
+        };
+    }
+
+    pub fn clear(&mut self) {
+        *self = MigrationFilter::new();
+    }
+
+    /// Returns the indices of the files to show. Files the source doesn't
+    /// report a size or date for are hidden by the matching filter.
+    pub fn apply(&self, files: &[PlannedFile], now: u64) -> Vec<usize> {
+        let search = self.search.to_lowercase();
+
+        files
+            .iter()
+            .enumerate()
+            .filter(|(_, planned)| {
+                search.is_empty()
+                    || planned.file.name.to_lowercase().contains(&search)
+                    || planned.file.id.to_lowercase().contains(&search)
+            })
+            .filter(|(_, planned)| match self.min_size {
+                Some(min_size) => planned.file.size.is_some_and(|size| size >= min_size),
+                None => true,
+            })
+            .filter(|(_, planned)| match self.added_within_days {
+                Some(days) => planned
+                    .file
+                    .created_secs()
+                    .is_some_and(|created| created + days * 86400 >= now),
+                None => true,
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Short description of the active settings, shown under the file table.
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+
+        if !self.search.is_empty() {
+            parts.push(format!("Search: {}", self.search));
+        }
+        if let Some(size) = self.min_size {
+            parts.push(format!("At least {}", format_size(size)));
+        }
+        if let Some(days) = self.added_within_days {
+            parts.push(format!("Added within {} days", days));
+        }
+
+        parts.join(" | ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use walter_core::migrator::SourceFile;
+
+    fn planned(name: &str, id: &str, size: Option<u64>, created_at: Option<&str>) -> PlannedFile {
+        PlannedFile {
+            file: SourceFile {
+                id: id.to_string(),
+                name: name.to_string(),
+                cid: Some(id.to_string()),
+                size,
+                created_at: created_at.map(str::to_string),
+            },
+            estimated_cost: None,
+            migrated: false,
+        }
+    }
+
+    #[test]
+    fn test_next_shard_size() {
+        // A default outside the presets comes back after the largest one
+        let default = 5 << 20;
+        let mut sizes = vec![default];
+        for _ in 0..5 {
+            sizes.push(next_shard_size(*sizes.last().unwrap(), default));
+        }
+        assert_eq!(
+            sizes,
+            vec![default, 1 << 20, 10 << 20, 100 << 20, 500 << 20, default]
+        );
+
+        // A default among the presets isn't offered twice
+        let default = 10 << 20;
+        assert_eq!(next_shard_size(default, default), 1 << 20);
+        assert_eq!(next_shard_size(1 << 20, default), 100 << 20);
+        assert_eq!(next_shard_size(500 << 20, default), default);
+
+        assert_eq!(next_shard_size(50 << 20, default), 100 << 20);
+    }
+
+    #[test]
+    fn test_migration_filter() {
+        let files = vec![
+            planned(
+                "photo.jpg",
+                "bafyA",
+                Some(2 << 20),
+                Some("2024-06-10T12:00:00Z"),
+            ),
+            planned("notes.txt", "bafyB", Some(100), Some("2024-06-01")),
+            planned("unknown.bin", "bafyC", None, None),
+        ];
+        let now = planned("now", "now", None, Some("2024-06-11T12:00:00Z"))
+            .file
+            .created_secs()
+            .unwrap();
+        let mut filter = MigrationFilter::new();
+        assert_eq!(filter.apply(&files, now), vec![0, 1, 2]);
+
+        // Names and IDs are searched regardless of case
+        filter.search = "PHOTO".to_string();
+        assert_eq!(filter.apply(&files, now), vec![0]);
+        filter.search = "bafyb".to_string();
+        assert_eq!(filter.apply(&files, now), vec![1]);
+        filter.clear();
+
+        // Files without a size or a date are hidden by those filters
+        filter.next_size_filter();
+        assert_eq!(filter.min_size, Some(1 << 20));
+        assert_eq!(filter.apply(&files, now), vec![0]);
+        filter.min_size = Some(100);
+        assert_eq!(filter.apply(&files, now), vec![0, 1]);
+        filter.clear();
+
+        filter.next_date_filter();
+        assert_eq!(filter.added_within_days, Some(1));
+        assert_eq!(filter.apply(&files, now), vec![0]);
+        assert!(filter.apply(&files, now + 1).is_empty());
+        filter.next_date_filter();
+        assert_eq!(filter.apply(&files, now), vec![0]);
+        filter.next_date_filter();
+        assert_eq!(filter.added_within_days, Some(30));
+        assert_eq!(filter.apply(&files, now), vec![0, 1]);
+        filter.next_date_filter();
+        filter.next_date_filter();
+        assert_eq!(filter.added_within_days, None);
+
+        filter.search = "a".to_string();
+        filter.min_size = Some(1 << 20);
+        filter.added_within_days = Some(7);
+        assert_eq!(
+            filter.summary(),
+            "Search: a | At least 1.00 MiB | Added within 7 days"
+        );
+    }
+}
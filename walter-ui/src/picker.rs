@@ -0,0 +1,234 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::widgets::ListState;
+
+pub struct PickerEntry {
+    pub name: String,
+    pub path: PathBuf,
+    pub is_dir: bool,
+    pub size: u64,
+}
+
+pub enum PickerResult {
+    Pending,
+    Cancelled,
+    Chosen(Vec<PathBuf>),
+}
+
+/// Popup for browsing the filesystem and choosing one or more files.
+pub struct FilePicker {
+    pub cwd: PathBuf,
+    pub entries: Vec<PickerEntry>,
+    pub list_state: ListState,
+    pub selected: BTreeSet<PathBuf>,
+    pub query: String,
+    pub multi_select: bool,
+    pub error: Option<String>,
+}
+
+impl FilePicker {
+    pub fn open(start: &Path, multi_select: bool) -> Self {
+        // Start from the directory of the current path if it has one
+        let cwd = if start.is_dir() {
+            start.to_path_buf()
+        } else {
+            start
+                .parent()
+                .filter(|parent| parent.is_dir())
+                .map(Path::to_path_buf)
+                .or_else(|| std::env::current_dir().ok())
+                .unwrap_or_else(|| PathBuf::from("/"))
+        };
+
+        let mut picker = FilePicker {
+            cwd: fs::canonicalize(&cwd).unwrap_or(cwd),
+            entries: Vec::new(),
+            list_state: ListState::default().with_selected(Some(0)),
+            selected: BTreeSet::new(),
+            query: String::new(),
+            multi_select,
+            error: None,
+        };
+        picker.load_entries();
+        picker
+    }
+
+    fn load_entries(&mut self) {
+        self.entries.clear();
+        self.error = None;
+
+        match fs::read_dir(&self.cwd) {
+            Ok(read_dir) => {
+                for entry in read_dir.flatten() {
+                    let Ok(metadata) = entry.metadata() else {
+                        continue;
+                    };
+
+                    self.entries.push(PickerEntry {
+                        name: entry.file_name().to_string_lossy().to_string(),
+                        path: entry.path(),
+                        is_dir: metadata.is_dir(),
+                        size: metadata.len(),
+                    });
+                }
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+
+        // Directories first, then files, each alphabetically
+        self.entries
+            .sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then(a.name.cmp(&b.name)));
+        self.list_state.select(Some(0));
+    }
+
+    /// Entries matching the typed prefix, hidden files only show up once a "." is typed.
+    pub fn visible_entries(&self) -> Vec<&PickerEntry> {
+        let query = self.query.to_lowercase();
+
+        self.entries
+            .iter()
+            .filter(|entry| query.starts_with('.') || !entry.name.starts_with('.'))
+            .filter(|entry| entry.name.to_lowercase().starts_with(&query))
+            .collect()
+    }
+
+    pub fn highlighted(&self) -> Option<&PickerEntry> {
+        self.visible_entries()
+            .get(self.list_state.selected().unwrap_or(0))
+            .copied()
+    }
+
+    fn next(&mut self) {
+        let len = self.visible_entries().len();
+        if len > 0 {
+            let i = match self.list_state.selected() {
+                Some(i) if i + 1 < len => i + 1,
+                _ => 0,
+            };
+            self.list_state.select(Some(i));
+        }
+    }
+
+    fn prev(&mut self) {
+        let len = self.visible_entries().len();
+        if len > 0 {
+            let i = match self.list_state.selected() {
+                Some(i) if i > 0 && i < len => i - 1,
+                _ => len - 1,
+            };
+            self.list_state.select(Some(i));
+        }
+    }
+
+    fn change_dir(&mut self, dir: PathBuf) {
+        self.cwd = dir;
+        self.query.clear();
+        self.load_entries();
+    }
+
+    fn parent(&mut self) {
+        if let Some(parent) = self.cwd.parent() {
+            self.change_dir(parent.to_path_buf());
+        }
+    }
+
+    fn toggle_selected(&mut self) {
+        let Some(entry) = self.highlighted() else {
+            return;
+        };
+
+        if entry.is_dir {
+            return;
+        }
+
+        let path = entry.path.clone();
+        if !self.selected.remove(&path) {
+            self.selected.insert(path);
+        }
+        self.next();
+    }
+
+    // Completes the typed prefix as far as every matching entry agrees, and
+    // enters the directory when only one matches
+    fn complete(&mut self) {
+        let visible = self.visible_entries();
+
+        if let [entry] = visible.as_slice() {
+            if entry.is_dir {
+                let dir = entry.path.clone();
+                self.change_dir(dir);
+            } else {
+                self.query = entry.name.clone();
+            }
+            return;
+        }
+
+        let Some(first) = visible.first() else {
+            return;
+        };
+
+        let mut prefix = first.name.clone();
+        for entry in &visible[1..] {
+            while !entry.name.starts_with(&prefix) {
+                prefix.pop();
+            }
+        }
+
+        if prefix.len() > self.query.len() {
+            self.query = prefix;
+            self.list_state.select(Some(0));
+        }
+    }
+
+    fn choose(&mut self) -> PickerResult {
+        if !self.selected.is_empty() {
+            return PickerResult::Chosen(self.selected.iter().cloned().collect());
+        }
+
+        let Some(entry) = self.highlighted() else {
+            return PickerResult::Pending;
+        };
+
+        if entry.is_dir {
+            let dir = entry.path.clone();
+            self.change_dir(dir);
+            PickerResult::Pending
+        } else {
+            PickerResult::Chosen(vec![entry.path.clone()])
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> PickerResult {
+        match key.code {
+            KeyCode::Esc => return PickerResult::Cancelled,
+            KeyCode::Enter => return self.choose(),
+            KeyCode::Up => self.prev(),
+            KeyCode::Down => self.next(),
+            KeyCode::Right => {
+                if let Some(entry) = self.highlighted().filter(|entry| entry.is_dir) {
+                    let dir = entry.path.clone();
+                    self.change_dir(dir);
+                }
+            }
+            KeyCode::Left => self.parent(),
+            KeyCode::Tab => self.complete(),
+            KeyCode::Char(' ') if self.multi_select => self.toggle_selected(),
+            KeyCode::Char(value) => {
+                self.query.push(value);
+                self.list_state.select(Some(0));
+            }
+            KeyCode::Backspace => {
+                if self.query.pop().is_none() {
+                    self.parent();
+                }
+                self.list_state.select(Some(0));
+            }
+            _ => {}
+        }
+
+        PickerResult::Pending
+    }
+}
@@ -3,13 +3,22 @@ use ratatui::{
     style::{Color, Modifier, Style, Stylize},
     text::{Line, Span, Text},
     widgets::{
-        Block, BorderType, Borders, Cell, Clear, Padding, Paragraph, Row, Scrollbar,
-        ScrollbarOrientation, Table, Wrap,
+        Block, BorderType, Borders, Cell, Clear, List, ListItem, Padding, Paragraph, Row,
+        Scrollbar, ScrollbarOrientation, Table, Wrap,
     },
     Frame,
 };
 
+use walter_core::cost::{format_cost, format_wal, total_cost, CostEstimate};
+
 use crate::app::{App, CurrentScreen};
+use crate::dashboard::{
+    describe_expiry, expiry_level, format_size, system_summary, DashboardView, ExpiryLevel,
+};
+use crate::downloader::DownloadFocus;
+use crate::input::{PasswordPrompt, TextInput};
+use crate::jobs::JobStatus;
+use crate::picker::FilePicker;
 
 pub fn render_ui(frame: &mut Frame, app: &mut App) {
     let centered_rect = centered_rect(95, 95, frame.area());
@@ -74,10 +83,52 @@ pub fn render_ui(frame: &mut Frame, app: &mut App) {
             );
             render_sharder_and_extender(frame, app, chunks[1]);
         }
+        CurrentScreen::Jobs => {
+            frame.render_widget(
+                Paragraph::new("").block(
+                    Block::bordered()
+                        .title("~ [ Jobs ] ~")
+                        .title_alignment(Alignment::Center),
+                ),
+                frame.area(),
+            );
+            render_jobs(frame, app, chunks[1]);
+        }
+        CurrentScreen::Downloader => {
+            frame.render_widget(
+                Paragraph::new("").block(
+                    Block::bordered()
+                        .title("~ [ Download ] ~")
+                        .title_alignment(Alignment::Center),
+                ),
+                frame.area(),
+            );
+            render_downloader(frame, app, chunks[1]);
+        }
+    }
+
+    if let Some(picker) = app.file_picker.as_mut() {
+        render_file_picker(frame, picker, centered_rect);
+    }
+
+    if let Some(prompt) = app.password_prompt.as_ref() {
+        render_password_prompt(frame, prompt, centered_rect);
+    }
+
+    if let Some((action, _)) = &app.pending_action {
+        render_confirm_popup(frame, centered_rect, action.title(), &app.pending_action_message());
+    }
+
+    if app.pending_spend.is_some() {
+        render_confirm_popup(frame, centered_rect, "Confirm Spending", &app.pending_spend_message());
+    }
+
+    if let Some((title, output)) = &app.action_output {
+        render_output_popup(frame, centered_rect, title, output);
     }
 
     if app.should_quit {
-        render_exit_popup(frame, centered_rect);
+        render_confirm_popup(frame, centered_rect, "Confirm Exit", "Do you really want to exit?");
     }
 }
 
@@ -152,54 +203,102 @@ fn render_user_blobs(frame: &mut Frame, app: &mut App, area: Rect) {
     let header_style = Style::default().fg(Color::LightCyan);
     let selected_style = Style::default().fg(Color::Black).bg(Color::White).bold();
 
-    if !app.user_blobs.is_empty() {
-        let header_cells = [
-            "Blob ID",
-            "Unencoded size",
-            "Certified",
-            "Deletable",
-            "Expiry epoch",
-            "Object ID",
-        ]
-        .iter()
-        .map(|&h| Cell::from(h).style(header_style))
-        .collect::<Vec<Cell>>();
-
-        let header = Row::new(header_cells).height(2);
-
-        let rows = app.user_blobs.iter().map(|data| {
-            let cells = [
-                data.blob_id.as_str(),
-                &data.unencoded_size.to_string(),
-                &data.is_certified.to_string(),
-                &data.is_deletable.to_string(),
-                &data.expiration_epoch.to_string(),
-                data.object_id.as_str(),
-            ]
+    if !app.user_blobs.is_empty() || !app.file_summaries.is_empty() {
+        let (header_titles, rows, widths, total) = match app.dashboard_view {
+            DashboardView::Blobs => (
+                vec![
+                    "Blob ID",
+                    "File",
+                    "Shard",
+                    "Encrypted",
+                    "Unencoded size",
+                    "Certified",
+                    "Deletable",
+                    "Expires",
+                    "Object ID",
+                ],
+                blob_rows(app),
+                vec![
+                    Constraint::Percentage(15),
+                    Constraint::Percentage(17),
+                    Constraint::Percentage(7),
+                    Constraint::Percentage(8),
+                    Constraint::Percentage(10),
+                    Constraint::Percentage(8),
+                    Constraint::Percentage(8),
+                    Constraint::Percentage(14),
+                    Constraint::Percentage(13),
+                ],
+                app.user_blobs.len(),
+            ),
+            DashboardView::Files => (
+                vec![
+                    "File",
+                    "Shards on chain",
+                    "Encrypted",
+                    "Total size",
+                    "Earliest expiry",
+                ],
+                file_rows(app),
+                vec![
+                    Constraint::Percentage(40),
+                    Constraint::Percentage(15),
+                    Constraint::Percentage(15),
+                    Constraint::Percentage(15),
+                    Constraint::Percentage(15),
+                ],
+                app.file_summaries.len(),
+            ),
+        };
+
+        let header_cells = header_titles
             .iter()
-            .map(|&content| Cell::from(truncate(content)))
+            .map(|&h| Cell::from(h).style(header_style))
             .collect::<Vec<Cell>>();
 
-            Row::new(cells)
-                .height(1)
-                .style(Style::default().fg(Color::Yellow))
-        });
+        let header = Row::new(header_cells).height(2);
 
-        let widths = &[
-            Constraint::Percentage(16),
-            Constraint::Percentage(16),
-            Constraint::Percentage(16),
-            Constraint::Percentage(16),
-            Constraint::Percentage(16),
-            Constraint::Percentage(16),
-        ];
+        let filter_summary = if app.is_editing {
+            format!("Search: {}_", app.blob_filter.search)
+        } else {
+            app.blob_filter.summary()
+        };
 
         let table = Table::new(rows, widths)
             .header(header)
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::LightCyan)),
+                    .border_style(Style::default().fg(Color::LightCyan))
+                    .title(
+                        Line::from(if app.dashboard_status.is_empty() {
+                            String::new()
+                        } else {
+                            format!(" {} ", app.dashboard_status)
+                        })
+                        .right_aligned(),
+                    )
+                    .title_bottom(
+                        Line::from(format!(
+                            " {} of {} {}{} {} ",
+                            app.visible_len(),
+                            total,
+                            match app.dashboard_view {
+                                DashboardView::Blobs => "blobs",
+                                DashboardView::Files => "files",
+                            },
+                            match app.mark_count() {
+                                0 => String::new(),
+                                count => format!(", {} selected", count),
+                            },
+                            if filter_summary.is_empty() {
+                                String::new()
+                            } else {
+                                format!("| {}", filter_summary)
+                            }
+                        ))
+                        .centered(),
+                    ),
             )
             .row_highlight_style(selected_style)
             .highlight_symbol(">> ");
@@ -226,48 +325,84 @@ fn render_user_blobs(frame: &mut Frame, app: &mut App, area: Rect) {
             .title("System Info")
             .title_alignment(Alignment::Center);
 
-        let system_info = Paragraph::new(app.walrus_system_info.clone())
+        let system_info = Paragraph::new(
+            app.walrus_info
+                .as_ref()
+                .map_or("System info unavailable".to_string(), system_summary),
+        )
             .block(system_info_block)
             .style(Style::default().fg(Color::Yellow))
             .alignment(Alignment::Left);
 
-        if let Some(blob) = app.user_blobs.get(app.table_state.selected().unwrap_or(0)) {
-            let blob_info = vec![
-                Line::from(Span::styled(
-                    format!("Blob ID: {}\n", blob.blob_id),
-                    Style::default().fg(Color::Yellow),
-                )),
-                Line::from(Span::styled(
-                    format!("Unencoded size: {}\n", blob.unencoded_size),
-                    Style::default().fg(Color::Yellow),
-                )),
-                Line::from(Span::styled(
-                    format!("Certified: {}\n", blob.is_certified),
-                    Style::default().fg(Color::Yellow),
-                )),
-                Line::from(Span::styled(
-                    format!("Deletable: {}\n", blob.is_deletable),
-                    Style::default().fg(Color::Yellow),
-                )),
-                Line::from(Span::styled(
-                    format!("Expiry epoch: {}\n", blob.expiration_epoch),
-                    Style::default().fg(Color::Yellow),
-                )),
-                Line::from(Span::styled(
-                    format!("Object ID: {}\n", blob.object_id),
-                    Style::default().fg(Color::Yellow),
-                )),
+        let details = if let Some(blob) = app.selected_blob() {
+            let location = app.blob_locations.get(&blob.blob_id);
+            let mut blob_info = vec![
+                format!("Blob ID: {}\n", blob.blob_id),
+                format!(
+                    "File: {}\n",
+                    location.map_or("-".to_string(), |l| l.file_path.clone())
+                ),
             ];
+            if let Some(location) = location {
+                blob_info.push(format!(
+                    "Shard: {}/{}\n",
+                    location.shard_index + 1,
+                    location.shard_count
+                ));
+                blob_info.push(format!("Encrypted: {}\n", location.is_encrypted));
+            }
+            blob_info.extend([
+                format!("Unencoded size: {}\n", format_size(blob.unencoded_size)),
+                format!("Certified: {}\n", blob.is_certified),
+                format!("Deletable: {}\n", blob.is_deletable),
+                format!(
+                    "Expiry epoch: {}, {}\n",
+                    blob.expiration_epoch,
+                    describe_expiry(blob.expiration_epoch, app.walrus_info.as_ref())
+                ),
+                format!("Object ID: {}\n", blob.object_id),
+            ]);
+            Some(("Blob Info", blob_info))
+        } else {
+            app.selected_file().map(|file| {
+                (
+                    "File Info",
+                    vec![
+                        format!("File: {}\n", file.file_path),
+                        format!(
+                            "Shards on chain: {}/{}\n",
+                            file.live_shards, file.shard_count
+                        ),
+                        format!("Encrypted: {}\n", file.is_encrypted),
+                        format!("Total size: {}\n", format_size(file.total_size)),
+                        format!(
+                            "Earliest expiry epoch: {}\n",
+                            file.earliest_expiry.map_or("-".to_string(), |epoch| format!(
+                                "{}, {}",
+                                epoch,
+                                describe_expiry(epoch, app.walrus_info.as_ref())
+                            ))
+                        ),
+                    ],
+                )
+            })
+        };
+
+        if let Some((details_title, details)) = details {
+            let details = details
+                .into_iter()
+                .map(|line| Line::from(Span::styled(line, Style::default().fg(Color::Yellow))))
+                .collect::<Vec<Line>>();
 
             let blob_block = Block::default()
                 .borders(Borders::ALL)
-                .title("Blob Info")
+                .title(details_title)
                 .title_alignment(Alignment::Center)
                 .border_type(BorderType::Rounded)
                 .style(Style::default().fg(Color::LightCyan))
                 .padding(Padding::new(1, 1, 1, 1));
 
-            let blob_paragraph = Paragraph::new(blob_info)
+            let blob_paragraph = Paragraph::new(details)
                 .block(blob_block)
                 .alignment(Alignment::Left)
                 .wrap(Wrap { trim: false });
@@ -286,6 +421,93 @@ fn render_user_blobs(frame: &mut Frame, app: &mut App, area: Rect) {
     }
 }
 
+fn blob_rows(app: &App) -> Vec<Row<'static>> {
+    app.visible_blobs
+        .iter()
+        .map(|&i| {
+            let data = &app.user_blobs[i];
+            let location = app.blob_locations.get(&data.blob_id);
+            let marked = app.marked_blobs.contains(&data.blob_id);
+
+            let mut cells = [
+                format!("{}{}", if marked { "✓ " } else { "" }, data.blob_id),
+                location.map_or("-".to_string(), |l| l.file_path.clone()),
+                location.map_or("-".to_string(), |l| {
+                    format!("{}/{}", l.shard_index + 1, l.shard_count)
+                }),
+                location.map_or("-".to_string(), |l| l.is_encrypted.to_string()),
+                format_size(data.unencoded_size),
+                data.is_certified.to_string(),
+                data.is_deletable.to_string(),
+                expiry_cell_text(app, data.expiration_epoch),
+                data.object_id.clone(),
+            ]
+            .iter()
+            .map(|content| Cell::from(truncate(content)))
+            .collect::<Vec<Cell>>();
+            cells[7] = cells[7].clone().style(expiry_style(app, data.expiration_epoch));
+
+            Row::new(cells)
+                .height(1)
+                .style(Style::default().fg(if marked { Color::LightGreen } else { Color::Yellow }))
+        })
+        .collect()
+}
+
+fn file_rows(app: &App) -> Vec<Row<'static>> {
+    app.visible_files
+        .iter()
+        .map(|&i| {
+            let file = &app.file_summaries[i];
+            let marked = app.marked_files.contains(&file.file_path);
+
+            let mut cells = [
+                format!("{}{}", if marked { "✓ " } else { "" }, file.file_path),
+                format!("{}/{}", file.live_shards, file.shard_count),
+                file.is_encrypted.to_string(),
+                format_size(file.total_size),
+                file.earliest_expiry
+                    .map_or("-".to_string(), |epoch| expiry_cell_text(app, epoch)),
+            ]
+            .into_iter()
+            .map(Cell::from)
+            .collect::<Vec<Cell>>();
+            if let Some(epoch) = file.earliest_expiry {
+                cells[4] = cells[4].clone().style(expiry_style(app, epoch));
+            }
+
+            let color = if marked {
+                Color::LightGreen
+            } else if file.live_shards < file.shard_count {
+                Color::Red
+            } else {
+                Color::Yellow
+            };
+
+            Row::new(cells).height(1).style(Style::default().fg(color))
+        })
+        .collect()
+}
+
+// Time left in a table cell, e.g. "in 5 epochs (~10 days)"
+fn expiry_cell_text(app: &App, expiration_epoch: u64) -> String {
+    describe_expiry(expiration_epoch, app.walrus_info.as_ref()).replace("expires ", "")
+}
+
+fn expiry_style(app: &App, expiration_epoch: u64) -> Style {
+    let Some(info) = &app.walrus_info else {
+        return Style::default();
+    };
+
+    let threshold = app.walrus_client.config.renew_epoch_threshold as u64;
+    match expiry_level(expiration_epoch, info, threshold) {
+        ExpiryLevel::Expired => Style::default().fg(Color::DarkGray),
+        ExpiryLevel::Critical => Style::default().fg(Color::Red).bold(),
+        ExpiryLevel::Warning => Style::default().fg(Color::LightRed),
+        ExpiryLevel::Healthy => Style::default().fg(Color::Green),
+    }
+}
+
 fn render_scrollbar(frame: &mut Frame, app: &mut App, area: Rect) {
     frame.render_stateful_widget(
         Scrollbar::default()
@@ -327,7 +549,7 @@ fn render_uploader(frame: &mut Frame, app: &mut App, area: Rect) {
         ])
         .split(chunks[0]);
 
-    let filename_text = format!("File path: {}", app.filename);
+    let filename_text = input_line("File path: ", &app.filename, app.is_editing);
     let filename_widget = Paragraph::new(filename_text)
         .style(Style::default().fg(Color::Cyan))
         .block(
@@ -339,22 +561,27 @@ fn render_uploader(frame: &mut Frame, app: &mut App, area: Rect) {
 
     frame.render_widget(filename_widget, left[0]);
 
-    let file_exists = std::path::Path::new(&app.filename).exists();
-    let file_info_text = if file_exists && !app.filename.is_empty() && !app.is_editing {
-        "File exists"
-    } else {
-        "File does not exist"
+    let file_info_text = match app.file_size {
+        Some(size) => format!("File exists ({})", format_size(size)),
+        None => "File does not exist".to_string(),
     };
 
-    let file_info_widget =
-        Paragraph::new(format!("{}, {}", file_info_text, &app.file_upload_status))
-            .style(Style::default().fg(Color::Cyan))
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .style(Style::default().fg(Color::Cyan)),
-            )
-            .alignment(Alignment::Center);
+    let mut file_info_lines = vec![Line::from(format!(
+        "{}, {}",
+        file_info_text, &app.file_upload_status
+    ))];
+    if let Some(estimates) = app.current_upload_estimate() {
+        file_info_lines.extend(cost_lines(&estimates, app.epochs));
+    }
+
+    let file_info_widget = Paragraph::new(Text::from(file_info_lines))
+        .style(Style::default().fg(Color::Cyan))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .style(Style::default().fg(Color::Cyan)),
+        )
+        .alignment(Alignment::Center);
 
     frame.render_widget(file_info_widget, left[1]);
 
@@ -364,36 +591,25 @@ fn render_uploader(frame: &mut Frame, app: &mut App, area: Rect) {
 fn render_migrator(frame: &mut Frame, app: &mut App, area: Rect) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Percentage(90), Constraint::Percentage(10)])
+        .constraints([Constraint::Min(1), Constraint::Percentage(10)])
         .split(area);
-    let content_area = chunks[0];
     let footer_area = chunks[1];
 
-    // Center the migrator content
-    let centered_area = centered_rect(60, 70, content_area);
-    let migrator_block = Block::default()
-        .title("Get API key from Pinata")
-        .title_alignment(Alignment::Center)
-        .borders(Borders::ALL)
-        .border_type(BorderType::Rounded)
-        .style(Style::default().fg(Color::Cyan));
-
-    frame.render_widget(migrator_block.clone(), centered_area);
-
     let content_chunks = Layout::default()
         .direction(Direction::Vertical)
-        .margin(2)
-        .constraints([Constraint::Percentage(80), Constraint::Percentage(20)])
-        .split(centered_area);
-
-    let inner_content_chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .spacing(2)
-        .constraints([Constraint::Length(5), Constraint::Length(5)])
-        .split(content_chunks[0]);
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Min(1),
+            Constraint::Length(3),
+        ])
+        .split(chunks[0].inner(Margin {
+            horizontal: 2,
+            vertical: 1,
+        }));
 
     let api_key_block = Block::default()
-        .title("Enter Pinata API Key")
+        .title("Pinata API Key")
         .title_alignment(Alignment::Center)
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
@@ -401,26 +617,139 @@ fn render_migrator(frame: &mut Frame, app: &mut App, area: Rect) {
 
     let api_key_paragraph = Paragraph::new(app.pinata_api_key.clone())
         .block(api_key_block)
-        .alignment(Alignment::Left)
-        .wrap(Wrap { trim: false });
+        .alignment(Alignment::Left);
 
-    let migration_status_block = Block::default()
-        .title("Migration Status")
-        .title_alignment(Alignment::Center)
-        .borders(Borders::ALL)
-        .border_type(BorderType::Rounded)
-        .style(Style::default().fg(Color::Green));
+    let batch = app.migration_batch();
+    let batch_cost: u64 = batch
+        .iter()
+        .filter_map(|planned| planned.estimated_cost)
+        .sum();
+    let batch_line = if app.migration_plan.files.is_empty() {
+        "every file".to_string()
+    } else {
+        format!(
+            "{} files, estimated {}",
+            batch.len(),
+            format_cost(batch_cost)
+        )
+    };
+    let options_text = format!(
+        "Epochs: {} | Shard size: {} | Encryption: {} | Batch: {}",
+        app.migration_epochs,
+        format_size(app.migration_shard_size as u64),
+        if app.migration_encrypt { "on" } else { "off" },
+        batch_line
+    );
+    let options_paragraph = Paragraph::new(options_text).block(
+        Block::default()
+            .title("Batch Options")
+            .title_alignment(Alignment::Center)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .style(Style::default().fg(Color::Cyan)),
+    );
+
+    let table_area = content_chunks[2];
+    if app.migration_plan.files.is_empty() {
+        let text = Text::from(
+            "\n\nPaste a Pinata API key and press [L] to list its files,\nor [M] to migrate all of them.",
+        );
+        let paragraph = Paragraph::new(text).alignment(Alignment::Center);
+        frame.render_widget(paragraph, table_area);
+    } else {
+        let header_style = Style::default().fg(Color::LightCyan);
+        let selected_style = Style::default().fg(Color::Black).bg(Color::White).bold();
+
+        let header_cells = ["", "Name", "Size", "Added", "CID", "Est. cost", "Status"]
+            .iter()
+            .map(|&h| Cell::from(h).style(header_style))
+            .collect::<Vec<Cell>>();
+        let header = Row::new(header_cells).height(2);
+
+        let rows = app.visible_migration_files.iter().map(|&i| {
+            let planned = &app.migration_plan.files[i];
+            let checked = app.checked_migration_files.contains(&planned.file.id);
+            let color = if planned.migrated {
+                Color::DarkGray
+            } else if checked {
+                Color::LightGreen
+            } else {
+                Color::White
+            };
+
+            Row::new(vec![
+                Cell::from(if checked { "[✓]" } else { "[ ]" }),
+                Cell::from(planned.file.name.clone()),
+                Cell::from(planned.file.size.map(format_size).unwrap_or_default()),
+                Cell::from(
+                    planned
+                        .file
+                        .created_at
+                        .as_deref()
+                        .map(|date| date.chars().take(10).collect::<String>())
+                        .unwrap_or_default(),
+                ),
+                Cell::from(planned.file.cid.clone().unwrap_or_default()),
+                Cell::from(planned.estimated_cost.map(format_wal).unwrap_or_default()),
+                Cell::from(if planned.migrated { "migrated" } else { "" }),
+            ])
+            .height(1)
+            .style(Style::default().fg(color))
+        });
+
+        let widths = &[
+            Constraint::Length(3),
+            Constraint::Percentage(25),
+            Constraint::Length(11),
+            Constraint::Length(10),
+            Constraint::Percentage(35),
+            Constraint::Length(16),
+            Constraint::Length(8),
+        ];
+
+        let filter_summary = app.migration_filter.summary();
+        let title = format!(
+            "Files on Pinata: {} shown of {}, {} checked{}",
+            app.visible_migration_files.len(),
+            app.migration_plan.files.len(),
+            app.checked_migration_files.len(),
+            if filter_summary.is_empty() {
+                String::new()
+            } else {
+                format!(" | {}", filter_summary)
+            }
+        );
+
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::LightCyan))
+                    .title(title)
+                    .title_alignment(Alignment::Center),
+            )
+            .row_highlight_style(selected_style)
+            .highlight_symbol(">> ");
+
+        frame.render_stateful_widget(table, table_area, &mut app.migration_table_state);
+    }
 
     let migration_status_paragraph = Paragraph::new(app.migration_status.clone())
-        .block(migration_status_block)
-        .alignment(Alignment::Left)
-        .wrap(Wrap { trim: false });
+        .block(
+            Block::default()
+                .title("Migration Status")
+                .title_alignment(Alignment::Center)
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .style(Style::default().fg(Color::Green)),
+        )
+        .alignment(Alignment::Left);
 
-    // Render the widgets
-    frame.render_widget(api_key_paragraph, inner_content_chunks[0]);
-    frame.render_widget(migration_status_paragraph, inner_content_chunks[1]);
+    frame.render_widget(api_key_paragraph, content_chunks[0]);
+    frame.render_widget(options_paragraph, content_chunks[1]);
+    frame.render_widget(migration_status_paragraph, content_chunks[3]);
 
-    // Render the footer
     render_footer(frame, app, footer_area);
 }
 
@@ -456,10 +785,25 @@ fn render_sharder_and_extender(frame: &mut Frame, app: &mut App, area: Rect) {
         .split(extender_area);
 
     let sharder_title = "Sharder";
-    let sharder_content = format!("File to shard: {}", app.filename);
+    let mut sharder_lines = vec![
+        input_line("File to shard: ", &app.filename, app.is_editing),
+        Line::from(match app.file_size {
+            Some(size) => format!("File exists ({})", format_size(size)),
+            None => "File does not exist".to_string(),
+        }),
+    ];
+    if let Some(estimates) = app.current_upload_estimate() {
+        let epochs = app.walrus_client.config.get_default_epochs();
+        sharder_lines.extend(cost_lines(&estimates, epochs));
+    }
+    let sharder_content = Text::from(sharder_lines);
     let sharder_status = match app.sharder_status.as_str() {
-        "success" => Paragraph::new("Sharding succeeded").style(Style::default().fg(Color::Green)),
-        "failure" => Paragraph::new("Sharding failed").style(Style::default().fg(Color::Red)),
+        status if status.starts_with("Sharding failed") => {
+            Paragraph::new(status).style(Style::default().fg(Color::Red))
+        }
+        status if status == "Sharding succeeded" || status.ends_with(" uploaded") => {
+            Paragraph::new(status).style(Style::default().fg(Color::Green))
+        }
         _ => Paragraph::new("").style(Style::default().fg(Color::Yellow)),
     };
     let sharder_block = Block::default()
@@ -474,10 +818,32 @@ fn render_sharder_and_extender(frame: &mut Frame, app: &mut App, area: Rect) {
     frame.render_widget(sharder_status, sharder_chunks[1]);
 
     let extender_title = "Epoch Extender";
-    let extender_content = format!("BlobID to epoch extend: {}", app.extender_blob_id);
+    let mut extender_lines = vec![Line::from(format!(
+        "BlobID to epoch extend: {}",
+        app.extender_blob_id
+    ))];
+    if let Some(blob) = app.extender_blob() {
+        extender_lines.push(Line::from(format!(
+            "{}, {}",
+            format_size(blob.unencoded_size),
+            describe_expiry(blob.expiration_epoch, app.walrus_info.as_ref())
+        )));
+        if let Some(cost) = app.extension_estimate(std::slice::from_ref(blob)) {
+            extender_lines.push(Line::from(format!(
+                "Estimated cost for {} more epochs: {}",
+                app.epochs,
+                format_cost(cost)
+            )));
+        }
+    }
+    let extender_content = Text::from(extender_lines);
     let extender_status = match app.extender_status.as_str() {
-        "success" => Paragraph::new("Extension succeeded").style(Style::default().fg(Color::Green)),
-        "failure" => Paragraph::new("Extension failed").style(Style::default().fg(Color::Red)),
+        status if status.starts_with("Extension failed") => {
+            Paragraph::new(status).style(Style::default().fg(Color::Red))
+        }
+        status if status == "Extension succeeded" || status.starts_with("Extended") => {
+            Paragraph::new(status).style(Style::default().fg(Color::Green))
+        }
         _ => Paragraph::new("").style(Style::default().fg(Color::Yellow)),
     };
     let extender_block = Block::default()
@@ -494,7 +860,416 @@ fn render_sharder_and_extender(frame: &mut Frame, app: &mut App, area: Rect) {
     render_footer(frame, app, chunks[1]);
 }
 
-fn render_exit_popup(frame: &mut Frame, area: Rect) {
+// Shards listed one per line before the rest are folded into a count
+const MAX_COST_LINES: usize = 5;
+
+fn cost_lines(estimates: &[CostEstimate], epochs: u16) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+
+    if estimates.len() > 1 {
+        for (i, shard) in estimates.iter().take(MAX_COST_LINES).enumerate() {
+            lines.push(Line::from(format!(
+                "Shard {}: {} -> {} encoded, {}",
+                i + 1,
+                format_size(shard.unencoded_size),
+                format_size(shard.encoded_size),
+                format_cost(shard.total())
+            )));
+        }
+        if estimates.len() > MAX_COST_LINES {
+            lines.push(Line::from(format!(
+                "...and {} more shards",
+                estimates.len() - MAX_COST_LINES
+            )));
+        }
+    }
+
+    let encoded: u64 = estimates.iter().map(|shard| shard.encoded_size).sum();
+    lines.push(
+        Line::from(format!(
+            "Estimated cost for {} epochs: {} ({} encoded)",
+            epochs,
+            format_cost(total_cost(estimates)),
+            format_size(encoded)
+        ))
+        .style(Style::default().fg(Color::Yellow)),
+    );
+    lines
+}
+
+fn render_jobs(frame: &mut Frame, app: &mut App, area: Rect) {
+    let watch_lines = watch_folder_lines(app);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(1),
+            Constraint::Length(watch_lines.len() as u16 + 2),
+            Constraint::Percentage(10),
+        ])
+        .split(area);
+
+    let table_area = chunks[0].inner(Margin {
+        horizontal: 2,
+        vertical: 1,
+    });
+
+    if app.jobs.jobs.is_empty() {
+        let text = Text::from("\n\n\nNo jobs yet.");
+        let paragraph = Paragraph::new(text).alignment(Alignment::Center);
+        frame.render_widget(paragraph, table_area);
+    } else {
+        let header_style = Style::default().fg(Color::LightCyan);
+        let selected_style = Style::default().fg(Color::Black).bg(Color::White).bold();
+
+        let header_cells = ["#", "Job", "Status"]
+            .iter()
+            .map(|&h| Cell::from(h).style(header_style))
+            .collect::<Vec<Cell>>();
+        let header = Row::new(header_cells).height(2);
+
+        let rows = app.jobs.jobs.iter().map(|job| {
+            let (status, color) = match &job.status {
+                JobStatus::Queued => ("Queued".to_string(), Color::Gray),
+                JobStatus::Running => match &job.progress {
+                    Some(progress) => (format!("Running: {}", progress), Color::Yellow),
+                    None => ("Running...".to_string(), Color::Yellow),
+                },
+                JobStatus::Finished(message) => (format!("Done: {}", message), Color::Green),
+                JobStatus::Failed(e) => (format!("Failed: {}", e), Color::Red),
+                JobStatus::Cancelled => ("Cancelled".to_string(), Color::Magenta),
+            };
+
+            Row::new(vec![
+                Cell::from(job.id.to_string()),
+                Cell::from(job.name.clone()),
+                Cell::from(status),
+            ])
+            .height(1)
+            .style(Style::default().fg(color))
+        });
+
+        let widths = &[
+            Constraint::Length(5),
+            Constraint::Percentage(45),
+            Constraint::Percentage(50),
+        ];
+
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::LightCyan))
+                    .title(format!("Running / queued: {}", app.jobs.active_count()))
+                    .title_alignment(Alignment::Center),
+            )
+            .row_highlight_style(selected_style)
+            .highlight_symbol(">> ");
+
+        frame.render_stateful_widget(table, table_area, &mut app.jobs_table_state);
+    }
+
+    let watch_area = chunks[1].inner(Margin {
+        horizontal: 2,
+        vertical: 0,
+    });
+    let watch_widget = Paragraph::new(Text::from(watch_lines)).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::LightCyan))
+            .title("Watch Folders")
+            .title_alignment(Alignment::Center),
+    );
+    frame.render_widget(watch_widget, watch_area);
+
+    render_footer(frame, app, chunks[2]);
+}
+
+fn watch_folder_lines(app: &App) -> Vec<Line<'static>> {
+    let config = &app.walrus_client.config;
+    let folders = config.get_watch_folders();
+    if folders.is_empty() {
+        return vec![Line::from(
+            "No watch folders, add them to watch_folders in ~/.walter/config.json",
+        )];
+    }
+
+    let status = match &app.folder_watcher {
+        Some(watcher) => Line::from(format!(
+            "Watching, {} file(s) settling. {}",
+            watcher.pending_count(),
+            app.watch_status
+        ))
+        .style(Style::default().fg(Color::Green)),
+        None => Line::from(format!("Not watching. {}", app.watch_status))
+            .style(Style::default().fg(Color::Gray)),
+    };
+
+    let mut lines = vec![status];
+    lines.extend(folders.iter().map(|folder| {
+        Line::from(format!(
+            "{}{}: {} epochs, {} shards{}, settles after {}s",
+            folder.path,
+            if folder.recursive { " (recursive)" } else { "" },
+            folder.get_epochs(config),
+            format_size(folder.get_shard_size(config) as u64),
+            if folder.encrypt { ", encrypted" } else { "" },
+            folder.settle_secs
+        ))
+    }));
+    lines
+}
+
+fn render_downloader(frame: &mut Frame, app: &mut App, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(90), Constraint::Percentage(10)])
+        .split(area);
+
+    let content_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(chunks[0].inner(Margin {
+            horizontal: 2,
+            vertical: 1,
+        }));
+
+    let right_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Min(1),
+        ])
+        .split(content_chunks[1]);
+
+    let focus_style = |focus: DownloadFocus| {
+        if app.download_focus == focus {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default().fg(Color::Cyan)
+        }
+    };
+
+    let files = app
+        .file_summaries
+        .iter()
+        .map(|file| {
+            ListItem::new(format!(
+                "{} ({} shards{})",
+                file.file_path,
+                file.shard_count,
+                if file.is_encrypted { ", encrypted" } else { "" }
+            ))
+        })
+        .collect::<Vec<ListItem>>();
+
+    let files_list = List::new(files)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .style(focus_style(DownloadFocus::Files))
+                .title("Registered Files")
+                .title_alignment(Alignment::Center),
+        )
+        .highlight_style(Style::default().fg(Color::Black).bg(Color::White).bold())
+        .highlight_symbol(">> ");
+
+    frame.render_stateful_widget(files_list, content_chunks[0], &mut app.download_list_state);
+
+    let editing_destination = app.is_editing && app.download_focus == DownloadFocus::Destination;
+    let editing_blob_id = app.is_editing && !editing_destination;
+
+    let blob_id = Paragraph::new(input_line("", &app.download_blob_id, editing_blob_id)).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .style(focus_style(DownloadFocus::BlobId))
+            .title("Blob ID (overrides the selected file)"),
+    );
+    frame.render_widget(blob_id, right_chunks[0]);
+
+    let destination = Paragraph::new(input_line("", &app.download_dest, editing_destination))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .style(focus_style(DownloadFocus::Destination))
+                .title("Destination"),
+        );
+    frame.render_widget(destination, right_chunks[1]);
+
+    let status = Paragraph::new(app.download_progress()).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .style(Style::default().fg(Color::Green))
+            .title("Status"),
+    );
+    frame.render_widget(status, right_chunks[2]);
+
+    let results = app
+        .download_results
+        .iter()
+        .map(|result| {
+            let (marker, color) = if result.passed {
+                ("✓", Color::Green)
+            } else {
+                ("✗", Color::Red)
+            };
+            ListItem::new(format!("{} {}", marker, result.message))
+                .style(Style::default().fg(color))
+        })
+        .collect::<Vec<ListItem>>();
+
+    let results_list = List::new(results).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .style(Style::default().fg(Color::Cyan))
+            .title("Verification"),
+    );
+    frame.render_widget(results_list, right_chunks[3]);
+
+    render_footer(frame, app, chunks[1]);
+}
+
+fn render_file_picker(frame: &mut Frame, picker: &mut FilePicker, area: Rect) {
+    let popup_area = centered_rect(70, 70, area);
+    frame.render_widget(Clear, popup_area);
+
+    let items = picker
+        .visible_entries()
+        .iter()
+        .map(|entry| {
+            let marker = if picker.selected.contains(&entry.path) {
+                "[x] "
+            } else if picker.multi_select && !entry.is_dir {
+                "[ ] "
+            } else {
+                "    "
+            };
+
+            if entry.is_dir {
+                ListItem::new(format!("{}{}/", marker, entry.name))
+                    .style(Style::default().fg(Color::LightCyan))
+            } else {
+                ListItem::new(format!(
+                    "{}{:<50} {:>12}",
+                    marker,
+                    entry.name,
+                    format_size(entry.size)
+                ))
+                .style(Style::default().fg(Color::Yellow))
+            }
+        })
+        .collect::<Vec<ListItem>>();
+
+    let preview = match (&picker.error, picker.highlighted()) {
+        (Some(e), _) => format!(" {} ", e),
+        (None, Some(entry)) if entry.is_dir => " Directory ".to_string(),
+        (None, Some(entry)) => format!(" {} ", format_size(entry.size)),
+        (None, None) => " No matching files ".to_string(),
+    };
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .style(Style::default().fg(Color::Cyan))
+                .title(format!(" {}/{} ", picker.cwd.display(), picker.query))
+                .title_alignment(Alignment::Left)
+                .title_bottom(Line::from(preview).right_aligned())
+                .title_bottom(
+                    Line::from(format!(
+                        " [Enter] Open/Choose | [Tab] Complete | [Space] Select ({}) | [Backspace/Left] Up | [ESC] Cancel ",
+                        picker.selected.len()
+                    ))
+                    .left_aligned(),
+                ),
+        )
+        .highlight_style(Style::default().fg(Color::Black).bg(Color::White).bold())
+        .highlight_symbol(">> ");
+
+    frame.render_stateful_widget(list, popup_area, &mut picker.list_state);
+}
+
+/// Renders a text field after its label, with the cursor shown while it has focus.
+fn input_line(label: &str, input: &TextInput, focused: bool) -> Line<'static> {
+    let text: Vec<char> = input.display_value().chars().collect();
+    let mut spans = vec![Span::raw(label.to_string())];
+
+    if !focused {
+        spans.push(Span::raw(text.iter().collect::<String>()));
+        return Line::from(spans);
+    }
+
+    let cursor = input.cursor().min(text.len());
+    let under_cursor = text.get(cursor).map(char::to_string).unwrap_or(" ".into());
+
+    spans.push(Span::raw(text[..cursor].iter().collect::<String>()));
+    spans.push(Span::styled(
+        under_cursor,
+        Style::default().add_modifier(Modifier::REVERSED),
+    ));
+    if cursor < text.len() {
+        spans.push(Span::raw(text[cursor + 1..].iter().collect::<String>()));
+    }
+
+    Line::from(spans)
+}
+
+fn render_password_prompt(frame: &mut Frame, prompt: &PasswordPrompt, area: Rect) {
+    let popup_width = 40;
+    let popup_height = 30;
+    let outer_rect = centered_rect(popup_width + 2, popup_height + 2, area);
+    let inner_rect = centered_rect(popup_width, popup_height, area);
+
+    frame.render_widget(Clear, outer_rect);
+
+    let popup_block = Block::default()
+        .borders(Borders::ALL)
+        .style(Style::default().bg(Color::Cyan).fg(Color::Black))
+        .bold()
+        .title_alignment(Alignment::Center)
+        .border_type(BorderType::Rounded)
+        .title(prompt.title);
+
+    let mut lines = vec![
+        Line::from(""),
+        input_line("Password: ", &prompt.password, !prompt.confirming),
+    ];
+    if prompt.require_confirmation {
+        lines.push(input_line(
+            "Confirm:  ",
+            &prompt.confirmation,
+            prompt.confirming,
+        ));
+    }
+    lines.push(Line::from(""));
+    lines.push(match &prompt.error {
+        Some(e) => Line::styled(e.clone(), Style::default().fg(Color::Red)),
+        None => Line::from(""),
+    });
+    lines.push(Line::from(if prompt.require_confirmation {
+        "[Enter] Next/Confirm | [Tab] Switch | [ESC] Cancel"
+    } else {
+        "[Enter] Confirm | [ESC] Cancel"
+    }));
+
+    let prompt_paragraph = Paragraph::new(lines)
+        .block(popup_block)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: false });
+
+    frame.render_widget(prompt_paragraph, inner_rect);
+}
+
+/// Yes/no popup used to confirm quitting and blob actions.
+fn render_confirm_popup(frame: &mut Frame, area: Rect, title: &str, message: &str) {
     let popup_width = 40;
     let popup_height = 30;
     let outer_rect = centered_rect(popup_width + 2, popup_height + 2, area);
@@ -508,44 +1283,69 @@ fn render_exit_popup(frame: &mut Frame, area: Rect) {
         .bold()
         .title_alignment(Alignment::Center)
         .border_type(BorderType::Rounded)
-        .title("Confirm Exit");
+        .title(title.to_string());
 
-    let exit_text = Text::styled(
-        "\n\nDo you really want to exit? \n\n [Y]es / [N]o",
+    let confirm_text = Text::styled(
+        format!("\n\n{} \n\n [Y]es / [N]o", message),
         Style::default()
             .fg(Color::White)
             .add_modifier(Modifier::BOLD),
     );
 
-    let exit_paragraph = Paragraph::new(exit_text)
+    let confirm_paragraph = Paragraph::new(confirm_text)
         .block(popup_block)
         .alignment(Alignment::Center)
         .wrap(Wrap { trim: false });
 
-    frame.render_widget(exit_paragraph, inner_rect);
+    frame.render_widget(confirm_paragraph, inner_rect);
+}
+
+fn render_output_popup(frame: &mut Frame, area: Rect, title: &str, output: &str) {
+    let popup_area = centered_rect(70, 70, area);
+    frame.render_widget(Clear, popup_area);
+
+    let output_block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .style(Style::default().fg(Color::Cyan))
+        .title(title.to_string())
+        .title_alignment(Alignment::Center)
+        .title_bottom(Line::from(" [ESC] Close ").centered());
+
+    let output_paragraph = Paragraph::new(output.to_string())
+        .block(output_block)
+        .style(Style::default().fg(Color::Yellow))
+        .wrap(Wrap { trim: false });
+
+    frame.render_widget(output_paragraph, popup_area);
 }
 
 fn render_footer(frame: &mut Frame, app: &mut App, area: Rect) {
     let instructions_block = Block::default().padding(Padding::vertical(1));
 
     let uploader_str = if app.is_editing {
-        format!("[1] Dashboard | [ESC] Exit Edit Mode | [Enter] Upload | [Up/Down] Epochs ({}) | [3] Migrator | [4] S&EE | [Q]uit", app.epochs)
+        format!("Type the file path | [Up/Down] History | [ESC] Exit Edit Mode | [Enter] Upload | [PgUp/PgDn] Epochs ({})", app.epochs)
     } else {
-        format!("[1] Dashboard | [E]dit Mode | [Enter] Upload | [Up/Down] Epochs ({}) | [3] Migrator | [4] S&EE | [Q]uit", app.epochs)
+        format!("[1] Dashboard | [E]dit Mode | [O]pen File | [Enter] Upload | [Up/Down] Epochs ({}) | [3] Migrator | [4] S&EE | [5] Jobs | [6] Download | [Q]uit", app.epochs)
     };
 
     let sharder_str = if app.is_editing {
-        format!("[1] Dashboard | [2] Uploader | [3] Migrator | [K] Shard | Encr[Y]pt | Epoch Ex[T]end | [ESC] Exit Edit Mode | [Q]uit")
+        "Type the file path | [Up/Down] History | [ESC] Exit Edit Mode".to_string()
     } else {
-        format!("[1] Dashboard | [2] Uploader | [3] Migrator | [K] Shard | Encr[Y]pt | [P]aste Blob ID | Epoch Ex[T]end | [E]dit Mode | [Q]uit")
+        "[1] Dashboard | [2] Uploader | [3] Migrator | [O]pen File | [K] Shard | Encr[Y]pt | [P]aste Blob ID | Epoch Ex[T]end | [5] Jobs | [6] Download | [E]dit Mode | [Q]uit".to_string()
     };
 
     let content = match app.current_screen {
         CurrentScreen::Splash => "Press 'Enter' to continue",
-        CurrentScreen::Dashboard => "[C]opy Blob ID | [2] Uploader | [3] Migrate | [4] Sharder & Epoch Extender | [Q]uit",
+        CurrentScreen::Dashboard if app.is_editing => "Type to search Blob and Object IDs | [Enter/ESC] Done",
+        CurrentScreen::Dashboard => "[C]opy Blob ID | [Space] Select | [A]ll | [M]atching | [U]nselect | Ex[T]end | [D]ownload | [Del]ete | [V]erify | [I]nfo | [O]pen | [G] Blobs/Files | [/] Search | [S]ort | [R]everse | [F]ilter Expiry | [X] Clear Filters | [2] Uploader | [3] Migrate | [4] S&EE | [5] Jobs | [6] Download | [Q]uit",
         CurrentScreen::Uploader => &uploader_str,
-        CurrentScreen::Migrator => "[1] Dashboard | [2] Uploader | [P]aste Key | [M]igrate | [4] Sharder & Epoch Extender | [Q]uit",
+        CurrentScreen::Migrator if app.is_editing => "Type to search names and CIDs | [Enter/ESC] Done",
+        CurrentScreen::Migrator => "[P]aste Key | [L]ist Files | [Space] Check | [A]ll | [/] Search | [S]ize | [D]ate | [R]eset Filters | [+/-] Epochs | Shard Size [K] | Encr[Y]pt | [M]igrate | [1] Dashboard | [5] Jobs | [Q]uit",
         CurrentScreen::SharderAndEpochExtender => &sharder_str,
+        CurrentScreen::Jobs => "[1] Dashboard | [2] Uploader | [3] Migrator | [4] S&EE | [6] Download | [Up/Down] Select | [C]ancel Job | [X] Clear Finished | [W]atch Folders | [Q]uit",
+        CurrentScreen::Downloader if app.is_editing => "Type the blob ID or destination | [Tab] Switch Field | [Enter] Download | [ESC] Exit Edit Mode",
+        CurrentScreen::Downloader => "[1] Dashboard | [Up/Down] Select File | [Tab] Switch Field | [E]dit Mode | [P]aste Blob ID | [X] Clear Blob ID | [Enter] Download | [5] Jobs | [Q]uit",
     };
 
     let instructions = Paragraph::new(Text::styled(content, Style::default().fg(Color::Green)))